@@ -0,0 +1,27 @@
+//! Samples for a few seconds, then prints findings and any threshold alerts.
+//!
+//! cargo run --example findings
+
+use system_monitor::{analyze_with_baselines, collect_history, evaluate_alerts, new_system, prime_cpu_usage, Baselines, Config};
+
+fn main() {
+    let mut config = Config::default();
+    config.monitoring.duration_secs = 3;
+    config.monitoring.sample_interval_secs = 1;
+
+    let mut sys = new_system();
+    prime_cpu_usage(&mut sys);
+    let history = collect_history(&mut sys, &config, || {});
+
+    let baselines = Baselines::load_configured(&config.baseline);
+    let analysis = analyze_with_baselines(&history, &baselines, &config);
+    for finding in analysis.security.findings.iter().chain(&analysis.recommendations) {
+        println!("[{}] {} ({})", finding.severity.label(), finding.summary, finding.id);
+    }
+
+    if let Some(latest) = history.last() {
+        for alert in evaluate_alerts(latest, &config).into_iter().filter(|alert| alert.active) {
+            println!("alert: {} - {}", alert.title, alert.body);
+        }
+    }
+}
@@ -0,0 +1,19 @@
+//! Writes an HTML health report built from a short sampling run.
+//!
+//! cargo run --example report -- report.html
+
+use system_monitor::{analyze, build_report, collect_history, new_system, prime_cpu_usage, render_report, Config, ReportFormat};
+
+fn main() {
+    let path = std::env::args().nth(1).unwrap_or_else(|| "report.html".to_string());
+    let mut config = Config::default();
+    config.monitoring.duration_secs = 5;
+
+    let mut sys = new_system();
+    prime_cpu_usage(&mut sys);
+    let history = collect_history(&mut sys, &config, || {});
+    let report = build_report(&sys, &history, analyze(&history, &config));
+
+    std::fs::write(&path, render_report(&report, ReportFormat::Html)).expect("write report");
+    println!("Wrote {path}");
+}
@@ -0,0 +1,13 @@
+//! Prints one metrics sample as JSON.
+//!
+//! cargo run --example snapshot
+
+use system_monitor::{collect_snapshot, new_system, prime_cpu_usage, MetricsScope};
+
+fn main() {
+    let mut sys = new_system();
+    prime_cpu_usage(&mut sys);
+
+    let snapshot = collect_snapshot(&mut sys, MetricsScope::Light);
+    println!("{}", serde_json::to_string_pretty(&snapshot).expect("metrics serialize"));
+}
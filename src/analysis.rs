@@ -1,44 +1,42 @@
-use crate::types::{SystemMetrics, UsageTrend, NetworkTrend, };
+use std::collections::{BTreeMap, VecDeque};
+use crate::config::{AnomalyConfig, AnomalyMode};
+use crate::history_query::Metric;
+use crate::types::{
+    AnomalyScore, Direction, Finding, FindingCategory, NetworkTrend, SeriesStats, Severity, Subject, SystemMetrics,
+    TrendDirection, TrendShape, UsageTrend,
+};
+
+/// Change over the window, as a share of the average, below which a series is flat.
+const FLAT_CHANGE: f64 = 0.05;
+/// Coefficients of variation separating steady, variable and volatile series.
+const STEADY_VOLATILITY: f64 = 0.1;
+const VOLATILE_VOLATILITY: f64 = 0.5;
+/// Peak-to-average ratio from which a series counts as spiky.
+const SPIKY: f64 = 2.0;
 
 pub fn analyze_cpu_trend(metrics_history: &[SystemMetrics]) -> Vec<UsageTrend> {
     let cpu_count = metrics_history[0].cpu_usage.len();
-    let mut trends = Vec::with_capacity(cpu_count);
-
-    for core in 0..cpu_count {
-        let usages: Vec<f32> = metrics_history.iter()
-            .map(|m| m.cpu_usage[core])
-            .collect();
-
-        let average = usages.iter().sum::<f32>() / usages.len() as f32;
-        let peak = usages.iter().cloned().fold(0f32, f32::max);
-        let pattern = calculate_usage_pattern(&usages);
-
-        trends.push(UsageTrend {
-            average: average as f64,
-            peak: peak as f64,
-            pattern,
-        });
-    }
-
-    trends
+    (0..cpu_count)
+        .map(|core| usage_trend(&series(metrics_history, |m| m.cpu_usage.get(core).map(|&u| f64::from(u))), 1.0))
+        .collect()
 }
 
+/// Used memory in bytes.
 pub fn analyze_memory_trend(metrics_history: &[SystemMetrics]) -> UsageTrend {
-    let usages: Vec<u64> = metrics_history.iter()
-        .map(|m| m.memory_usage)
-        .collect();
+    let total = metrics_history.last().map_or(0, |m| m.memory_total);
+    usage_trend(&series(metrics_history, |m| Some(m.memory_usage as f64)), total as f64 / 100.0)
+}
 
-    let average = usages.iter().sum::<u64>() / usages.len() as u64;
-    let peak = usages.iter().cloned().max().unwrap_or(0);
-    let pattern = calculate_usage_pattern(&usages.iter()
-        .map(|&u| u as f32)
-        .collect::<Vec<f32>>());
+/// Used swap in bytes.
+pub fn analyze_swap_trend(metrics_history: &[SystemMetrics]) -> UsageTrend {
+    let total = metrics_history.last().map_or(0, |m| m.swap_total);
+    usage_trend(&series(metrics_history, |m| Some(m.swap_usage as f64)), total as f64 / 100.0)
+}
 
-    UsageTrend {
-        average: average as f64,
-        peak: peak as f64,
-        pattern,
-    }
+/// CPU temperature in °C, or `None` without a sensor.
+pub fn analyze_temperature_trend(metrics_history: &[SystemMetrics]) -> Option<UsageTrend> {
+    let points = series(metrics_history, |m| m.temperature.cpu_temp.as_ref().map(|t| f64::from(t.celsius)));
+    (!points.is_empty()).then(|| usage_trend(&points, 1.0))
 }
 
 pub fn analyze_network_trend(metrics_history: &[SystemMetrics]) -> NetworkTrend {
@@ -48,52 +46,500 @@ pub fn analyze_network_trend(metrics_history: &[SystemMetrics]) -> NetworkTrend
 
     let total_rx: u64 = metrics_history.iter().map(|m| m.network_rx).sum();
     let total_tx: u64 = metrics_history.iter().map(|m| m.network_tx).sum();
+    let rx = rate_trend(metrics_history, Metric::NetworkRx);
+    let tx = rate_trend(metrics_history, Metric::NetworkTx);
 
     NetworkTrend {
         rx_rate: total_rx as f64 / duration,
         tx_rate: total_tx as f64 / duration,
+        rx_peak: rx.peak,
+        tx_peak: tx.peak,
+        rx: rx.shape,
+        tx: tx.shape,
+        rx_stats: rx.stats,
+        tx_stats: tx.stats,
+    }
+}
+
+/// Bytes read and written per second by all processes.
+pub fn analyze_disk_io_trend(metrics_history: &[SystemMetrics]) -> UsageTrend {
+    rate_trend(metrics_history, Metric::DiskIo)
+}
+
+fn rate_trend(metrics_history: &[SystemMetrics], metric: Metric) -> UsageTrend {
+    let Some(first) = metrics_history.first() else {
+        return usage_trend(&[], metric.noise_floor());
+    };
+    let points: Vec<(f64, f64)> = metric
+        .sample_values(metrics_history)
+        .into_iter()
+        .map(|(at, value)| (minutes_since(first, at), value))
+        .collect();
+    usage_trend(&points, metric.noise_floor())
+}
+
+/// `(minutes since the first sample, value)` for every sample that has a value.
+fn series(metrics_history: &[SystemMetrics], value: impl Fn(&SystemMetrics) -> Option<f64>) -> Vec<(f64, f64)> {
+    let Some(first) = metrics_history.first() else {
+        return Vec::new();
+    };
+    metrics_history
+        .iter()
+        .filter_map(|m| Some((minutes_since(first, m.recorded_at), value(m)?)))
+        .collect()
+}
+
+fn minutes_since(first: &SystemMetrics, at: std::time::SystemTime) -> f64 {
+    at.duration_since(first.recorded_at).unwrap_or_default().as_secs_f64() / 60.0
+}
+
+/// Average, peak, distribution and shape of `(minutes, value)` points. `floor` is the smallest change
+/// over the window that counts as rising or falling, and the smallest average volatility
+/// and spikiness are measured against, so a nearly idle series is not called volatile
+/// over noise.
+pub fn usage_trend(points: &[(f64, f64)], floor: f64) -> UsageTrend {
+    let (Some(&(first, _)), Some(&(last, _))) = (points.first(), points.last()) else {
+        return UsageTrend {
+            average: 0.0,
+            peak: 0.0,
+            stats: SeriesStats::default(),
+            shape: trend_shape(0.0, TrendDirection::Flat, 0.0, 0.0),
+        };
+    };
+    let n = points.len() as f64;
+    let average = points.iter().map(|(_, value)| value).sum::<f64>() / n;
+    let variance = points.iter().map(|(_, value)| (value - average).powi(2)).sum::<f64>() / n;
+    let mut sorted: Vec<f64> = points.iter().map(|&(_, value)| value).collect();
+    sorted.sort_by(f64::total_cmp);
+    let peak = sorted[sorted.len() - 1];
+    let stats = SeriesStats {
+        min: sorted[0],
+        p50: percentile(&sorted, 50.0),
+        p90: percentile(&sorted, 90.0),
+        p95: percentile(&sorted, 95.0),
+        p99: percentile(&sorted, 99.0),
+        std_dev: variance.sqrt(),
+    };
+    let scale = average.abs().max(floor).max(f64::EPSILON);
+
+    let slope = linear_fit(points).map_or(0.0, |fit| fit.slope);
+    let change = slope * (last - first);
+    let direction = if change.abs() < (FLAT_CHANGE * average.abs()).max(floor) {
+        TrendDirection::Flat
+    } else if change > 0.0 {
+        TrendDirection::Rising
+    } else {
+        TrendDirection::Falling
+    };
+
+    UsageTrend {
+        average,
+        peak,
+        stats,
+        shape: trend_shape(slope, direction, stats.std_dev / scale, peak / scale),
     }
 }
 
-pub fn classify_usage_pattern(pattern: f64) -> &'static str {
-    match pattern {
-        p if p < 0.2 => "Very Low",
-        p if p < 0.4 => "Low",
-        p if p < 0.6 => "Moderate",
-        p if p < 0.8 => "High",
-        _ => "Very High"
+fn trend_shape(slope_per_min: f64, direction: TrendDirection, volatility: f64, spikiness: f64) -> TrendShape {
+    let mut pattern = format!("{}, ", direction.label());
+    pattern.push_str(match volatility {
+        v if v < STEADY_VOLATILITY => "steady",
+        v if v < VOLATILE_VOLATILITY => "variable",
+        _ => "volatile",
+    });
+    if spikiness >= SPIKY {
+        pattern.push_str(", spiky");
     }
+    TrendShape { slope_per_min, direction, volatility, spikiness, pattern }
 }
 
-fn calculate_usage_pattern(values: &[f32]) -> f64 {
-    if values.is_empty() {
+/// Nearest-rank percentile of values sorted ascending; `p` is in `0..=100`.
+pub fn percentile(sorted: &[f64], p: f64) -> f64 {
+    if sorted.is_empty() {
         return 0.0;
     }
+    let rank = (p / 100.0 * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+/// What a detector expected the next value to be.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Expected {
+    pub value: f64,
+    /// Standard deviation, or its robust estimate in MAD mode.
+    pub spread: f64,
+}
 
-    let max = values.iter().cloned().fold(0f32, f32::max);
-    let min = values.iter().cloned().fold(f32::MAX, f32::min);
-    let avg = values.iter().sum::<f32>() / values.len() as f32;
+impl Expected {
+    /// Spreads between `value` and the expectation; negative when below it.
+    pub fn score(&self, value: f64) -> f64 {
+        (value - self.value) / self.spread
+    }
+}
 
-    let volatility = if max != min {
-        (max - min) / avg
-    } else {
-        0.0
-    };
+/// Streaming anomaly detector for one series: each value is scored against the values
+/// before it, then learned.
+#[derive(Debug, Clone)]
+pub struct AnomalyDetector {
+    mode: AnomalyMode,
+    alpha: f64,
+    window: usize,
+    warmup: usize,
+    min_spread: f64,
+    seen: usize,
+    mean: f64,
+    variance: f64,
+    recent: VecDeque<f64>,
+}
 
-    let mut trend = 0.0;
-    for window in values.windows(2) {
-        if window[1] > window[0] {
-            trend += 1.0;
-        } else if window[1] < window[0] {
-            trend -= 1.0;
+impl AnomalyDetector {
+    /// `min_spread` keeps a perfectly steady series from scoring jitter as huge deviations.
+    pub fn new(config: &AnomalyConfig, min_spread: f64) -> Self {
+        Self {
+            mode: config.mode,
+            alpha: config.alpha.clamp(f64::EPSILON, 1.0),
+            window: config.window.max(3),
+            warmup: config.warmup.max(2),
+            min_spread,
+            seen: 0,
+            mean: 0.0,
+            variance: 0.0,
+            recent: VecDeque::new(),
         }
     }
-    trend /= (values.len() - 1) as f32;
 
-    let result = ((volatility + trend.abs() + (avg / max)) / 3.0) as f64;
-    if result.is_nan() {
-        0.0
-    } else {
-        result
+    /// The expectation for the next value, once `warmup` values have been seen.
+    pub fn expected(&self) -> Option<Expected> {
+        if self.seen < self.warmup {
+            return None;
+        }
+        let (value, spread) = match self.mode {
+            AnomalyMode::Ewma => (self.mean, self.variance.sqrt()),
+            AnomalyMode::Mad => {
+                let mut recent: Vec<f64> = self.recent.iter().copied().collect();
+                recent.sort_by(f64::total_cmp);
+                let center = median(&recent);
+                let mut deviations: Vec<f64> = recent.iter().map(|value| (value - center).abs()).collect();
+                deviations.sort_by(f64::total_cmp);
+                // Scaled so the MAD estimates the standard deviation of normal data.
+                (center, 1.4826 * median(&deviations))
+            }
+        };
+        Some(Expected { value, spread: spread.max(self.min_spread) })
+    }
+
+    /// Scores `value` against what came before it, then learns it. `None` during warmup.
+    pub fn update(&mut self, value: f64) -> Option<(Expected, f64)> {
+        let scored = self.expected().map(|expected| (expected, expected.score(value)));
+        if self.seen == 0 {
+            self.mean = value;
+        } else {
+            let delta = value - self.mean;
+            self.mean += self.alpha * delta;
+            self.variance = (1.0 - self.alpha) * (self.variance + self.alpha * delta * delta);
+        }
+        self.seen += 1;
+        self.recent.push_back(value);
+        if self.recent.len() > self.window {
+            self.recent.pop_front();
+        }
+        scored
+    }
+}
+
+/// One [`AnomalyDetector`] per metric, fed one sample at a time.
+#[derive(Debug, Clone)]
+pub struct AnomalyDetectors {
+    config: AnomalyConfig,
+    detectors: BTreeMap<Metric, AnomalyDetector>,
+}
+
+impl AnomalyDetectors {
+    pub fn new(config: &AnomalyConfig) -> Self {
+        Self { config: config.clone(), detectors: BTreeMap::new() }
+    }
+
+    /// Feeds the latest sample in `metrics_history` to every metric's detector and returns
+    /// findings for the values at least `z_threshold` from what was expected.
+    pub fn observe(&mut self, metrics_history: &[SystemMetrics]) -> Vec<Finding> {
+        if !self.config.enabled {
+            return Vec::new();
+        }
+        let mut findings = Vec::new();
+        for metric in Metric::ALL {
+            let Some(value) = metric.latest_value(metrics_history) else {
+                continue;
+            };
+            let detector = self
+                .detectors
+                .entry(metric)
+                .or_insert_with(|| AnomalyDetector::new(&self.config, metric.noise_floor()));
+            if let Some((expected, score)) = detector.update(value) {
+                if score.abs() >= self.config.z_threshold {
+                    findings.push(anomaly_finding(
+                        format!("anomaly:{}", metric.name()),
+                        metric,
+                        value,
+                        expected,
+                        self.config.z_threshold,
+                        "from recent samples",
+                    ));
+                }
+            }
+        }
+        findings
+    }
+}
+
+/// Streams `metrics_history` through fresh detectors and returns the findings for its
+/// latest sample.
+pub fn detect_anomalies(metrics_history: &[SystemMetrics], config: &AnomalyConfig) -> Vec<Finding> {
+    let mut detectors = AnomalyDetectors::new(config);
+    let mut findings = Vec::new();
+    for end in 1..=metrics_history.len() {
+        findings = detectors.observe(&metrics_history[..end]);
+    }
+    findings
+}
+
+/// A warning for `value` lying `z_threshold` or more spreads from `expected`; `context`
+/// says where the expectation comes from.
+pub fn anomaly_finding(
+    id: String,
+    metric: Metric,
+    value: f64,
+    expected: Expected,
+    z_threshold: f64,
+    context: &str,
+) -> Finding {
+    let score = expected.score(value);
+    let direction = Direction::of(score);
+    let network = matches!(metric, Metric::NetworkRx | Metric::NetworkTx);
+    Finding {
+        id,
+        category: if network { FindingCategory::Network } else { FindingCategory::Anomaly },
+        severity: Severity::Warning,
+        subject: Subject::System,
+        summary: format!(
+            "{} at {}, expected {} ± {} {context} ({:.1}σ {})",
+            metric.label(),
+            metric.format_value(value),
+            metric.format_value(expected.value),
+            metric.format_value(expected.spread),
+            score.abs(),
+            direction.label(),
+        ),
+        value: Some(value),
+        threshold: Some(expected.value + score.signum() * z_threshold * expected.spread),
+        action: Some(if network {
+            "Check firewall settings and monitor connections for unauthorized access".to_string()
+        } else {
+            "Check which processes account for the change".to_string()
+        }),
+        anomaly: Some(AnomalyScore { score: score.abs(), direction }),
+    }
+}
+
+/// Least-squares line through a series of points.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LinearFit {
+    pub slope: f64,
+    pub intercept: f64,
+    /// Share of the variance in `y` the line explains (0-1).
+    pub r_squared: f64,
+}
+
+/// Fits `y = slope * x + intercept`. Needs two distinct `x` values.
+pub fn linear_fit(points: &[(f64, f64)]) -> Option<LinearFit> {
+    let n = points.len() as f64;
+    let mean_x = points.iter().map(|(x, _)| x).sum::<f64>() / n;
+    let mean_y = points.iter().map(|(_, y)| y).sum::<f64>() / n;
+    let (mut sxx, mut sxy, mut syy) = (0.0, 0.0, 0.0);
+    for (x, y) in points {
+        sxx += (x - mean_x) * (x - mean_x);
+        sxy += (x - mean_x) * (y - mean_y);
+        syy += (y - mean_y) * (y - mean_y);
+    }
+    if points.len() < 2 || sxx == 0.0 {
+        return None;
+    }
+    let slope = sxy / sxx;
+    Some(LinearFit {
+        slope,
+        intercept: mean_y - slope * mean_x,
+        r_squared: if syy == 0.0 { 1.0 } else { sxy * sxy / (sxx * syy) },
+    })
+}
+
+/// Theil-Sen line: the median of the slopes between every pair of points, so a few
+/// outliers (a large download that is deleted again) barely move it. Needs two distinct
+/// `x` values.
+pub fn theil_sen_fit(points: &[(f64, f64)]) -> Option<LinearFit> {
+    let mut slopes = Vec::with_capacity(points.len() * points.len().saturating_sub(1) / 2);
+    for (i, (x1, y1)) in points.iter().enumerate() {
+        for (x2, y2) in &points[i + 1..] {
+            if x2 != x1 {
+                slopes.push((y2 - y1) / (x2 - x1));
+            }
+        }
+    }
+    if slopes.is_empty() {
+        return None;
+    }
+    slopes.sort_by(f64::total_cmp);
+    let slope = median(&slopes);
+    let mut intercepts: Vec<f64> = points.iter().map(|(x, y)| y - slope * x).collect();
+    intercepts.sort_by(f64::total_cmp);
+    let intercept = median(&intercepts);
+
+    let mean_y = points.iter().map(|(_, y)| y).sum::<f64>() / points.len() as f64;
+    let total: f64 = points.iter().map(|(_, y)| (y - mean_y).powi(2)).sum();
+    let residual: f64 = points.iter().map(|(x, y)| (y - slope * x - intercept).powi(2)).sum();
+    Some(LinearFit {
+        slope,
+        intercept,
+        r_squared: if total == 0.0 { 1.0 } else { (1.0 - residual / total).max(0.0) },
+    })
+}
+
+/// Median of values sorted ascending.
+fn median(sorted: &[f64]) -> f64 {
+    match sorted.len() {
+        0 => 0.0,
+        n if n % 2 == 1 => sorted[n / 2],
+        n => (sorted[n / 2 - 1] + sorted[n / 2]) / 2.0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(secs: u64, cpu: f32) -> SystemMetrics {
+        SystemMetrics {
+            network_rx: 6000,
+            ..SystemMetrics::sample(secs, cpu)
+        }
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn trends_separate_direction_volatility_and_spikiness() {
+        let rising: Vec<_> = (0..30).map(|minute| sample(minute * 60, 20.0 + minute as f32)).collect();
+        let trend = &analyze_cpu_trend(&rising)[0];
+        assert_eq!(trend.shape.direction, TrendDirection::Rising);
+        assert!((trend.shape.slope_per_min - 1.0).abs() < 1e-9);
+        assert_eq!(trend.shape.pattern, "rising, variable");
+
+        let steady: Vec<_> = (0..30).map(|minute| sample(minute * 60, 50.0 + (minute % 2) as f32)).collect();
+        assert_eq!(analyze_cpu_trend(&steady)[0].shape.pattern, "flat, steady");
+
+        // Idle with one burst: flat overall, but volatile and spiky.
+        let bursty: Vec<_> = (0..30).map(|minute| sample(minute * 60, if minute == 15 { 90.0 } else { 5.0 })).collect();
+        let trend = &analyze_cpu_trend(&bursty)[0];
+        assert_eq!(trend.shape.direction, TrendDirection::Flat);
+        assert!(trend.shape.volatility > VOLATILE_VOLATILITY);
+        assert!(trend.shape.spikiness > 5.0);
+        assert_eq!(trend.shape.pattern, "flat, volatile, spiky");
+
+        let mut falling: Vec<_> = (0..30).map(|minute| sample(minute * 60, 0.0)).collect();
+        for (minute, metrics) in falling.iter_mut().enumerate() {
+            metrics.memory_usage = 90 - minute as u64;
+        }
+        assert_eq!(analyze_memory_trend(&falling).shape.direction, TrendDirection::Falling);
+        assert!(analyze_temperature_trend(&falling).is_none());
+        let network = analyze_network_trend(&falling);
+        assert_eq!(network.rx_peak, 100.0);
+        assert_eq!(network.rx.direction, TrendDirection::Flat);
+    }
+
+    #[test]
+    fn trends_carry_percentiles_min_and_std_dev() {
+        let history: Vec<_> = (0..100).map(|minute| sample(minute * 60, (minute + 1) as f32)).collect();
+        let stats = analyze_cpu_trend(&history)[0].stats;
+        assert_eq!((stats.min, stats.p50, stats.p90, stats.p95, stats.p99), (1.0, 50.0, 90.0, 95.0, 99.0));
+        assert!((stats.std_dev - 28.866).abs() < 1e-3);
+
+        let mut history = history;
+        for (minute, metrics) in history.iter_mut().enumerate() {
+            metrics.disk_io = if minute % 10 == 0 { 600_000 } else { 6000 };
+        }
+        let disk_io = analyze_disk_io_trend(&history);
+        assert_eq!(disk_io.stats.p50, 100.0);
+        assert_eq!(disk_io.peak, 10_000.0);
+    }
+
+    #[test]
+    fn fits_a_line_through_points() {
+        let fit = linear_fit(&[(0.0, 1.0), (1.0, 3.0), (2.0, 5.0)]).unwrap();
+        assert_eq!(fit.slope, 2.0);
+        assert_eq!(fit.intercept, 1.0);
+        assert_eq!(fit.r_squared, 1.0);
+        assert!(linear_fit(&[(1.0, 1.0)]).is_none());
+        assert!(linear_fit(&[(1.0, 1.0), (1.0, 2.0)]).is_none());
+    }
+
+    #[test]
+    fn theil_sen_ignores_outliers() {
+        let mut points: Vec<(f64, f64)> = (0..20).map(|x| (x as f64, 2.0 * x as f64 + 5.0)).collect();
+        points[7].1 = 500.0;
+        points[8].1 = 480.0;
+
+        let robust = theil_sen_fit(&points).unwrap();
+        assert_eq!(robust.slope, 2.0);
+        assert_eq!(robust.intercept, 5.0);
+        assert!(linear_fit(&points).unwrap().slope < 0.0);
+        assert!(theil_sen_fit(&[(1.0, 1.0), (1.0, 5.0)]).is_none());
+    }
+
+    #[test]
+    fn ewma_detector_scores_departures_after_warmup() {
+        let config = AnomalyConfig::default();
+        let mut detector = AnomalyDetector::new(&config, 1.0);
+        for i in 0..config.warmup {
+            assert!(detector.update(if i % 2 == 0 { 10.0 } else { 12.0 }).is_none());
+        }
+
+        let (expected, score) = detector.update(11.0).unwrap();
+        assert!((expected.value - 11.0).abs() < 0.5);
+        assert!(score.abs() < 1.0);
+        assert!(detector.update(40.0).unwrap().1 > config.z_threshold);
+        assert!(detector.expected().unwrap().spread >= 1.0);
+    }
+
+    #[test]
+    fn mad_detector_is_not_thrown_off_by_earlier_outliers() {
+        let scores = |mode| {
+            let mut detector = AnomalyDetector::new(&AnomalyConfig { mode, ..AnomalyConfig::default() }, 1.0);
+            for i in 0..30 {
+                detector.update(if i == 25 { 100.0 } else { 10.0 + (i % 3) as f64 });
+            }
+            detector.update(30.0).unwrap()
+        };
+
+        let (mad_expected, mad_score) = scores(AnomalyMode::Mad);
+        let (_, ewma_score) = scores(AnomalyMode::Ewma);
+        assert_eq!(mad_expected.value, 11.0);
+        assert!(mad_score > 10.0);
+        assert!(ewma_score < 4.0);
+    }
+
+    #[test]
+    fn anomalies_carry_score_and_direction() {
+        let mut history: Vec<_> = (0..30).map(|i| sample(i * 60, if i % 2 == 0 { 20.0 } else { 22.0 })).collect();
+        history.push(sample(30 * 60, 95.0));
+        let config = AnomalyConfig::default();
+
+        let findings = detect_anomalies(&history, &config);
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].id, "anomaly:cpu");
+        assert_eq!(findings[0].category, FindingCategory::Anomaly);
+        let anomaly = findings[0].anomaly.unwrap();
+        assert_eq!(anomaly.direction, Direction::Above);
+        assert!(anomaly.score > 20.0);
+
+        history.last_mut().unwrap().cpu_usage = vec![0.0];
+        assert_eq!(detect_anomalies(&history, &config)[0].anomaly.unwrap().direction, Direction::Below);
+        assert!(detect_anomalies(&history[..10], &config).is_empty());
+        assert!(detect_anomalies(&history, &AnomalyConfig { enabled: false, ..config }).is_empty());
+    }
+}
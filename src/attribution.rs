@@ -0,0 +1,207 @@
+//! Names the processes behind a peak in system CPU, memory or disk I/O.
+
+use std::collections::HashMap;
+use std::time::SystemTime;
+use humansize::{format_size, BINARY};
+use serde::Serialize;
+use sysinfo::PidExt;
+use crate::history_query::Metric;
+use crate::types::{ProcessMetrics, SystemMetrics};
+
+/// The metrics whose peaks can be traced to processes.
+pub const ATTRIBUTED_METRICS: [Metric; 3] = [Metric::Cpu, Metric::Memory, Metric::DiskIo];
+const TOP_CONTRIBUTORS: usize = 3;
+
+/// A process's share of a peak.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Contributor {
+    /// Process ID.
+    pub pid: u32,
+    /// Process name.
+    pub name: String,
+    /// Percent of one core for CPU, bytes of growth for memory, bytes per second for disk I/O.
+    pub value: f64,
+}
+
+/// The highest value of a metric and the processes that drove it.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Peak {
+    /// The metric that peaked.
+    pub metric: Metric,
+    /// When the peak sample was taken.
+    pub at: SystemTime,
+    /// In the metric's unit.
+    pub value: f64,
+    /// The processes that drove the peak, largest first.
+    pub contributors: Vec<Contributor>,
+}
+
+/// The peak of each attributed metric in `metrics_history`, with its top contributors.
+pub fn find_peaks(metrics_history: &[SystemMetrics]) -> Vec<Peak> {
+    ATTRIBUTED_METRICS
+        .into_iter()
+        .filter_map(|metric| {
+            let (at, value) = metric.sample_values(metrics_history).into_iter().max_by(|a, b| a.1.total_cmp(&b.1))?;
+            let index = metrics_history.iter().position(|sample| sample.recorded_at == at)?;
+            Some(Peak { metric, at, value, contributors: contributors(metric, metrics_history, index) })
+        })
+        .collect()
+}
+
+/// The processes that drove `metric` in sample `index`, largest first: CPU usage over the
+/// sample's interval, memory growth since the lowest sample before it, or disk I/O over the
+/// interval. Empty when there is nothing to compare against.
+pub fn contributors(metric: Metric, metrics_history: &[SystemMetrics], index: usize) -> Vec<Contributor> {
+    let Some(sample) = metrics_history.get(index) else {
+        return Vec::new();
+    };
+    let mut contributors: Vec<Contributor> = match metric {
+        Metric::Cpu => live(sample).map(|process| contributor(process, f64::from(process.cpu_usage))).collect(),
+        Metric::Memory => {
+            let Some(trough) = metrics_history[..index].iter().min_by_key(|earlier| earlier.memory_usage) else {
+                return Vec::new();
+            };
+            let before: HashMap<(u32, u64), u64> = live(trough)
+                .map(|process| ((process.pid.as_u32(), process.start_time), process.memory_usage))
+                .collect();
+            live(sample)
+                .map(|process| {
+                    let was = before.get(&(process.pid.as_u32(), process.start_time)).copied().unwrap_or_default();
+                    contributor(process, process.memory_usage as f64 - was as f64)
+                })
+                .collect()
+        }
+        Metric::DiskIo => {
+            let Some(elapsed) = index
+                .checked_sub(1)
+                .and_then(|previous| sample.recorded_at.duration_since(metrics_history[previous].recorded_at).ok())
+                .filter(|elapsed| !elapsed.is_zero())
+            else {
+                return Vec::new();
+            };
+            live(sample)
+                .map(|process| contributor(process, process.disk_usage as f64 / elapsed.as_secs_f64()))
+                .collect()
+        }
+        _ => Vec::new(),
+    };
+    contributors.retain(|contributor| contributor.value > 0.0);
+    contributors.sort_by(|a, b| b.value.total_cmp(&a.value).then(a.pid.cmp(&b.pid)));
+    contributors.truncate(TOP_CONTRIBUTORS);
+    contributors
+}
+
+fn live(sample: &SystemMetrics) -> impl Iterator<Item = &ProcessMetrics> {
+    sample.process_metrics.iter().filter(|process| !process.zombie)
+}
+
+fn contributor(process: &ProcessMetrics, value: f64) -> Contributor {
+    Contributor { pid: process.pid.as_u32(), name: process.name.clone(), value }
+}
+
+/// `firefox (PID 812) 85.0%, cc1 (PID 990) 40.0%`.
+pub fn format_contributors(metric: Metric, contributors: &[Contributor]) -> String {
+    contributors
+        .iter()
+        .map(|contributor| {
+            let value = match metric {
+                Metric::Memory => format!("+{}", format_size(contributor.value.max(0.0) as u64, BINARY)),
+                Metric::DiskIo => format!("{}/s", format_size(contributor.value.max(0.0) as u64, BINARY)),
+                _ => format!("{:.1}%", contributor.value),
+            };
+            format!("{} (PID {}) {value}", contributor.name, contributor.pid)
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// `CPU usage peaked at 97.0%: firefox (PID 812) 85.0%`, or without the list when no
+/// process stands out.
+pub fn describe_peak(peak: &Peak) -> String {
+    let described = format!("{} peaked at {}", peak.metric.label(), peak.metric.format_value(peak.value));
+    if peak.contributors.is_empty() {
+        described
+    } else {
+        format!("{described}: {}", format_contributors(peak.metric, &peak.contributors))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::*;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    const MIB: u64 = 1024 * 1024;
+
+    fn process(pid: usize, cpu_usage: f32, memory_usage: u64, disk_usage: u64) -> ProcessMetrics {
+        ProcessMetrics {
+            name: format!("proc{pid}"),
+            pid: sysinfo::Pid::from(pid),
+            cpu_usage,
+            memory_usage,
+            disk_usage,
+            cmd: Vec::new(),
+            user: None,
+            zombie: false,
+            start_time: 100,
+            threads: None,
+            parent: None,
+        }
+    }
+
+    fn sample(minute: u64, cpu: f32, process_metrics: Vec<ProcessMetrics>) -> SystemMetrics {
+        SystemMetrics {
+            cpu_usage: vec![cpu, cpu],
+            memory_usage: process_metrics.iter().map(|process| process.memory_usage).sum(),
+            memory_total: 8 * 1024 * MIB,
+            disk_io: process_metrics.iter().map(|process| process.disk_usage).sum(),
+            process_metrics,
+            ..SystemMetrics::sample(minute * 60, cpu)
+        }
+    }
+
+    /// pid 1 is a steady large process; at minute 2 pid 2 burns CPU and writes to disk,
+    /// and by minute 3 pid 3 has grown by 2 GiB.
+    fn history() -> Vec<SystemMetrics> {
+        vec![
+            sample(0, 10.0, vec![process(1, 10.0, 2048 * MIB, 0), process(2, 1.0, 100 * MIB, 0)]),
+            sample(1, 12.0, vec![process(1, 12.0, 2048 * MIB, 0), process(2, 1.0, 100 * MIB, 60 * MIB)]),
+            sample(2, 95.0, vec![process(1, 15.0, 2048 * MIB, 0), process(2, 170.0, 100 * MIB, 600 * MIB)]),
+            sample(3, 20.0, vec![
+                process(1, 10.0, 2048 * MIB, 0),
+                process(2, 2.0, 100 * MIB, 0),
+                process(3, 5.0, 2048 * MIB, 0),
+            ]),
+        ]
+    }
+
+    #[test]
+    fn peaks_name_the_processes_behind_them() {
+        let peaks = find_peaks(&history());
+        assert_eq!(peaks.len(), 3);
+
+        let cpu = &peaks[0];
+        assert_eq!((cpu.metric, cpu.value), (Metric::Cpu, 95.0));
+        assert_eq!(cpu.contributors.iter().map(|c| c.pid).collect::<Vec<_>>(), vec![2, 1]);
+        assert_eq!(describe_peak(cpu), "CPU usage peaked at 95.0%: proc2 (PID 2) 170.0%, proc1 (PID 1) 15.0%");
+
+        // Memory is compared with the lowest earlier sample, so only the new process counts.
+        let memory = &peaks[1];
+        assert_eq!(memory.at, UNIX_EPOCH + Duration::from_secs(1_700_000_000 + 180));
+        assert_eq!(memory.contributors, vec![Contributor { pid: 3, name: "proc3".to_string(), value: (2048 * MIB) as f64 }]);
+        assert_eq!(format_contributors(Metric::Memory, &memory.contributors), "proc3 (PID 3) +2 GiB");
+
+        let disk_io = &peaks[2];
+        assert_eq!(disk_io.value, (10 * MIB) as f64);
+        assert_eq!(format_contributors(Metric::DiskIo, &disk_io.contributors), "proc2 (PID 2) 10 MiB/s");
+    }
+
+    #[test]
+    fn first_sample_has_nothing_to_compare_against() {
+        let history = history();
+        assert!(contributors(Metric::Memory, &history, 0).is_empty());
+        assert!(contributors(Metric::DiskIo, &history, 0).is_empty());
+        assert_eq!(contributors(Metric::Cpu, &history, 0).len(), 2);
+    }
+}
@@ -0,0 +1,345 @@
+//! Per-metric baselines learned over time, one slot per hour of the week, that samples
+//! are scored against.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use chrono::{DateTime, Datelike, Local, Timelike};
+use serde::{Deserialize, Serialize};
+use crate::config::{self, BaselineConfig};
+use crate::dir_watch::expand_home;
+use crate::history_query::Metric;
+use crate::analysis::{anomaly_finding, Expected};
+use crate::types::{Finding, SystemMetrics};
+
+pub const BASELINE_SCHEMA_VERSION: u32 = 1;
+/// One slot per hour of the week, Monday 00:00 first.
+pub const SLOTS: usize = 7 * 24;
+/// Metrics baselines are learned for.
+pub const BASELINE_METRICS: [Metric; 5] = [
+    Metric::Cpu,
+    Metric::Memory,
+    Metric::NetworkRx,
+    Metric::NetworkTx,
+    Metric::Disk,
+];
+
+/// Running mean and variance of one metric in one slot (Welford's algorithm).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct SlotStats {
+    /// Values observed.
+    pub count: u64,
+    /// Mean of the values observed.
+    pub mean: f64,
+    /// Sum of squared differences from the mean.
+    pub m2: f64,
+}
+
+impl SlotStats {
+    /// Adds one value.
+    pub fn observe(&mut self, value: f64) {
+        self.count += 1;
+        let delta = value - self.mean;
+        self.mean += delta / self.count as f64;
+        self.m2 += delta * (value - self.mean);
+    }
+
+    /// Sample variance; 0 until two values are observed.
+    pub fn variance(&self) -> f64 {
+        if self.count < 2 {
+            0.0
+        } else {
+            self.m2 / (self.count - 1) as f64
+        }
+    }
+
+    /// Square root of [`SlotStats::variance`].
+    pub fn std_dev(&self) -> f64 {
+        self.variance().sqrt()
+    }
+}
+
+/// How far the latest sample is from what its slot has learned.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Deviation {
+    /// The metric scored.
+    pub metric: Metric,
+    /// Hour-of-week slot the sample falls in.
+    pub slot: usize,
+    /// The latest sample's value.
+    pub value: f64,
+    /// The slot's learned mean.
+    pub expected: f64,
+    /// The slot's learned standard deviation.
+    pub std_dev: f64,
+    /// Standard deviations from the expected value; negative when below it.
+    pub score: f64,
+}
+
+/// Learned hour-of-week statistics per metric, loaded with [`Baselines::load_configured`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Baselines {
+    /// Format version of the baselines file.
+    pub schema_version: u32,
+    /// One entry per hour-of-week slot for each learned metric.
+    pub metrics: BTreeMap<Metric, Vec<SlotStats>>,
+}
+
+impl Default for Baselines {
+    fn default() -> Self {
+        Self { schema_version: BASELINE_SCHEMA_VERSION, metrics: BTreeMap::new() }
+    }
+}
+
+impl Baselines {
+    /// Reads baselines from `path`. A missing file starts empty; an unreadable one is
+    /// logged and also starts empty, so a bad file never stops the monitor.
+    pub fn load(path: &Path) -> Self {
+        let contents = match fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Self::default(),
+            Err(e) => {
+                log::warn!("Failed to read baselines {}: {e}", path.display());
+                return Self::default();
+            }
+        };
+        match serde_json::from_str::<Baselines>(&contents) {
+            Ok(baselines) if baselines.schema_version == BASELINE_SCHEMA_VERSION
+                && baselines.metrics.values().all(|slots| slots.len() == SLOTS) => baselines,
+            Ok(baselines) => {
+                log::warn!(
+                    "Ignoring baselines {} with schema version {}",
+                    path.display(),
+                    baselines.schema_version
+                );
+                Self::default()
+            }
+            Err(e) => {
+                log::warn!("Ignoring unreadable baselines {}: {e}", path.display());
+                Self::default()
+            }
+        }
+    }
+
+    /// The configured baselines, or empty ones when learning is disabled.
+    pub fn load_configured(config: &BaselineConfig) -> Self {
+        match baseline_path(config).filter(|_| config.enabled) {
+            Some(path) => Self::load(&path),
+            None => Self::default(),
+        }
+    }
+
+    /// Writes to a temporary file first so a crash never leaves a torn file behind.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let tmp = path.with_extension("json.tmp");
+        fs::write(&tmp, serde_json::to_vec(self).map_err(io::Error::other)?)?;
+        fs::rename(&tmp, path)
+    }
+
+    /// What was learned for `metric` in hour-of-week `slot`.
+    pub fn slot(&self, metric: Metric, slot: usize) -> Option<&SlotStats> {
+        self.metrics.get(&metric).and_then(|slots| slots.get(slot))
+    }
+
+    /// Learns the latest sample in `metrics_history` into its slot.
+    pub fn observe(&mut self, metrics_history: &[SystemMetrics]) {
+        for (metric, at, value) in latest_values(metrics_history) {
+            self.metrics.entry(metric).or_insert_with(|| vec![SlotStats::default(); SLOTS])[slot_of(at)]
+                .observe(value);
+        }
+    }
+
+    /// Scores the latest sample against its slot. Slots with fewer than `min_samples`
+    /// samples are not scored yet.
+    pub fn score(&self, metrics_history: &[SystemMetrics], min_samples: u64) -> Vec<Deviation> {
+        latest_values(metrics_history)
+            .into_iter()
+            .filter_map(|(metric, at, value)| {
+                let slot = slot_of(at);
+                let stats = self.slot(metric, slot).filter(|stats| stats.count >= min_samples.max(2))?;
+                let std_dev = stats.std_dev().max(metric.noise_floor());
+                Some(Deviation {
+                    metric,
+                    slot,
+                    value,
+                    expected: stats.mean,
+                    std_dev,
+                    score: (value - stats.mean) / std_dev,
+                })
+            })
+            .collect()
+    }
+}
+
+pub fn baseline_path(config: &BaselineConfig) -> Option<PathBuf> {
+    config
+        .path
+        .as_deref()
+        .map(expand_home)
+        .or_else(config::default_baseline_path)
+}
+
+/// Hour-of-week slot of `at` in local time.
+pub fn slot_of(at: SystemTime) -> usize {
+    let local: DateTime<Local> = at.into();
+    local.weekday().num_days_from_monday() as usize * 24 + local.hour() as usize
+}
+
+/// `Tue 14:00` for the slot covering Tuesday 14:00-14:59.
+pub fn slot_label(slot: usize) -> String {
+    const DAYS: [&str; 7] = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+    format!("{} {:02}:00", DAYS[slot / 24 % 7], slot % 24)
+}
+
+/// Findings for deviations at least `z_threshold` standard deviations from the expected value.
+pub fn baseline_findings(metrics_history: &[SystemMetrics], baselines: &Baselines, config: &BaselineConfig) -> Vec<Finding> {
+    if !config.enabled {
+        return Vec::new();
+    }
+    baselines
+        .score(metrics_history, config.min_samples)
+        .into_iter()
+        .filter(|deviation| deviation.score.abs() >= config.z_threshold)
+        .map(|deviation| {
+            let expected = Expected { value: deviation.expected, spread: deviation.std_dev };
+            anomaly_finding(
+                format!("baseline:{}", deviation.metric.name()),
+                deviation.metric,
+                deviation.value,
+                expected,
+                config.z_threshold,
+                &format!("on {}", slot_label(deviation.slot)),
+            )
+        })
+        .collect()
+}
+
+/// The latest sample's value of each baseline metric. Network rates need the sample before it.
+fn latest_values(metrics_history: &[SystemMetrics]) -> Vec<(Metric, SystemTime, f64)> {
+    let Some(latest) = metrics_history.last() else {
+        return Vec::new();
+    };
+    BASELINE_METRICS
+        .into_iter()
+        .filter_map(|metric| Some((metric, latest.recorded_at, metric.latest_value(metrics_history)?)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::*;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    const MIB: u64 = 1024 * 1024;
+
+    fn sample(secs: u64, cpu: f32, network_rx: u64) -> SystemMetrics {
+        SystemMetrics {
+            network_rx,
+            ..SystemMetrics::sample(secs, cpu)
+        }
+    }
+
+    /// Learns one sample a minute for an hour, a week apart each time, so every sample
+    /// lands in the same slot.
+    fn learned(weeks: u64, cpu: impl Fn(u64) -> f32, network_rx: u64) -> Baselines {
+        let mut baselines = Baselines::default();
+        for week in 0..weeks {
+            let start = week * 7 * 24 * 3600;
+            let mut history = Vec::new();
+            for minute in 0..2 {
+                history.push(sample(start + minute * 60, cpu(week * 2 + minute), network_rx * 60));
+                baselines.observe(&history);
+            }
+        }
+        baselines
+    }
+
+    #[test]
+    fn slot_stats_track_mean_and_variance() {
+        let mut stats = SlotStats::default();
+        for value in [2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0] {
+            stats.observe(value);
+        }
+        assert_eq!(stats.count, 8);
+        assert_eq!(stats.mean, 5.0);
+        assert!((stats.variance() - 32.0 / 7.0).abs() < 1e-9);
+        assert_eq!(SlotStats::default().std_dev(), 0.0);
+    }
+
+    #[test]
+    fn slots_cover_each_hour_of_the_week() {
+        let at = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        assert_eq!(slot_of(at + Duration::from_secs(7 * 24 * 3600)), slot_of(at));
+        assert_eq!(slot_of(at + Duration::from_secs(3600)), (slot_of(at) + 1) % SLOTS);
+        assert_eq!(slot_label(0), "Mon 00:00");
+        assert_eq!(slot_label(24 + 14), "Tue 14:00");
+    }
+
+    #[test]
+    fn samples_are_scored_against_their_slot() {
+        // CPU alternates between 20% and 30%; the network steadily receives 1 MiB/s.
+        let baselines = learned(10, |i| if i % 2 == 0 { 20.0 } else { 30.0 }, MIB);
+        let slot = slot_of(sample(0, 0.0, 0).recorded_at);
+        assert_eq!(baselines.slot(Metric::Cpu, slot).unwrap().count, 20);
+        // Network rates start with the second sample of each hour.
+        assert_eq!(baselines.slot(Metric::NetworkRx, slot).unwrap().count, 10);
+
+        let busy = [sample(60, 25.0, 0), sample(120, 90.0, 3 * MIB * 60)];
+        let deviations = baselines.score(&busy, 10);
+        let cpu = deviations.iter().find(|d| d.metric == Metric::Cpu).unwrap();
+        assert!((cpu.expected - 25.0).abs() < 1e-9);
+        assert!(cpu.score > 10.0);
+        let network = deviations.iter().find(|d| d.metric == Metric::NetworkRx).unwrap();
+        assert_eq!(network.std_dev, 64.0 * 1024.0);
+        assert_eq!(network.score, 32.0);
+
+        assert!(baselines.score(&busy, 50).is_empty());
+        assert!(Baselines::default().score(&busy, 0).is_empty());
+    }
+
+    #[test]
+    fn only_deviations_past_the_threshold_become_findings() {
+        let baselines = learned(10, |i| if i % 2 == 0 { 20.0 } else { 30.0 }, MIB);
+        let config = BaselineConfig::default();
+
+        let usual = [sample(60, 25.0, MIB * 60), sample(120, 28.0, MIB * 60)];
+        assert!(baseline_findings(&usual, &baselines, &config).is_empty());
+
+        let quiet = [sample(60, 25.0, MIB * 60), sample(120, 0.0, 0)];
+        let mut findings = baseline_findings(&quiet, &baselines, &config);
+        findings.sort_by(|a, b| a.id.cmp(&b.id));
+        let ids: Vec<_> = findings.iter().map(|f| f.id.as_str()).collect();
+        assert_eq!(ids, vec!["baseline:cpu", "baseline:network-rx"]);
+        assert_eq!(findings[0].category, FindingCategory::Anomaly);
+        assert_eq!(findings[0].anomaly.unwrap().direction, Direction::Below);
+        assert!(findings[0].summary.contains("on "));
+        assert!(findings[0].threshold.unwrap() < 25.0);
+        assert_eq!(findings[1].category, FindingCategory::Network);
+
+        let disabled = BaselineConfig { enabled: false, ..config };
+        assert!(baseline_findings(&quiet, &baselines, &disabled).is_empty());
+    }
+
+    #[test]
+    fn baselines_persist_and_bad_files_start_empty() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("nested").join("baselines.json");
+        assert_eq!(Baselines::load(&path), Baselines::default());
+
+        let baselines = learned(3, |_| 40.0, MIB);
+        baselines.save(&path).unwrap();
+        assert_eq!(Baselines::load(&path), baselines);
+        assert!(!path.with_extension("json.tmp").exists());
+
+        fs::write(&path, "{\"schema_version\":1,\"metrics\":{\"cpu\":[]}}").unwrap();
+        assert_eq!(Baselines::load(&path), Baselines::default());
+        fs::write(&path, "not json").unwrap();
+        assert_eq!(Baselines::load(&path), Baselines::default());
+    }
+}
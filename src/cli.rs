@@ -0,0 +1,583 @@
+use std::path::Path;
+use std::io::{self, Write};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use clap::builder::{PossibleValuesParser, TypedValueParser};
+use sysinfo::{System, SystemExt, PidExt};
+
+use crate::{
+    analyze_with_forecasts, build_report, collect_history, collect_snapshot, config, coolant, daemon, forecast, metrics, new_system,
+    prime_cpu_usage, recording, render_report, report, snapshot, Analysis, Baselines, MetricsScope,
+    NotificationManager, SystemMetrics,
+};
+use crate::display::{
+    display_health_score,
+    display_system_info,
+    display_performance_analysis,
+    display_security_analysis,
+    display_recommendations,
+    display_temp_files,
+    display_temperature_info,
+    display_process_summary
+};
+use crate::display::{display_history_summary, display_replay, display_snapshot_diff};
+use crate::history::{history_dir, HistoryStore};
+use crate::history_query::{self, Granularity, Metric};
+use crate::temp_manager::delete_temp_files;
+use humansize::{format_size, BINARY};
+
+#[derive(Parser)]
+#[command(name = "system-monitor", version, about = "A lightweight cross-platform system monitoring tool with desktop notifications")]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Commands>,
+
+    #[arg(long, global = true)]
+    config: Option<std::path::PathBuf>,
+
+    #[arg(long, global = true)]
+    no_notify: bool,
+
+    #[arg(long, global = true)]
+    interval: Option<u64>,
+}
+
+#[derive(Subcommand)]
+enum Commands {
+    Monitor(OutputArgs),
+    Daemon,
+    ShowTempFiles(OutputArgs),
+    CleanTemp,
+    Config(OutputArgs),
+    Report(ReportArgs),
+    /// Show recorded history for one metric, or a trend summary of a window
+    History(HistoryArgs),
+    /// Record samples at the daemon's check interval (see --interval) to a file
+    Record(RecordArgs),
+    /// Replay a recording through the daemon's alerting, printing what it would notify
+    Replay(ReplayArgs),
+    /// Save point-in-time snapshots for later comparison
+    #[command(subcommand)]
+    Snapshot(SnapshotCommand),
+    /// Compare two snapshots saved with `snapshot save`
+    Diff(DiffArgs),
+}
+
+#[derive(Subcommand)]
+enum SnapshotCommand {
+    /// Sample the system and write a snapshot file
+    Save(SnapshotSaveArgs),
+}
+
+#[derive(Args)]
+struct SnapshotSaveArgs {
+    /// File to write; defaults to snapshot-<host>-<time>.json in the current directory
+    path: Option<std::path::PathBuf>,
+}
+
+#[derive(Args)]
+struct DiffArgs {
+    /// The earlier snapshot
+    before: std::path::PathBuf,
+
+    /// The later snapshot
+    after: std::path::PathBuf,
+
+    #[command(flatten)]
+    output: OutputArgs,
+}
+
+#[derive(Args)]
+struct RecordArgs {
+    /// How long to record, e.g. 10m or 1h
+    #[arg(long, default_value = "10m", value_parser = history_query::parse_duration)]
+    duration: std::time::Duration,
+
+    /// File to write the recording to
+    output: std::path::PathBuf,
+}
+
+#[derive(Args)]
+struct ReplayArgs {
+    /// Recording written by `record`
+    file: std::path::PathBuf,
+}
+
+#[derive(Args)]
+struct HistoryArgs {
+    /// Series to show
+    #[arg(
+        long,
+        default_value = "cpu",
+        value_parser = PossibleValuesParser::new(Metric::ALL.map(Metric::name)).try_map(|name| name.parse::<Metric>()),
+    )]
+    metric: Metric,
+
+    /// Start of the window: a duration before now (6h, 2d, 1h30m), Unix seconds, or "now"
+    #[arg(long, default_value = "1h")]
+    since: String,
+
+    /// End of the window, in the same forms as --since
+    #[arg(long, default_value = "now")]
+    until: String,
+
+    /// Bucket size such as 5m or 1h, "raw" for every sample, or "auto"
+    #[arg(long, default_value = "auto")]
+    resolution: Granularity,
+
+    /// Output format
+    #[arg(long, value_enum, default_value_t = HistoryFormat::Table)]
+    format: HistoryFormat,
+
+    /// Run the trend analysis over the window instead of listing values
+    #[arg(long)]
+    summary: bool,
+
+    /// Write the output to a file instead of stdout
+    #[arg(long)]
+    output: Option<std::path::PathBuf>,
+}
+
+#[derive(ValueEnum, Clone, Copy, PartialEq, Eq)]
+enum HistoryFormat {
+    Table,
+    Csv,
+    Json,
+}
+
+#[derive(Args)]
+struct ReportArgs {
+    /// Report format
+    #[arg(long, value_enum, default_value_t = ReportFormat::Markdown)]
+    format: ReportFormat,
+
+    /// Write the report to a file instead of stdout
+    #[arg(long)]
+    output: Option<std::path::PathBuf>,
+}
+
+#[derive(ValueEnum, Clone, Copy, PartialEq, Eq)]
+enum ReportFormat {
+    #[value(name = "md")]
+    Markdown,
+    Html,
+}
+
+#[derive(Args, Clone, Default)]
+struct OutputArgs {
+    /// Output format
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+    format: OutputFormat,
+
+    /// Write the report to a file instead of stdout (JSON only)
+    #[arg(long, requires = "format")]
+    output: Option<std::path::PathBuf>,
+}
+
+#[derive(ValueEnum, Clone, Copy, Default, PartialEq, Eq)]
+enum OutputFormat {
+    #[default]
+    Text,
+    Json,
+}
+
+impl OutputArgs {
+    fn is_json(&self) -> bool {
+        self.format == OutputFormat::Json
+    }
+
+    fn emit(&self, contents: &str) {
+        if let Err(e) = report::write_output(contents, self.output.as_deref()) {
+            eprintln!("Failed to write output: {e}");
+            std::process::exit(1);
+        }
+    }
+}
+
+fn prompt_temp_file_age() -> Option<u64> {
+    println!("\nChoose files to delete based on age:");
+    println!("1. Recent files (1-2 days old)");
+    println!("2. Moderately old files (3-5 days old)");
+    println!("3. Old files (6+ days old)");
+    println!("4. Cancel cleanup");
+
+    print!("\nEnter your choice (1-4): ");
+    io::stdout().flush().unwrap();
+
+    let mut input = String::new();
+    io::stdin().read_line(&mut input).unwrap();
+
+    match input.trim() {
+        "1" => {
+            println!("Deleting files 1-2 days old...");
+            Some(2)
+        }
+        "2" => {
+            println!("Deleting files 3-5 days old...");
+            Some(5)
+        }
+        "3" => {
+            println!("Deleting files 6+ days old...");
+            Some(6)
+        }
+        "4" => {
+            println!("Cleanup cancelled.");
+            None
+        }
+        _ => {
+            println!("Invalid choice. Cleanup cancelled.");
+            None
+        }
+    }
+}
+
+fn prompt_apply_coolant(temp: f32, threshold: f64) -> bool {
+    println!("\nTemperature is {temp:.1}°C (over the {threshold:.1}°C threshold).");
+    print!("Apply coolant by lowering priority of the top CPU processes? [y/N]: ");
+    io::stdout().flush().unwrap();
+
+    let mut input = String::new();
+    io::stdin().read_line(&mut input).unwrap();
+    matches!(input.trim().to_lowercase().as_str(), "y" | "yes")
+}
+
+fn collect_monitoring_history(sys: &mut System, cfg: &config::Config, progress: bool) -> Vec<SystemMetrics> {
+    if progress {
+        println!("Collecting system metrics over {} seconds...", cfg.monitoring.duration_secs);
+        display_process_summary(sys);
+    } else {
+        prime_cpu_usage(sys);
+    }
+
+    collect_history(sys, cfg, || {
+        if progress {
+            print!(".");
+            io::stdout().flush().unwrap();
+        }
+    })
+}
+
+fn run_monitor(cfg: &config::Config, output: &OutputArgs) {
+    let mut sys = new_system();
+    let metrics_history = collect_monitoring_history(&mut sys, cfg, !output.is_json());
+
+    if output.is_json() {
+        notify_latest(&metrics_history, cfg);
+        let analysis = analyze_samples(&metrics_history, cfg);
+        output.emit(&report::to_json(&build_report(&sys, &metrics_history, analysis)));
+        return;
+    }
+    println!("\n");
+
+    let analysis = analyze_samples(&metrics_history, cfg);
+    display_health_score(&analysis.health);
+    display_system_info(&sys);
+    display_performance_analysis(&metrics_history);
+
+    if let Some(last_metrics) = metrics_history.last() {
+        display_temperature_info(last_metrics, cfg);
+    }
+
+    display_security_analysis(&analysis.security);
+    display_recommendations(&analysis.recommendations);
+
+    notify_latest(&metrics_history, cfg);
+
+    if cfg.coolant.enabled {
+        if let Some(last_metrics) = metrics_history.last() {
+            let max_temp = last_metrics
+                .temperature
+                .components
+                .values()
+                .map(|reading| reading.celsius)
+                .fold(f32::NEG_INFINITY, f32::max)
+                .max(
+                    last_metrics
+                        .temperature
+                        .cpu_temp
+                        .as_ref()
+                        .map(|reading| reading.celsius)
+                        .unwrap_or(f32::NEG_INFINITY),
+                );
+
+            if max_temp > cfg.thresholds.temperature_celsius as f32
+                && prompt_apply_coolant(max_temp, cfg.thresholds.temperature_celsius)
+            {
+                sys.refresh_processes();
+                let fresh_processes = metrics::collect_process_metrics(&mut sys);
+                let targets = coolant::select_targets(&fresh_processes, &cfg.coolant);
+                if targets.is_empty() {
+                    println!("No throttleable processes found — nothing to cool.");
+                } else {
+                    println!("\nLowering priority of:");
+                    for target in &targets {
+                        println!(
+                            "  {} (pid {}) — {:.1}% CPU",
+                            target.name,
+                            target.pid.as_u32(),
+                            target.cpu_usage
+                        );
+                    }
+
+                    let report = coolant::apply_coolant(&targets, &cfg.coolant);
+                    for cooled in &report.cooled {
+                        println!("  cooled: {cooled}");
+                    }
+                    for error in &report.errors {
+                        println!("  failed: {error}");
+                    }
+                }
+            }
+        }
+    }
+
+    println!("\nAvailable Commands:");
+    println!("  system-monitor show-temp-files");
+    println!("  system-monitor clean-temp");
+    println!("  system-monitor daemon");
+    println!("  system-monitor config");
+    println!("  system-monitor report --format md|html");
+}
+
+/// Analyzes freshly collected samples, with the learned baselines and the disk usage
+/// trend from the history store.
+fn analyze_samples(metrics_history: &[SystemMetrics], cfg: &config::Config) -> Analysis {
+    let forecasts = forecast::forecast_with_history(metrics_history, cfg);
+    analyze_with_forecasts(metrics_history, &Baselines::load_configured(&cfg.baseline), &forecasts, cfg)
+}
+
+fn notify_latest(metrics_history: &[SystemMetrics], cfg: &config::Config) {
+    if cfg.notifications.enabled {
+        let mut notifier = NotificationManager::new(cfg.notifications.cooldown_secs);
+        notifier.check_and_notify(metrics_history, cfg);
+    }
+}
+
+fn run_report(cfg: &config::Config, args: &ReportArgs) {
+    let mut sys = new_system();
+    let metrics_history = collect_monitoring_history(&mut sys, cfg, false);
+    let analysis = analyze_samples(&metrics_history, cfg);
+    let document = build_report(&sys, &metrics_history, analysis);
+
+    let format = match args.format {
+        ReportFormat::Markdown => crate::ReportFormat::Markdown,
+        ReportFormat::Html => crate::ReportFormat::Html,
+    };
+    let rendered = render_report(&document, format);
+    if let Err(e) = report::write_output(&rendered, args.output.as_deref()) {
+        eprintln!("Failed to write report: {e}");
+        std::process::exit(1);
+    }
+}
+
+fn run_history(cfg: &config::Config, args: &HistoryArgs) {
+    let exit = |message: String| -> ! {
+        eprintln!("{message}");
+        std::process::exit(1);
+    };
+
+    let now = std::time::SystemTime::now();
+    let since = history_query::parse_time(&args.since, now).unwrap_or_else(|e| exit(format!("--since: {e}")));
+    let until = history_query::parse_time(&args.until, now).unwrap_or_else(|e| exit(format!("--until: {e}")));
+    if since >= until {
+        exit("--since must be before --until".to_string());
+    }
+    let Some(dir) = history_dir(&cfg.history) else {
+        exit("Could not determine the history directory; set [history] path in the config".to_string());
+    };
+    let store = HistoryStore::open_read_only(&dir, &cfg.history);
+
+    let rendered = if args.summary {
+        let summary = history_query::summarize(&store, since, until)
+            .unwrap_or_else(|e| exit(format!("Failed to read history from {}: {e}", dir.display())));
+        match args.format {
+            HistoryFormat::Json => report::to_json(&summary),
+            HistoryFormat::Table if args.output.is_none() => {
+                display_history_summary(&summary);
+                return;
+            }
+            _ => exit("--summary supports table output to stdout or --format json".to_string()),
+        }
+    } else {
+        let series = history_query::query_series(&store, args.metric, since, until, args.resolution, now)
+            .unwrap_or_else(|e| exit(format!("Failed to read history from {}: {e}", dir.display())));
+        match args.format {
+            HistoryFormat::Table => history_query::render_table(&series),
+            HistoryFormat::Csv => history_query::render_csv(&series),
+            HistoryFormat::Json => report::to_json(&series),
+        }
+    };
+
+    if let Err(e) = report::write_output(&rendered, args.output.as_deref()) {
+        exit(format!("Failed to write output: {e}"));
+    }
+}
+
+fn run_record(cfg: &config::Config, args: &RecordArgs) {
+    let interval = std::time::Duration::from_secs(cfg.daemon.check_interval_secs.max(1));
+    let samples = (args.duration.as_secs() / interval.as_secs()).max(1);
+    println!(
+        "Recording {samples} sample(s) every {}s to {}...",
+        interval.as_secs(),
+        args.output.display()
+    );
+
+    let mut sys = new_system();
+    let result = recording::record(&mut sys, &args.output, args.duration, interval, |_| {
+        print!(".");
+        io::stdout().flush().unwrap();
+    });
+    match result {
+        Ok(written) => println!("\nRecorded {written} sample(s)."),
+        Err(e) => {
+            eprintln!("\nFailed to record to {}: {e}", args.output.display());
+            std::process::exit(1);
+        }
+    }
+}
+
+fn run_replay(cfg: &config::Config, args: &ReplayArgs) {
+    let recording = recording::read_recording(&args.file).unwrap_or_else(|e| {
+        eprintln!("{e}");
+        std::process::exit(1);
+    });
+    let outcome = recording::replay(&recording.samples, &Baselines::load_configured(&cfg.baseline), cfg);
+    display_replay(&recording.header, &outcome);
+}
+
+fn run_snapshot_save(args: &SnapshotSaveArgs) {
+    println!("Taking snapshot...");
+    let mut sys = new_system();
+    prime_cpu_usage(&mut sys);
+    let taken = snapshot::take_snapshot(&mut sys);
+
+    let path = args.path.clone().unwrap_or_else(|| {
+        let host = taken.system.host_name.as_deref().unwrap_or("unknown");
+        let time = history_query::format_utc(taken.taken_at).replace(['-', ':'], "").replace(' ', "-");
+        format!("snapshot-{host}-{time}.json").into()
+    });
+    if let Err(e) = std::fs::write(&path, report::to_json(&taken)) {
+        eprintln!("Failed to write {}: {e}", path.display());
+        std::process::exit(1);
+    }
+    println!("Saved snapshot to {}", path.display());
+}
+
+fn run_diff(args: &DiffArgs) {
+    let load = |path: &Path| {
+        snapshot::load_snapshot(path).unwrap_or_else(|e| {
+            eprintln!("{e}");
+            std::process::exit(1);
+        })
+    };
+    let diff = snapshot::diff_snapshots(&load(&args.before), &load(&args.after));
+    if args.output.is_json() {
+        args.output.emit(&report::to_json(&diff));
+    } else {
+        display_snapshot_diff(&diff);
+    }
+}
+
+fn run_show_temp_files(output: &OutputArgs) {
+    let mut sys = new_system();
+
+    if !output.is_json() {
+        println!("Collecting temporary file information...");
+    }
+    let metrics = collect_snapshot(&mut sys, MetricsScope::Full);
+    if output.is_json() {
+        output.emit(&report::to_json(&report::build_temp_files_report(&metrics)));
+    } else {
+        display_temp_files(&metrics);
+    }
+}
+
+fn run_clean_temp() {
+    let days_threshold = match prompt_temp_file_age() {
+        Some(days) => days,
+        None => return,
+    };
+
+    println!("\nCleaning temporary files...");
+    let temp_dir = std::env::temp_dir();
+    let temp_paths: Vec<&Path> = vec![
+        temp_dir.as_path(),
+        Path::new("/tmp"),
+        Path::new("/var/tmp"),
+    ];
+
+    let stats = delete_temp_files(&temp_paths, days_threshold);
+
+    println!("\nCleanup Results:");
+    println!("Files Deleted: {}", stats.files_deleted);
+    println!("Space Freed: {}", format_size(stats.bytes_freed, BINARY));
+
+    if !stats.errors.is_empty() {
+        println!("\nErrors encountered:");
+        for error in &stats.errors {
+            println!("- {error}");
+        }
+    }
+}
+
+/// Runs the `system-monitor` command line: parses the process arguments, loads the
+/// config and runs the chosen subcommand.
+pub fn run_cli() {
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
+    let cli = Cli::parse();
+
+    let config_path = config::config_path(cli.config.as_deref());
+    let mut cfg = config::load(config_path.as_deref());
+    if cli.no_notify {
+        cfg.notifications.enabled = false;
+    }
+    if let Some(interval) = cli.interval {
+        cfg.daemon.check_interval_secs = interval;
+    }
+
+    let command = cli.command.unwrap_or(Commands::Monitor(OutputArgs::default()));
+    let output_args = match &command {
+        Commands::Monitor(output) | Commands::ShowTempFiles(output) | Commands::Config(output) => Some(output),
+        Commands::Diff(args) => Some(&args.output),
+        _ => None,
+    };
+    if output_args.is_some_and(|output| output.output.is_some() && !output.is_json()) {
+        eprintln!("error: --output is only supported with --format json");
+        std::process::exit(2);
+    }
+    let quiet = output_args.is_some_and(OutputArgs::is_json)
+        || matches!(command, Commands::Report(_) | Commands::History(_) | Commands::Replay(_));
+    if !quiet {
+        println!("Advanced System Performance Monitor v{} Starting...\n", env!("CARGO_PKG_VERSION"));
+    }
+
+    match command {
+        Commands::Monitor(output) => run_monitor(&cfg, &output),
+        Commands::Daemon => daemon::run_daemon(&cfg),
+        Commands::ShowTempFiles(output) => run_show_temp_files(&output),
+        Commands::CleanTemp => run_clean_temp(),
+        Commands::Config(output) if output.is_json() => {
+            output.emit(&report::to_json(&report::build_config_report(&cfg, config_path.as_deref())));
+        }
+        Commands::Config(_) => config::display_config(&cfg, config_path.as_deref()),
+        Commands::Report(args) => run_report(&cfg, &args),
+        Commands::History(args) => run_history(&cfg, &args),
+        Commands::Record(args) => run_record(&cfg, &args),
+        Commands::Replay(args) => run_replay(&cfg, &args),
+        Commands::Snapshot(SnapshotCommand::Save(args)) => run_snapshot_save(&args),
+        Commands::Diff(args) => run_diff(&args),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn history_accepts_every_metric() {
+        for metric in Metric::ALL {
+            let cli = Cli::try_parse_from(["system-monitor", "history", "--metric", metric.name()]).unwrap();
+            assert!(matches!(cli.command, Some(Commands::History(args)) if args.metric == metric));
+        }
+        assert!(Cli::try_parse_from(["system-monitor", "history", "--metric", "bogus"]).is_err());
+    }
+}
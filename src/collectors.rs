@@ -0,0 +1,292 @@
+use std::collections::HashMap;
+use std::io::Read;
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+use crate::config::{CollectorConfig, CustomThreshold};
+use crate::notifications::{Alert, AlertKind};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CustomMetric {
+    pub collector: String,
+    pub name: String,
+    pub value: f64,
+}
+
+#[derive(Default)]
+pub struct CollectorScheduler {
+    last_run: HashMap<String, Instant>,
+}
+
+impl CollectorScheduler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn run_due(&mut self, collectors: &[CollectorConfig], now: Instant) -> Vec<CustomMetric> {
+        let mut metrics = Vec::new();
+
+        for collector in collectors {
+            let due = self.last_run.get(&collector.name).is_none_or(|last| {
+                now.duration_since(*last).as_secs() >= collector.interval_secs
+            });
+            if !due {
+                continue;
+            }
+            self.last_run.insert(collector.name.clone(), now);
+
+            match run_collector(collector) {
+                Ok(collected) => metrics.extend(collected),
+                Err(e) => log::warn!("Collector {} failed: {e}", collector.name),
+            }
+        }
+
+        metrics
+    }
+}
+
+pub fn run_collector(collector: &CollectorConfig) -> Result<Vec<CustomMetric>, String> {
+    let stdout = run_with_timeout(&collector.command, Duration::from_secs(collector.timeout_secs))?;
+    let values = parse_output(&stdout)?;
+
+    Ok(values
+        .into_iter()
+        .map(|(name, value)| CustomMetric {
+            collector: collector.name.clone(),
+            name,
+            value,
+        })
+        .collect())
+}
+
+fn run_with_timeout(command: &[String], timeout: Duration) -> Result<String, String> {
+    let (program, args) = command.split_first().ok_or("empty command")?;
+
+    let mut child = Command::new(program)
+        .args(args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| format!("failed to start {program}: {e}"))?;
+
+    // Drain stdout on a separate thread so a chatty command can't block on a full pipe.
+    let mut pipe = child.stdout.take().ok_or("stdout not captured")?;
+    let reader = std::thread::spawn(move || {
+        let mut output = String::new();
+        pipe.read_to_string(&mut output).map(|_| output)
+    });
+
+    let started = Instant::now();
+    let status = loop {
+        match child.try_wait().map_err(|e| e.to_string())? {
+            Some(status) => break status,
+            None if started.elapsed() >= timeout => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(format!("timed out after {}s", timeout.as_secs()));
+            }
+            None => std::thread::sleep(POLL_INTERVAL),
+        }
+    };
+
+    let output = reader
+        .join()
+        .map_err(|_| "stdout reader panicked".to_string())?
+        .map_err(|e| e.to_string())?;
+
+    if status.success() {
+        Ok(output)
+    } else {
+        Err(format!("exited with {status}"))
+    }
+}
+
+pub fn parse_output(output: &str) -> Result<Vec<(String, f64)>, String> {
+    let trimmed = output.trim();
+    if trimmed.starts_with('{') {
+        let value: serde_json::Value = serde_json::from_str(trimmed).map_err(|e| e.to_string())?;
+        let mut values = Vec::new();
+        flatten_json("", &value, &mut values);
+        return Ok(values);
+    }
+
+    let mut values = Vec::new();
+    for line in trimmed.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let mut parts = line.split_whitespace();
+        let (Some(name), Some(raw), None) = (parts.next(), parts.next(), parts.next()) else {
+            log::debug!("Skipping malformed collector line: {line}");
+            continue;
+        };
+        match raw.parse::<f64>() {
+            Ok(value) => values.push((name.to_string(), value)),
+            Err(_) => log::debug!("Skipping non-numeric collector value: {line}"),
+        }
+    }
+    Ok(values)
+}
+
+fn flatten_json(prefix: &str, value: &serde_json::Value, out: &mut Vec<(String, f64)>) {
+    match value {
+        serde_json::Value::Object(map) => {
+            for (key, nested) in map {
+                let name = if prefix.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{prefix}.{key}")
+                };
+                flatten_json(&name, nested, out);
+            }
+        }
+        serde_json::Value::Number(number) => {
+            if let Some(v) = number.as_f64() {
+                out.push((prefix.to_string(), v));
+            }
+        }
+        serde_json::Value::Bool(flag) => out.push((prefix.to_string(), if *flag { 1.0 } else { 0.0 })),
+        _ => {}
+    }
+}
+
+pub fn evaluate_thresholds(collectors: &[CollectorConfig], metrics: &[CustomMetric]) -> Vec<Alert> {
+    let mut alerts = Vec::new();
+
+    for collector in collectors {
+        for threshold in &collector.thresholds {
+            let Some(metric) = metrics
+                .iter()
+                .find(|m| m.collector == collector.name && m.name == threshold.metric)
+            else {
+                continue;
+            };
+
+            let key = format!("{}.{}", collector.name, threshold.metric);
+            let breach = threshold_breach(threshold, metric.value);
+            alerts.push(Alert {
+                kind: AlertKind::Custom(key.clone()),
+                active: breach.is_some(),
+                title: format!("Custom Metric Alert: {key}"),
+                body: breach.unwrap_or_default(),
+                recovery: None,
+            });
+        }
+    }
+
+    alerts
+}
+
+fn threshold_breach(threshold: &CustomThreshold, value: f64) -> Option<String> {
+    if let Some(max) = threshold.max {
+        if value > max {
+            return Some(format!("{} is {value} (max: {max})", threshold.metric));
+        }
+    }
+    if let Some(min) = threshold.min {
+        if value < min {
+            return Some(format!("{} is {value} (min: {min})", threshold.metric));
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn collector(name: &str, command: &[&str], thresholds: Vec<CustomThreshold>) -> CollectorConfig {
+        CollectorConfig {
+            name: name.to_string(),
+            command: command.iter().map(|s| s.to_string()).collect(),
+            interval_secs: 60,
+            timeout_secs: 5,
+            thresholds,
+        }
+    }
+
+    fn metric(collector: &str, name: &str, value: f64) -> CustomMetric {
+        CustomMetric {
+            collector: collector.to_string(),
+            name: name.to_string(),
+            value,
+        }
+    }
+
+    #[test]
+    fn parses_name_value_lines() {
+        let output = "# comment\nqueue_depth 42\n\nseats_free 3.5\nbroken line here\nnot_a_number abc\n";
+        assert_eq!(parse_output(output).unwrap(), vec![
+            ("queue_depth".to_string(), 42.0),
+            ("seats_free".to_string(), 3.5),
+        ]);
+    }
+
+    #[test]
+    fn parses_nested_json() {
+        let output = r#"{"queue": {"depth": 7, "name": "main"}, "healthy": true}"#;
+        let mut values = parse_output(output).unwrap();
+        values.sort_by(|a, b| a.0.cmp(&b.0));
+        assert_eq!(values, vec![
+            ("healthy".to_string(), 1.0),
+            ("queue.depth".to_string(), 7.0),
+        ]);
+    }
+
+    #[test]
+    fn invalid_json_is_an_error() {
+        assert!(parse_output("{not json").is_err());
+    }
+
+    #[test]
+    fn thresholds_raise_custom_alerts() {
+        let collectors = vec![collector("farm", &["true"], vec![
+            CustomThreshold { metric: "backlog".to_string(), max: Some(50.0), min: None },
+            CustomThreshold { metric: "workers".to_string(), max: None, min: Some(2.0) },
+            CustomThreshold { metric: "missing".to_string(), max: Some(1.0), min: None },
+        ])];
+        let metrics = vec![metric("farm", "backlog", 80.0), metric("farm", "workers", 4.0)];
+
+        let alerts = evaluate_thresholds(&collectors, &metrics);
+
+        assert_eq!(alerts.len(), 2);
+        assert_eq!(alerts[0].kind, AlertKind::Custom("farm.backlog".to_string()));
+        assert!(alerts[0].active);
+        assert!(alerts[0].body.contains("80"));
+        assert!(!alerts[1].active);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn runs_command_and_collects_metrics() {
+        let config = collector("echo", &["sh", "-c", "echo 'depth 12'"], Vec::new());
+        assert_eq!(run_collector(&config).unwrap(), vec![metric("echo", "depth", 12.0)]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn slow_command_times_out() {
+        let mut config = collector("slow", &["sleep", "5"], Vec::new());
+        config.timeout_secs = 0;
+        let err = run_collector(&config).unwrap_err();
+        assert!(err.contains("timed out"), "error was: {err}");
+    }
+
+    #[test]
+    fn scheduler_respects_interval() {
+        let mut scheduler = CollectorScheduler::new();
+        let collectors = vec![collector("none", &["definitely-not-a-real-command"], Vec::new())];
+        let start = Instant::now();
+
+        scheduler.run_due(&collectors, start);
+        let first = scheduler.last_run["none"];
+        scheduler.run_due(&collectors, start + Duration::from_secs(30));
+        assert_eq!(scheduler.last_run["none"], first);
+        scheduler.run_due(&collectors, start + Duration::from_secs(60));
+        assert_eq!(scheduler.last_run["none"], start + Duration::from_secs(60));
+    }
+}
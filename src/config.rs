@@ -1,59 +1,412 @@
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
+use crate::history_query::Metric;
 
-#[derive(Debug, Default, Deserialize, Clone, PartialEq)]
+/// The whole `config.toml`; see config.example.toml for every option. Missing sections
+/// and fields take their defaults.
+#[derive(Debug, Default, Serialize, Deserialize, Clone, PartialEq)]
 #[serde(default)]
 pub struct Config {
+    /// `[monitoring]`: sampling duration and interval for `monitor` and `report`.
     pub monitoring: MonitoringConfig,
+    /// `[thresholds]`: limits for the built-in alerts and findings.
     pub thresholds: ThresholdConfig,
+    /// `[notifications]`: desktop notifications and their cooldown.
     pub notifications: NotificationConfig,
+    /// `[daemon]`: check interval and log file of the background daemon.
     pub daemon: DaemonConfig,
+    /// `[coolant]`: which processes may be reniced when running hot.
     pub coolant: CoolantConfig,
+    /// `[history]`: where samples and rollups are stored and for how long.
+    pub history: HistoryConfig,
+    /// `[baseline]`: learned hour-of-week baselines.
+    pub baseline: BaselineConfig,
+    /// `[anomaly]`: how far from its baseline a metric must be to be reported.
+    pub anomaly: AnomalyConfig,
+    /// `[leak_detection]`: per-process memory growth detection.
+    pub leak_detection: LeakConfig,
+    /// `[forecast]`: disk fill forecasts.
+    pub forecast: ForecastConfig,
+    /// `[spawn_detection]`: runaway process spawning.
+    pub spawn_detection: SpawnConfig,
+    /// `[health_score]`: weights of the health score components.
+    pub health_score: HealthScoreConfig,
+    /// `[[collectors]]`: custom commands whose output is turned into metrics.
+    pub collectors: Vec<CollectorConfig>,
+    /// `[[log_watches]]`: log files matched against patterns.
+    pub log_watches: Vec<LogWatchConfig>,
+    /// `[[health_checks]]`: TCP and HTTP endpoint checks.
+    pub health_checks: Vec<HealthCheckConfig>,
+    /// `[[process_rules]]`: limits for specific processes.
+    pub process_rules: Vec<ProcessRuleConfig>,
+    /// `[[watch_dirs]]`: directories watched for size and growth.
+    pub watch_dirs: Vec<WatchDirConfig>,
+    /// `[[percentile_alerts]]`: alerts on a percentile over a trailing window.
+    pub percentile_alerts: Vec<PercentileAlertConfig>,
 }
 
-#[derive(Debug, Deserialize, Clone, PartialEq)]
+/// How long `monitor` and `report` sample for.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 #[serde(default)]
 pub struct MonitoringConfig {
+    /// Total sampling time.
     pub duration_secs: u64,
+    /// Time between samples.
     pub sample_interval_secs: u64,
 }
 
-#[derive(Debug, Deserialize, Clone, PartialEq)]
+/// Limits for the built-in alerts and findings.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 #[serde(default)]
 pub struct ThresholdConfig {
+    /// Average CPU usage.
     pub cpu_percent: f64,
+    /// Memory in use.
     pub memory_percent: f64,
+    /// Hottest sensor.
     pub temperature_celsius: f64,
+    /// Usage of any mount.
     pub disk_percent: f64,
+    /// Swap in use.
     pub swap_percent: f64,
+    /// Memory of all browser processes together.
     pub browser_memory_mb: f64,
 }
 
-#[derive(Debug, Deserialize, Clone, PartialEq)]
+/// Desktop notifications for the built-in alerts.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 #[serde(default)]
 pub struct NotificationConfig {
+    /// Send notifications at all.
     pub enabled: bool,
+    /// Alert on `thresholds.cpu_percent`.
     pub cpu_alert: bool,
+    /// Alert on `thresholds.memory_percent`.
     pub memory_alert: bool,
+    /// Alert on `thresholds.temperature_celsius`.
     pub temperature_alert: bool,
+    /// Alert on `thresholds.disk_percent` and disk forecasts.
     pub disk_alert: bool,
+    /// Minimum time between repeats of the same alert.
     pub cooldown_secs: u64,
 }
 
-#[derive(Debug, Deserialize, Clone, PartialEq)]
+/// The background daemon.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 #[serde(default)]
 pub struct DaemonConfig {
+    /// Time between samples.
     pub check_interval_secs: u64,
 }
 
-#[derive(Debug, Deserialize, Clone, PartialEq)]
+/// Lowering the priority of the busiest processes when running hot.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 #[serde(default)]
 pub struct CoolantConfig {
+    /// Offer it in interactive `monitor` runs.
     pub enabled: bool,
+    /// How many of the busiest processes to renice.
     pub top_processes: usize,
+    /// Nice level to set; higher is lower priority.
     pub nice_level: i32,
 }
 
+/// The on-disk store of daemon samples and their rollups.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct HistoryConfig {
+    /// Record every daemon sample.
+    pub enabled: bool,
+    /// Store directory; defaults to `<data dir>/system-monitor/history`.
+    pub path: Option<PathBuf>,
+    /// Raw samples older than this are dropped.
+    pub retention_days: f64,
+    /// The oldest raw samples are dropped once the store is larger.
+    pub max_size_mb: f64,
+    /// Size of each file in the store.
+    pub segment_size_mb: f64,
+    /// Processes kept per sample, largest memory first.
+    pub process_limit: usize,
+    /// How long per-minute rollups are kept.
+    pub minute_retention_days: f64,
+    /// How long per-hour rollups are kept.
+    pub hour_retention_days: f64,
+    /// How long per-day rollups are kept.
+    pub day_retention_days: f64,
+}
+
+/// Learned hour-of-week baselines that samples are scored against.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct BaselineConfig {
+    /// Learn and score baselines.
+    pub enabled: bool,
+    /// Baselines file; defaults to `<data dir>/system-monitor/baselines.json`.
+    pub path: Option<PathBuf>,
+    /// Samples a time slot needs before it is used for scoring.
+    pub min_samples: u64,
+    /// Standard deviations from the slot's mean that count as anomalous.
+    pub z_threshold: f64,
+}
+
+/// Streaming detector that scores each sample against the recent past.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct AnomalyConfig {
+    /// Score every sample.
+    pub enabled: bool,
+    /// How the recent past is summarized.
+    pub mode: AnomalyMode,
+    /// Score at which a value counts as anomalous; lower is more sensitive.
+    pub z_threshold: f64,
+    /// EWMA smoothing factor: the weight of each new sample.
+    pub alpha: f64,
+    /// Samples the MAD mode takes its median from.
+    pub window: usize,
+    /// Samples seen before anything is scored.
+    pub warmup: usize,
+}
+
+/// How the anomaly detector summarizes the recent past.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AnomalyMode {
+    /// Exponentially weighted mean and variance.
+    Ewma,
+    /// Median and median absolute deviation of a sliding window; ignores outliers in it.
+    Mad,
+}
+
+/// Per-process memory growth the daemon reports as a suspected leak.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct LeakConfig {
+    /// Report suspected leaks.
+    pub enabled: bool,
+    /// How long a process must be tracked before it can be reported.
+    pub min_hours: f64,
+    /// How far back the trend line is fitted.
+    pub window_hours: f64,
+    /// Slower growth is not reported.
+    pub min_growth_mb_per_hour: f64,
+    /// How closely memory must follow the trend line (0-1), so spiky usage is not reported.
+    pub min_r_squared: f64,
+}
+
+/// Time-to-full forecasts per mount, fitted to recent disk usage.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct ForecastConfig {
+    /// Forecast when each mount fills up.
+    pub enabled: bool,
+    /// How much usage history the trend is fitted to.
+    pub window_days: f64,
+    /// History needed before a mount is forecast.
+    pub min_hours: f64,
+    /// Notify when a mount is forecast to fill up within this many days.
+    pub horizon_days: f64,
+}
+
+/// Runaway process creation, measured between consecutive samples.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct SpawnConfig {
+    /// Report runaway spawning.
+    pub enabled: bool,
+    /// New children per minute from one parent.
+    pub max_spawns_per_min: f64,
+    /// New processes per minute owned by one user.
+    pub max_user_spawns_per_min: f64,
+    /// Children of one parent running at once.
+    pub max_children: usize,
+}
+
+/// Weights of the sub-scores in the 0-100 health score; only their ratios matter, and a
+/// weight of 0 leaves that sub-score out.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct HealthScoreConfig {
+    /// Average CPU over the run.
+    pub cpu: f64,
+    /// Memory in use.
+    pub memory: f64,
+    /// Swap in use.
+    pub swap: f64,
+    /// The fullest mount.
+    pub disk: f64,
+    /// The hottest sensor, against `thresholds.temperature_celsius`.
+    pub temperature: f64,
+    /// Critical, warning and info findings.
+    pub findings: f64,
+}
+
+/// A command the daemon runs on a schedule, printing `name value` lines or a JSON
+/// object of numbers.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct CollectorConfig {
+    /// Prefix of the metrics it reports.
+    pub name: String,
+    /// Program and arguments.
+    pub command: Vec<String>,
+    /// How often to run it, at most once per daemon check.
+    #[serde(default = "default_collector_interval_secs")]
+    pub interval_secs: u64,
+    /// The command is killed after this long.
+    #[serde(default = "default_collector_timeout_secs")]
+    pub timeout_secs: u64,
+    /// Limits on the metrics it prints.
+    #[serde(default)]
+    pub thresholds: Vec<CustomThreshold>,
+}
+
+/// Alerts when a collector metric leaves `min..=max`.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct CustomThreshold {
+    /// Metric name, as printed by the command.
+    pub metric: String,
+    /// Upper limit.
+    pub max: Option<f64>,
+    /// Lower limit.
+    pub min: Option<f64>,
+}
+
+/// A log file the daemon tails, following rotation.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct LogWatchConfig {
+    /// The log file.
+    pub path: PathBuf,
+    /// Patterns counted in each check.
+    #[serde(default)]
+    pub patterns: Vec<LogPatternConfig>,
+}
+
+/// A regex counted per daemon check.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct LogPatternConfig {
+    /// Name used in alerts.
+    pub name: String,
+    /// Matched against each new line.
+    pub regex: String,
+    /// Matches allowed per check before alerting.
+    #[serde(default)]
+    pub max_matches: u64,
+}
+
+/// A TCP or HTTP endpoint checked on every daemon check.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct HealthCheckConfig {
+    /// Name used in alerts.
+    pub name: String,
+    /// `host:port` to connect to.
+    pub tcp: Option<String>,
+    /// `http://` URL to GET.
+    pub http: Option<String>,
+    /// HTTP status that counts as healthy.
+    #[serde(default = "default_expected_status")]
+    pub expected_status: u16,
+    /// Slower responses count as failures.
+    pub max_latency_ms: Option<u64>,
+    /// Connect and read timeout.
+    #[serde(default = "default_health_timeout_ms")]
+    pub timeout_ms: u64,
+}
+
+/// Limits for the processes that match every `match_*` field set.
+#[derive(Debug, Default, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ProcessRuleConfig {
+    /// Name used in alerts.
+    pub name: String,
+    /// Process name, case-insensitive.
+    pub match_name: Option<String>,
+    /// Regex on the process name.
+    pub match_regex: Option<String>,
+    /// Substring of the full command line.
+    pub match_cmdline: Option<String>,
+    /// Owning user.
+    pub match_user: Option<String>,
+    /// Fewest matching processes allowed.
+    pub min_instances: Option<usize>,
+    /// Most matching processes allowed.
+    pub max_instances: Option<usize>,
+    /// CPU limit per process, in percent of one core.
+    pub max_cpu_percent: Option<f64>,
+    /// Memory limit per process.
+    pub max_memory_mb: Option<f64>,
+    /// Open file limit per process; Linux only.
+    pub max_open_files: Option<usize>,
+}
+
+/// A directory the daemon scans for size, file count and growth.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct WatchDirConfig {
+    /// The directory; `~` expands to the home directory.
+    pub path: PathBuf,
+    /// Total size limit.
+    pub max_size_mb: Option<f64>,
+    /// File count limit.
+    pub max_files: Option<u64>,
+    /// Size growth limit, averaged over recent scans.
+    pub max_growth_mb_per_hour: Option<f64>,
+    /// File count growth limit, averaged over recent scans.
+    pub max_files_growth_per_hour: Option<f64>,
+    /// Time between scans.
+    #[serde(default = "default_watch_scan_interval_secs")]
+    pub scan_interval_secs: u64,
+    /// Largest entries listed in alerts.
+    #[serde(default = "default_watch_top")]
+    pub top: usize,
+}
+
+/// Alerts when a percentile of a metric over a trailing window leaves `min..=max`, e.g.
+/// p95 CPU over 15 minutes above 85%.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct PercentileAlertConfig {
+    /// The series the percentile is taken of.
+    pub metric: Metric,
+    /// Percentile to compare, e.g. 95.
+    #[serde(default = "default_alert_percentile")]
+    pub percentile: f64,
+    /// Trailing window the percentile covers.
+    #[serde(default = "default_alert_window_mins")]
+    pub window_mins: f64,
+    /// In the metric's unit: percent, bytes per second or °C.
+    pub max: Option<f64>,
+    /// Lower limit, in the same unit as `max`.
+    pub min: Option<f64>,
+}
+
+fn default_collector_interval_secs() -> u64 {
+    60
+}
+
+fn default_collector_timeout_secs() -> u64 {
+    10
+}
+
+fn default_watch_scan_interval_secs() -> u64 {
+    300
+}
+
+fn default_watch_top() -> usize {
+    5
+}
+
+fn default_alert_percentile() -> f64 {
+    95.0
+}
+
+fn default_alert_window_mins() -> f64 {
+    15.0
+}
+
+fn default_expected_status() -> u16 {
+    200
+}
+
+fn default_health_timeout_ms() -> u64 {
+    2000
+}
+
 impl Default for MonitoringConfig {
     fn default() -> Self {
         Self {
@@ -107,16 +460,122 @@ impl Default for CoolantConfig {
     }
 }
 
+impl Default for HistoryConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            path: None,
+            retention_days: 7.0,
+            max_size_mb: 256.0,
+            segment_size_mb: 8.0,
+            process_limit: 20,
+            minute_retention_days: 30.0,
+            hour_retention_days: 365.0,
+            day_retention_days: 1825.0,
+        }
+    }
+}
+
+impl Default for BaselineConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            path: None,
+            min_samples: 10,
+            z_threshold: 3.0,
+        }
+    }
+}
+
+impl Default for LeakConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            min_hours: 2.0,
+            window_hours: 6.0,
+            min_growth_mb_per_hour: 10.0,
+            min_r_squared: 0.8,
+        }
+    }
+}
+
+impl Default for ForecastConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            window_days: 7.0,
+            min_hours: 6.0,
+            horizon_days: 7.0,
+        }
+    }
+}
+
+impl Default for SpawnConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_spawns_per_min: 60.0,
+            max_user_spawns_per_min: 120.0,
+            max_children: 500,
+        }
+    }
+}
+
+impl Default for HealthScoreConfig {
+    fn default() -> Self {
+        Self {
+            cpu: 20.0,
+            memory: 20.0,
+            swap: 10.0,
+            disk: 15.0,
+            temperature: 10.0,
+            findings: 25.0,
+        }
+    }
+}
+
+impl AnomalyMode {
+    /// `ewma` or `mad`.
+    pub fn name(self) -> &'static str {
+        match self {
+            AnomalyMode::Ewma => "ewma",
+            AnomalyMode::Mad => "mad",
+        }
+    }
+}
+
+impl Default for AnomalyConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            mode: AnomalyMode::Ewma,
+            z_threshold: 4.0,
+            alpha: 0.1,
+            window: 60,
+            warmup: 20,
+        }
+    }
+}
+
 pub fn default_config_path() -> Option<PathBuf> {
     dirs::config_dir().map(|dir| dir.join("system-monitor").join("config.toml"))
 }
 
-pub fn load(path: Option<&Path>) -> Config {
-    let config_path = path
-        .map(PathBuf::from)
-        .or_else(default_config_path);
+pub fn default_history_dir() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join("system-monitor").join("history"))
+}
 
-    let Some(config_path) = config_path else {
+pub fn default_baseline_path() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join("system-monitor").join("baselines.json"))
+}
+
+/// The file [`load`] reads: `path` if given, otherwise [`default_config_path`].
+pub fn config_path(path: Option<&Path>) -> Option<PathBuf> {
+    path.map(PathBuf::from).or_else(default_config_path)
+}
+
+pub fn load(path: Option<&Path>) -> Config {
+    let Some(config_path) = config_path(path) else {
         return Config::default();
     };
 
@@ -130,8 +589,8 @@ pub fn load(path: Option<&Path>) -> Config {
     })
 }
 
-pub fn display_config(config: &Config) {
-    println!("Config file location: {}", default_config_path()
+pub fn display_config(config: &Config, config_path: Option<&Path>) {
+    println!("Config file location: {}", config_path
         .map(|p| p.display().to_string())
         .unwrap_or_else(|| "unknown".to_string()));
     println!();
@@ -162,6 +621,157 @@ pub fn display_config(config: &Config) {
     println!("  enabled = {}", config.coolant.enabled);
     println!("  top_processes = {}", config.coolant.top_processes);
     println!("  nice_level = {}", config.coolant.nice_level);
+    println!();
+    println!("[history]");
+    println!("  enabled = {}", config.history.enabled);
+    println!("  path = {:?}", config.history.path.clone()
+        .or_else(default_history_dir)
+        .map(|p| p.display().to_string())
+        .unwrap_or_else(|| "unknown".to_string()));
+    println!("  retention_days = {}", config.history.retention_days);
+    println!("  max_size_mb = {}", config.history.max_size_mb);
+    println!("  segment_size_mb = {}", config.history.segment_size_mb);
+    println!("  process_limit = {}", config.history.process_limit);
+    println!("  minute_retention_days = {}", config.history.minute_retention_days);
+    println!("  hour_retention_days = {}", config.history.hour_retention_days);
+    println!("  day_retention_days = {}", config.history.day_retention_days);
+    println!();
+    println!("[baseline]");
+    println!("  enabled = {}", config.baseline.enabled);
+    println!("  path = {:?}", config.baseline.path.clone()
+        .or_else(default_baseline_path)
+        .map(|p| p.display().to_string())
+        .unwrap_or_else(|| "unknown".to_string()));
+    println!("  min_samples = {}", config.baseline.min_samples);
+    println!("  z_threshold = {}", config.baseline.z_threshold);
+    println!();
+    println!("[anomaly]");
+    println!("  enabled = {}", config.anomaly.enabled);
+    println!("  mode = {:?}", config.anomaly.mode.name());
+    println!("  z_threshold = {}", config.anomaly.z_threshold);
+    println!("  alpha = {}", config.anomaly.alpha);
+    println!("  window = {}", config.anomaly.window);
+    println!("  warmup = {}", config.anomaly.warmup);
+    println!();
+    println!("[leak_detection]");
+    println!("  enabled = {}", config.leak_detection.enabled);
+    println!("  min_hours = {}", config.leak_detection.min_hours);
+    println!("  window_hours = {}", config.leak_detection.window_hours);
+    println!("  min_growth_mb_per_hour = {}", config.leak_detection.min_growth_mb_per_hour);
+    println!("  min_r_squared = {}", config.leak_detection.min_r_squared);
+    println!();
+    println!("[forecast]");
+    println!("  enabled = {}", config.forecast.enabled);
+    println!("  window_days = {}", config.forecast.window_days);
+    println!("  min_hours = {}", config.forecast.min_hours);
+    println!("  horizon_days = {}", config.forecast.horizon_days);
+    println!();
+    println!("[spawn_detection]");
+    println!("  enabled = {}", config.spawn_detection.enabled);
+    println!("  max_spawns_per_min = {}", config.spawn_detection.max_spawns_per_min);
+    println!("  max_user_spawns_per_min = {}", config.spawn_detection.max_user_spawns_per_min);
+    println!("  max_children = {}", config.spawn_detection.max_children);
+    println!();
+    println!("[health_score]");
+    println!("  cpu = {}", config.health_score.cpu);
+    println!("  memory = {}", config.health_score.memory);
+    println!("  swap = {}", config.health_score.swap);
+    println!("  disk = {}", config.health_score.disk);
+    println!("  temperature = {}", config.health_score.temperature);
+    println!("  findings = {}", config.health_score.findings);
+    for collector in &config.collectors {
+        println!();
+        println!("[[collectors]]");
+        println!("  name = {:?}", collector.name);
+        println!("  command = {:?}", collector.command);
+        println!("  interval_secs = {}", collector.interval_secs);
+        println!("  timeout_secs = {}", collector.timeout_secs);
+        for threshold in &collector.thresholds {
+            println!(
+                "  threshold {}: min = {}, max = {}",
+                threshold.metric,
+                threshold.min.map(|v| v.to_string()).unwrap_or_else(|| "-".to_string()),
+                threshold.max.map(|v| v.to_string()).unwrap_or_else(|| "-".to_string()),
+            );
+        }
+    }
+    for watch in &config.log_watches {
+        println!();
+        println!("[[log_watches]]");
+        println!("  path = {:?}", watch.path.display().to_string());
+        for pattern in &watch.patterns {
+            println!("  pattern {}: /{}/ (max_matches = {})", pattern.name, pattern.regex, pattern.max_matches);
+        }
+    }
+    for check in &config.health_checks {
+        println!();
+        println!("[[health_checks]]");
+        println!("  name = {:?}", check.name);
+        if let Some(address) = &check.tcp {
+            println!("  tcp = {address:?}");
+        }
+        if let Some(url) = &check.http {
+            println!("  http = {url:?}");
+            println!("  expected_status = {}", check.expected_status);
+        }
+        if let Some(budget) = check.max_latency_ms {
+            println!("  max_latency_ms = {budget}");
+        }
+        println!("  timeout_ms = {}", check.timeout_ms);
+    }
+    for rule in &config.process_rules {
+        println!();
+        println!("[[process_rules]]");
+        println!("  name = {:?}", rule.name);
+        let fields = [
+            ("match_name", rule.match_name.as_ref().map(|v| format!("{v:?}"))),
+            ("match_regex", rule.match_regex.as_ref().map(|v| format!("{v:?}"))),
+            ("match_cmdline", rule.match_cmdline.as_ref().map(|v| format!("{v:?}"))),
+            ("match_user", rule.match_user.as_ref().map(|v| format!("{v:?}"))),
+            ("min_instances", rule.min_instances.map(|v| v.to_string())),
+            ("max_instances", rule.max_instances.map(|v| v.to_string())),
+            ("max_cpu_percent", rule.max_cpu_percent.map(|v| v.to_string())),
+            ("max_memory_mb", rule.max_memory_mb.map(|v| v.to_string())),
+            ("max_open_files", rule.max_open_files.map(|v| v.to_string())),
+        ];
+        for (key, value) in fields {
+            if let Some(value) = value {
+                println!("  {key} = {value}");
+            }
+        }
+    }
+    for watch in &config.watch_dirs {
+        println!();
+        println!("[[watch_dirs]]");
+        println!("  path = {:?}", watch.path.display().to_string());
+        if let Some(max) = watch.max_size_mb {
+            println!("  max_size_mb = {max}");
+        }
+        if let Some(max) = watch.max_files {
+            println!("  max_files = {max}");
+        }
+        if let Some(max) = watch.max_growth_mb_per_hour {
+            println!("  max_growth_mb_per_hour = {max}");
+        }
+        if let Some(max) = watch.max_files_growth_per_hour {
+            println!("  max_files_growth_per_hour = {max}");
+        }
+        println!("  scan_interval_secs = {}", watch.scan_interval_secs);
+        println!("  top = {}", watch.top);
+    }
+    for alert in &config.percentile_alerts {
+        println!();
+        println!("[[percentile_alerts]]");
+        println!("  metric = {:?}", alert.metric.name());
+        println!("  percentile = {}", alert.percentile);
+        println!("  window_mins = {}", alert.window_mins);
+        if let Some(max) = alert.max {
+            println!("  max = {max}");
+        }
+        if let Some(min) = alert.min {
+            println!("  min = {min}");
+        }
+    }
 }
 
 #[cfg(test)]
@@ -202,6 +812,64 @@ mod tests {
                 top_processes: 3,
                 nice_level: 15,
             },
+            history: HistoryConfig {
+                enabled: true,
+                path: None,
+                retention_days: 7.0,
+                max_size_mb: 256.0,
+                segment_size_mb: 8.0,
+                process_limit: 20,
+                minute_retention_days: 30.0,
+                hour_retention_days: 365.0,
+                day_retention_days: 1825.0,
+            },
+            baseline: BaselineConfig {
+                enabled: true,
+                path: None,
+                min_samples: 10,
+                z_threshold: 3.0,
+            },
+            anomaly: AnomalyConfig {
+                enabled: true,
+                mode: AnomalyMode::Ewma,
+                z_threshold: 4.0,
+                alpha: 0.1,
+                window: 60,
+                warmup: 20,
+            },
+            leak_detection: LeakConfig {
+                enabled: true,
+                min_hours: 2.0,
+                window_hours: 6.0,
+                min_growth_mb_per_hour: 10.0,
+                min_r_squared: 0.8,
+            },
+            forecast: ForecastConfig {
+                enabled: true,
+                window_days: 7.0,
+                min_hours: 6.0,
+                horizon_days: 7.0,
+            },
+            spawn_detection: SpawnConfig {
+                enabled: true,
+                max_spawns_per_min: 60.0,
+                max_user_spawns_per_min: 120.0,
+                max_children: 500,
+            },
+            health_score: HealthScoreConfig {
+                cpu: 20.0,
+                memory: 20.0,
+                swap: 10.0,
+                disk: 15.0,
+                temperature: 10.0,
+                findings: 25.0,
+            },
+            collectors: Vec::new(),
+            log_watches: Vec::new(),
+            health_checks: Vec::new(),
+            process_rules: Vec::new(),
+            watch_dirs: Vec::new(),
+            percentile_alerts: Vec::new(),
         });
     }
 
@@ -283,7 +951,263 @@ nice_level = 10
                 top_processes: 5,
                 nice_level: 10,
             },
+            history: HistoryConfig::default(),
+            baseline: BaselineConfig::default(),
+            anomaly: AnomalyConfig::default(),
+            leak_detection: LeakConfig::default(),
+            forecast: ForecastConfig::default(),
+            spawn_detection: SpawnConfig::default(),
+            health_score: HealthScoreConfig::default(),
+            collectors: Vec::new(),
+            log_watches: Vec::new(),
+            health_checks: Vec::new(),
+            process_rules: Vec::new(),
+            watch_dirs: Vec::new(),
+            percentile_alerts: Vec::new(),
+        });
+    }
+
+    #[test]
+    fn collectors_parse_with_defaults_and_thresholds() {
+        let toml_content = r#"
+[[collectors]]
+name = "build_farm"
+command = ["/usr/local/bin/backlog", "--json"]
+
+[[collectors.thresholds]]
+metric = "backlog"
+max = 50.0
+
+[[collectors]]
+name = "licenses"
+command = ["license-seats"]
+interval_secs = 300
+timeout_secs = 5
+"#;
+        let config: Config = toml::from_str(toml_content).unwrap();
+
+        assert_eq!(config.collectors.len(), 2);
+        assert_eq!(config.collectors[0], CollectorConfig {
+            name: "build_farm".to_string(),
+            command: vec!["/usr/local/bin/backlog".to_string(), "--json".to_string()],
+            interval_secs: 60,
+            timeout_secs: 10,
+            thresholds: vec![CustomThreshold {
+                metric: "backlog".to_string(),
+                max: Some(50.0),
+                min: None,
+            }],
         });
+        assert_eq!(config.collectors[1].interval_secs, 300);
+        assert_eq!(config.collectors[1].timeout_secs, 5);
+        assert!(config.collectors[1].thresholds.is_empty());
+    }
+
+    #[test]
+    fn log_watches_parse_patterns() {
+        let toml_content = r#"
+[[log_watches]]
+path = "/var/log/syslog"
+
+[[log_watches.patterns]]
+name = "oom"
+regex = "Out of memory"
+
+[[log_watches.patterns]]
+name = "io_errors"
+regex = "I/O error"
+max_matches = 5
+"#;
+        let config: Config = toml::from_str(toml_content).unwrap();
+
+        assert_eq!(config.log_watches, vec![LogWatchConfig {
+            path: PathBuf::from("/var/log/syslog"),
+            patterns: vec![
+                LogPatternConfig {
+                    name: "oom".to_string(),
+                    regex: "Out of memory".to_string(),
+                    max_matches: 0,
+                },
+                LogPatternConfig {
+                    name: "io_errors".to_string(),
+                    regex: "I/O error".to_string(),
+                    max_matches: 5,
+                },
+            ],
+        }]);
+    }
+
+    #[test]
+    fn health_checks_parse_tcp_and_http() {
+        let toml_content = r#"
+[[health_checks]]
+name = "postgres"
+tcp = "127.0.0.1:5432"
+
+[[health_checks]]
+name = "api"
+http = "http://localhost:8080/health"
+expected_status = 204
+max_latency_ms = 250
+timeout_ms = 1000
+"#;
+        let config: Config = toml::from_str(toml_content).unwrap();
+
+        assert_eq!(config.health_checks, vec![
+            HealthCheckConfig {
+                name: "postgres".to_string(),
+                tcp: Some("127.0.0.1:5432".to_string()),
+                http: None,
+                expected_status: 200,
+                max_latency_ms: None,
+                timeout_ms: 2000,
+            },
+            HealthCheckConfig {
+                name: "api".to_string(),
+                tcp: None,
+                http: Some("http://localhost:8080/health".to_string()),
+                expected_status: 204,
+                max_latency_ms: Some(250),
+                timeout_ms: 1000,
+            },
+        ]);
+    }
+
+    #[test]
+    fn process_rules_parse_matchers_and_limits() {
+        let toml_content = r#"
+[[process_rules]]
+name = "postgres"
+match_name = "postgres"
+min_instances = 1
+
+[[process_rules]]
+name = "node"
+match_regex = "^node"
+match_user = "deploy"
+max_memory_mb = 2048.0
+max_open_files = 4096
+"#;
+        let config: Config = toml::from_str(toml_content).unwrap();
+
+        assert_eq!(config.process_rules, vec![
+            ProcessRuleConfig {
+                name: "postgres".to_string(),
+                match_name: Some("postgres".to_string()),
+                min_instances: Some(1),
+                ..ProcessRuleConfig::default()
+            },
+            ProcessRuleConfig {
+                name: "node".to_string(),
+                match_regex: Some("^node".to_string()),
+                match_user: Some("deploy".to_string()),
+                max_memory_mb: Some(2048.0),
+                max_open_files: Some(4096),
+                ..ProcessRuleConfig::default()
+            },
+        ]);
+    }
+
+    #[test]
+    fn process_rules_require_a_name() {
+        let toml_content = r#"
+[[process_rules]]
+match_name = "postgres"
+"#;
+        assert!(toml::from_str::<Config>(toml_content).is_err());
+    }
+
+    #[test]
+    fn watch_dirs_parse_with_defaults() {
+        let toml_content = r#"
+[[watch_dirs]]
+path = "~/Downloads"
+max_size_mb = 10240.0
+max_growth_mb_per_hour = 500.0
+max_files_growth_per_hour = 2000.0
+"#;
+        let config: Config = toml::from_str(toml_content).unwrap();
+
+        assert_eq!(config.watch_dirs, vec![WatchDirConfig {
+            path: PathBuf::from("~/Downloads"),
+            max_size_mb: Some(10240.0),
+            max_files: None,
+            max_growth_mb_per_hour: Some(500.0),
+            max_files_growth_per_hour: Some(2000.0),
+            scan_interval_secs: 300,
+            top: 5,
+        }]);
+    }
+
+    #[test]
+    fn percentile_alerts_parse_with_defaults() {
+        let toml_content = r#"
+[[percentile_alerts]]
+metric = "cpu"
+max = 85.0
+
+[[percentile_alerts]]
+metric = "network-rx"
+percentile = 99.0
+window_mins = 60.0
+max = 10485760.0
+"#;
+        let config: Config = toml::from_str(toml_content).unwrap();
+
+        assert_eq!(config.percentile_alerts, vec![
+            PercentileAlertConfig { metric: Metric::Cpu, percentile: 95.0, window_mins: 15.0, max: Some(85.0), min: None },
+            PercentileAlertConfig {
+                metric: Metric::NetworkRx,
+                percentile: 99.0,
+                window_mins: 60.0,
+                max: Some(10_485_760.0),
+                min: None,
+            },
+        ]);
+    }
+
+    #[test]
+    fn history_section_overrides_defaults() {
+        let toml_content = r#"
+[history]
+path = "/var/lib/system-monitor"
+retention_days = 30.0
+"#;
+        let config: Config = toml::from_str(toml_content).unwrap();
+
+        assert_eq!(config.history.path, Some(PathBuf::from("/var/lib/system-monitor")));
+        assert_eq!(config.history.retention_days, 30.0);
+        assert_eq!(config.history.max_size_mb, 256.0);
+        assert!(config.history.enabled);
+    }
+
+    #[test]
+    fn baseline_section_overrides_defaults() {
+        let toml_content = r#"
+[baseline]
+path = "/var/lib/system-monitor/baselines.json"
+z_threshold = 4.0
+"#;
+        let config: Config = toml::from_str(toml_content).unwrap();
+
+        assert_eq!(config.baseline.path, Some(PathBuf::from("/var/lib/system-monitor/baselines.json")));
+        assert_eq!(config.baseline.z_threshold, 4.0);
+        assert_eq!(config.baseline.min_samples, 10);
+        assert!(config.baseline.enabled);
+    }
+
+    #[test]
+    fn anomaly_mode_parses_from_snake_case() {
+        let toml_content = r#"
+[anomaly]
+mode = "mad"
+z_threshold = 5.0
+"#;
+        let config: Config = toml::from_str(toml_content).unwrap();
+
+        assert_eq!(config.anomaly.mode, AnomalyMode::Mad);
+        assert_eq!(config.anomaly.z_threshold, 5.0);
+        assert_eq!(config.anomaly.warmup, 20);
     }
 
     #[test]
@@ -109,6 +109,12 @@ mod tests {
             cpu_usage,
             memory_usage: 0,
             disk_usage: 0,
+            cmd: Vec::new(),
+            user: None,
+            zombie: false,
+            start_time: 0,
+            threads: None,
+            parent: None,
         }
     }
 
@@ -0,0 +1,218 @@
+//! Per-core load analysis that spots single-threaded bottlenecks: one core pinned while
+//! the rest idle, and load that stays concentrated on whichever core is busiest.
+
+use std::collections::HashMap;
+use sysinfo::PidExt;
+use crate::types::{Finding, FindingCategory, ProcessMetrics, Severity, Subject, SystemMetrics};
+
+/// Fewer samples than this say nothing about persistence.
+const MIN_SAMPLES: usize = 5;
+/// A core at or above this percent is saturated.
+const PINNED_PERCENT: f64 = 90.0;
+/// The other cores averaging below this percent are idle next to a saturated one.
+const IDLE_PERCENT: f64 = 30.0;
+/// Share of samples a core must be pinned in to count as persistently pinned.
+const PINNED_SHARE: f64 = 0.8;
+/// Average gap, in percentage points, between the busiest core and the mean of the rest.
+const IMBALANCE_POINTS: f64 = 50.0;
+/// A process averaging between these percents of one core could be a single busy thread.
+const ONE_CORE: (f64, f64) = (70.0, 130.0);
+
+/// Findings for cores that stay saturated while the others idle, or for load that stays
+/// lopsided across cores, naming the process most likely to be the single busy thread.
+pub fn core_findings(metrics_history: &[SystemMetrics]) -> Vec<Finding> {
+    let samples: Vec<&SystemMetrics> = metrics_history.iter().filter(|sample| sample.cpu_usage.len() > 1).collect();
+    if samples.len() < MIN_SAMPLES {
+        return Vec::new();
+    }
+    let cores = samples.iter().map(|sample| sample.cpu_usage.len()).min().unwrap_or_default();
+
+    let mut pinned_samples = vec![0usize; cores];
+    let mut core_totals = vec![0.0; cores];
+    let mut gap_total = 0.0;
+    for sample in &samples {
+        let usage: Vec<f64> = sample.cpu_usage[..cores].iter().map(|&usage| f64::from(usage)).collect();
+        let total: f64 = usage.iter().sum();
+        for (core, &value) in usage.iter().enumerate() {
+            core_totals[core] += value;
+            let others = (total - value) / (cores - 1) as f64;
+            if value >= PINNED_PERCENT && others < IDLE_PERCENT {
+                pinned_samples[core] += 1;
+            }
+        }
+        let busiest = usage.iter().copied().fold(0.0, f64::max);
+        gap_total += busiest - (total - busiest) / (cores - 1) as f64;
+    }
+
+    let count = samples.len() as f64;
+    let pinned: Vec<usize> = (0..cores)
+        .filter(|&core| pinned_samples[core] as f64 / count >= PINNED_SHARE)
+        .collect();
+    let culprit = likely_process(&samples);
+    let named = culprit.as_ref().map(|culprit| format!(", likely {}", culprit.describe())).unwrap_or_default();
+    let action = Some(match &culprit {
+        Some(culprit) => format!("Check whether {} can use more threads, or split its work across processes", culprit.name),
+        None => "Look for a single-threaded process saturating one core".to_string(),
+    });
+
+    if !pinned.is_empty() {
+        let average = pinned.iter().map(|&core| core_totals[core] / count).fold(0.0, f64::max);
+        return vec![Finding {
+            id: "cpu_pinned".to_string(),
+            category: FindingCategory::Cpu,
+            severity: Severity::Warning,
+            subject: Subject::Cores { cores: pinned.clone() },
+            summary: format!(
+                "Core {} pinned at {average:.0}% while the others idle{named}",
+                pinned.iter().map(ToString::to_string).collect::<Vec<_>>().join(", ")
+            ),
+            value: Some(average),
+            threshold: Some(PINNED_PERCENT),
+            action,
+            anomaly: None,
+        }];
+    }
+
+    let gap = gap_total / count;
+    if gap >= IMBALANCE_POINTS {
+        return vec![Finding {
+            id: "cpu_imbalance".to_string(),
+            category: FindingCategory::Cpu,
+            severity: Severity::Info,
+            subject: Subject::System,
+            summary: format!("CPU load is uneven: the busiest core averages {gap:.0} points above the others{named}"),
+            value: Some(gap),
+            threshold: Some(IMBALANCE_POINTS),
+            action,
+            anomaly: None,
+        }];
+    }
+    Vec::new()
+}
+
+struct Culprit {
+    pid: u32,
+    name: String,
+    threads: Option<u32>,
+    average: f64,
+}
+
+impl Culprit {
+    /// `encoder (PID 812, 1 thread) at 99% of a core`.
+    fn describe(&self) -> String {
+        let threads = match self.threads {
+            Some(1) => ", 1 thread".to_string(),
+            Some(threads) => format!(", {threads} threads"),
+            None => String::new(),
+        };
+        format!("{} (PID {}{threads}) at {:.0}% of a core", self.name, self.pid, self.average)
+    }
+}
+
+/// The process that kept about one core busy across at least half the samples, preferring
+/// single-threaded ones, then the fewest threads, then the busiest.
+fn likely_process(samples: &[&SystemMetrics]) -> Option<Culprit> {
+    let mut usage: HashMap<(u32, u64), (&ProcessMetrics, f64, usize)> = HashMap::new();
+    for process in samples.iter().flat_map(|sample| &sample.process_metrics).filter(|process| !process.zombie) {
+        let entry = usage.entry((process.pid.as_u32(), process.start_time)).or_insert((process, 0.0, 0));
+        entry.0 = process;
+        entry.1 += f64::from(process.cpu_usage);
+        entry.2 += 1;
+    }
+    usage
+        .into_values()
+        .filter(|(_, _, seen)| *seen * 2 >= samples.len())
+        .map(|(process, total, seen)| Culprit {
+            pid: process.pid.as_u32(),
+            name: process.name.clone(),
+            threads: process.threads,
+            average: total / seen as f64,
+        })
+        .filter(|culprit| (ONE_CORE.0..=ONE_CORE.1).contains(&culprit.average))
+        .min_by(|a, b| {
+            (a.threads != Some(1))
+                .cmp(&(b.threads != Some(1)))
+                .then(a.threads.unwrap_or(u32::MAX).cmp(&b.threads.unwrap_or(u32::MAX)))
+                .then(b.average.total_cmp(&a.average))
+                .then(a.pid.cmp(&b.pid))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::*;
+
+    fn process(pid: usize, cpu_usage: f32, threads: u32) -> ProcessMetrics {
+        ProcessMetrics {
+            name: format!("proc{pid}"),
+            pid: sysinfo::Pid::from(pid),
+            cpu_usage,
+            memory_usage: 0,
+            disk_usage: 0,
+            cmd: Vec::new(),
+            user: None,
+            zombie: false,
+            start_time: 100,
+            threads: Some(threads),
+            parent: None,
+        }
+    }
+
+    fn sample(minute: u64, cpu_usage: Vec<f32>, process_metrics: Vec<ProcessMetrics>) -> SystemMetrics {
+        SystemMetrics {
+            cpu_usage,
+            process_metrics,
+            ..SystemMetrics::sample(minute * 60, 0.0)
+        }
+    }
+
+    /// A multi-threaded process spread thin and a single-threaded one at about one core.
+    fn processes() -> Vec<ProcessMetrics> {
+        vec![process(1, 100.0, 24), process(2, 98.0, 1), process(3, 10.0, 4)]
+    }
+
+    #[test]
+    fn pinned_core_names_the_single_threaded_process() {
+        let history: Vec<SystemMetrics> = (0..10)
+            .map(|minute| sample(minute, vec![5.0, 99.0, 8.0, 4.0], processes()))
+            .collect();
+        let findings = core_findings(&history);
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].id, "cpu_pinned");
+        assert_eq!(findings[0].subject, Subject::Cores { cores: vec![1] });
+        assert_eq!(
+            findings[0].summary,
+            "Core 1 pinned at 99% while the others idle, likely proc2 (PID 2, 1 thread) at 98% of a core"
+        );
+    }
+
+    #[test]
+    fn a_thread_moving_between_cores_shows_as_imbalance() {
+        let history: Vec<SystemMetrics> = (0..8)
+            .map(|minute| {
+                let mut cpu_usage = vec![5.0; 4];
+                cpu_usage[minute as usize % 4] = 97.0;
+                sample(minute, cpu_usage, processes())
+            })
+            .collect();
+        let findings = core_findings(&history);
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].id, "cpu_imbalance");
+        assert_eq!(findings[0].value, Some(92.0));
+        assert!(findings[0].summary.contains("likely proc2"));
+    }
+
+    #[test]
+    fn busy_or_brief_load_is_not_a_bottleneck() {
+        let saturated: Vec<SystemMetrics> = (0..10)
+            .map(|minute| sample(minute, vec![95.0, 99.0, 92.0, 97.0], Vec::new()))
+            .collect();
+        assert!(core_findings(&saturated).is_empty());
+
+        let brief: Vec<SystemMetrics> = (0..4)
+            .map(|minute| sample(minute, vec![5.0, 99.0, 8.0, 4.0], Vec::new()))
+            .collect();
+        assert!(core_findings(&brief).is_empty());
+    }
+}
@@ -1,12 +1,27 @@
 use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant, SystemTime};
 use sysinfo::{System, SystemExt};
+use crate::analysis::AnomalyDetectors;
+use crate::baseline::{baseline_path, Baselines};
+use crate::collectors::{evaluate_thresholds, CollectorScheduler};
 use crate::config::Config;
+use crate::dir_watch::{evaluate_report, DirWatcher};
+use crate::display::format_finding;
+use crate::forecast::{forecast_alerts, forecast_findings, DiskForecast, DiskTracker};
+use crate::health_checks::{evaluate_results, HealthMonitor};
+use crate::health_score::{health_score, HealthScore};
+use crate::history::HistoryStore;
+use crate::leaks::LeakTracker;
+use crate::log_watch::{build_watchers, evaluate_hits};
+use crate::process_rules::{build_rules, evaluate_rules, ProcessRule};
 use crate::metrics::collect_system_metrics;
-use crate::notifications::NotificationManager;
+use crate::notifications::{Alert, NotificationManager};
+use crate::percentile_alerts::PercentileAlerts;
 use crate::security::{perform_security_analysis, generate_recommendations};
-use crate::types::{MetricsScope, SystemMetrics};
+use crate::types::{Finding, FindingCategory, MetricsScope, SecurityAnalysis, Severity, SystemMetrics};
+use crate::Analysis;
 
 pub fn run_daemon(config: &Config) {
     let running = Arc::new(AtomicBool::new(true));
@@ -34,8 +49,17 @@ pub fn run_daemon(config: &Config) {
 
     let mut notification_manager = NotificationManager::new(config.notifications.cooldown_secs);
     let interval = Duration::from_secs(config.daemon.check_interval_secs);
-    let max_history = 10;
-    let mut metrics_history: Vec<SystemMetrics> = Vec::with_capacity(max_history);
+    let mut metrics_history: Vec<SystemMetrics> = Vec::with_capacity(MAX_HISTORY);
+    let process_rules = build_rules(&config.process_rules);
+    let check_alerts = spawn_checks(config, &running, interval);
+    let mut health_band: Option<&str> = None;
+    let mut history_store = open_history_store(config);
+    let mut last_compaction: Option<Instant> = None;
+    let mut state = SampleState::new(Baselines::load_configured(&config.baseline), config);
+    let mut last_baseline_save = Instant::now();
+    if let Some(store) = &history_store {
+        seed_from_history(&mut state, store, config);
+    }
 
     while running.load(Ordering::SeqCst) {
         sys.refresh_all();
@@ -45,60 +69,319 @@ pub fn run_daemon(config: &Config) {
         let mem_percent = metrics.memory_usage as f64 / metrics.memory_total as f64 * 100.0;
         log::debug!("CPU: {avg_cpu:.1}%, Memory: {mem_percent:.0}%");
 
-        metrics_history.push(metrics);
-        if metrics_history.len() > max_history {
-            metrics_history.remove(0);
+        if let Some(store) = history_store.as_mut() {
+            if let Err(e) = store.append(&metrics) {
+                log::warn!("Failed to write history sample: {e}");
+            }
+            if last_compaction.is_none_or(|last| last.elapsed() >= COMPACTION_INTERVAL) {
+                match store.compact(SystemTime::now()) {
+                    Ok(stats) if stats.segments_removed > 0 => log::info!(
+                        "Compacted history: removed {} segment(s), {} bytes",
+                        stats.segments_removed,
+                        stats.bytes_freed,
+                    ),
+                    Ok(_) => {}
+                    Err(e) => log::warn!("Failed to compact history: {e}"),
+                }
+                last_compaction = Some(Instant::now());
+            }
         }
 
-        notification_manager.check_and_notify(metrics_history.last().unwrap(), config);
-
-        let security_analysis = perform_security_analysis(&sys, &metrics_history, config);
-        let recommendations = generate_recommendations(&metrics_history, &security_analysis, config);
+        push_sample(&mut metrics_history, metrics);
+        let analysis = check_samples(&mut notification_manager, &process_rules, &mut state, &metrics_history, config);
+        if last_baseline_save.elapsed() >= BASELINE_SAVE_INTERVAL {
+            save_baselines(&state.baselines, config);
+            last_baseline_save = Instant::now();
+        }
 
-        for finding in &security_analysis.zombie_processes {
-            log::warn!("Zombie process: {finding}");
+        for alerts in check_alerts.try_iter() {
+            notification_manager.notify_alerts(alerts, config);
         }
-        for finding in &security_analysis.unusual_network_activity {
-            log::warn!("Network: {finding}");
+
+        let band = score_band(analysis.health.score);
+        if health_band.replace(band) != Some(band) {
+            log::info!("Health score now {band}: {}", analysis.health.describe());
+        } else {
+            log::debug!("Health score: {}", analysis.health.describe());
         }
-        for finding in &security_analysis.swap_pressure {
-            log::warn!("Swap: {finding}");
+        for finding in &analysis.security.findings {
+            log_finding(finding);
         }
-        for rec in &recommendations {
-            log::info!("Recommendation: {rec}");
+        for rec in &analysis.recommendations {
+            log::info!("Recommendation: {}", format_finding(rec));
         }
 
         std::thread::sleep(interval);
     }
 
+    save_baselines(&state.baselines, config);
     println!("Daemon stopped.");
 }
 
+/// The band a health score falls in. The daemon logs the score at info on start and when
+/// it moves to another band, and at debug otherwise.
+fn score_band(score: u8) -> &'static str {
+    match score {
+        80.. => "healthy",
+        50..=79 => "degraded",
+        _ => "poor",
+    }
+}
+
+/// Starts a thread per configured family of collectors, log watchers, health checks and
+/// directory watchers, so slow commands, endpoints and directory walks don't hold up
+/// sampling. Their alerts come back over the returned channel.
+fn spawn_checks(config: &Config, running: &Arc<AtomicBool>, interval: Duration) -> Receiver<Vec<Alert>> {
+    let (sender, receiver) = mpsc::channel();
+
+    if !config.collectors.is_empty() {
+        let collectors = config.collectors.clone();
+        let mut scheduler = CollectorScheduler::new();
+        spawn_check("collectors", running, interval, &sender, move || {
+            let custom_metrics = scheduler.run_due(&collectors, Instant::now());
+            for metric in &custom_metrics {
+                log::debug!("Custom metric {}.{} = {}", metric.collector, metric.name, metric.value);
+            }
+            evaluate_thresholds(&collectors, &custom_metrics)
+        });
+    }
+
+    if !config.log_watches.is_empty() {
+        let mut watchers = build_watchers(&config.log_watches);
+        spawn_check("log-watches", running, interval, &sender, move || {
+            let mut alerts = Vec::new();
+            for watcher in &mut watchers {
+                let hits = watcher.poll();
+                for hit in hits.iter().filter(|hit| hit.count > 0) {
+                    log::info!("Log {}: {} match(es) of {}", hit.path.display(), hit.count, hit.pattern);
+                }
+                alerts.extend(evaluate_hits(&hits));
+            }
+            alerts
+        });
+    }
+
+    if !config.health_checks.is_empty() {
+        let checks = config.health_checks.clone();
+        let mut monitor = HealthMonitor::new();
+        spawn_check("health-checks", running, interval, &sender, move || {
+            let results = monitor.run(&checks);
+            for result in &results {
+                match &result.error {
+                    Some(error) => log::warn!(
+                        "Health check {} failed: {error} ({:.0}% available recently)",
+                        result.name,
+                        monitor.history(&result.name)
+                            .map(|history| history.availability() * 100.0)
+                            .unwrap_or_default(),
+                    ),
+                    None => log::debug!(
+                        "Health check {} ok in {}ms (avg {:.0}ms)",
+                        result.name,
+                        result.latency.map(|latency| latency.as_millis()).unwrap_or_default(),
+                        monitor.history(&result.name)
+                            .and_then(|history| history.average_latency_ms())
+                            .unwrap_or_default(),
+                    ),
+                }
+            }
+            evaluate_results(&results)
+        });
+    }
+
+    if !config.watch_dirs.is_empty() {
+        let watches = config.watch_dirs.clone();
+        let mut watcher = DirWatcher::new();
+        spawn_check("dir-watches", running, interval, &sender, move || {
+            let mut alerts = Vec::new();
+            for (watch, report) in watcher.run_due(&watches, Instant::now()) {
+                log::debug!(
+                    "Directory {}: {} bytes in {} files",
+                    report.path.display(),
+                    report.scan.total_size,
+                    report.scan.file_count,
+                );
+                alerts.extend(evaluate_report(watch, &report));
+            }
+            alerts
+        });
+    }
+
+    receiver
+}
+
+/// Runs `check` every `interval` on its own thread until the daemon stops.
+fn spawn_check(
+    name: &str,
+    running: &Arc<AtomicBool>,
+    interval: Duration,
+    sender: &Sender<Vec<Alert>>,
+    mut check: impl FnMut() -> Vec<Alert> + Send + 'static,
+) {
+    let running = running.clone();
+    let sender = sender.clone();
+    let spawned = std::thread::Builder::new().name(name.to_string()).spawn(move || {
+        while running.load(Ordering::SeqCst) {
+            if sender.send(check()).is_err() {
+                break;
+            }
+            std::thread::sleep(interval);
+        }
+    });
+    if let Err(e) = spawned {
+        log::warn!("Failed to start the {name} thread: {e}");
+    }
+}
+
+const COMPACTION_INTERVAL: Duration = Duration::from_secs(3600);
+const BASELINE_SAVE_INTERVAL: Duration = Duration::from_secs(600);
+/// Samples kept in memory as the baseline for findings.
+pub const MAX_HISTORY: usize = 10;
+
+/// Appends a sample, dropping the oldest once [`MAX_HISTORY`] are held.
+pub fn push_sample(metrics_history: &mut Vec<SystemMetrics>, metrics: SystemMetrics) {
+    metrics_history.push(metrics);
+    if metrics_history.len() > MAX_HISTORY {
+        metrics_history.remove(0);
+    }
+}
+
+/// What the sample checks learn from one sample to the next.
+pub struct SampleState {
+    pub baselines: Baselines,
+    pub anomalies: AnomalyDetectors,
+    pub leaks: LeakTracker,
+    pub disks: DiskTracker,
+    /// Refreshed whenever `disks` gains a point.
+    pub disk_forecasts: Vec<DiskForecast>,
+    pub percentiles: PercentileAlerts,
+}
+
+impl SampleState {
+    pub fn new(baselines: Baselines, config: &Config) -> Self {
+        Self {
+            baselines,
+            anomalies: AnomalyDetectors::new(&config.anomaly),
+            leaks: LeakTracker::new(&config.leak_detection),
+            disks: DiskTracker::new(&config.forecast),
+            disk_forecasts: Vec::new(),
+            percentiles: PercentileAlerts::new(&config.percentile_alerts),
+        }
+    }
+}
+
+/// The checks driven only by collected samples: threshold alerts, process rules and
+/// findings for the latest sample, which is then learned into `state`. Replaying a
+/// recording runs the same checks.
+pub fn check_samples(
+    notification_manager: &mut NotificationManager,
+    process_rules: &[ProcessRule],
+    state: &mut SampleState,
+    metrics_history: &[SystemMetrics],
+    config: &Config,
+) -> Analysis {
+    let Some(latest) = metrics_history.last() else {
+        return Analysis { security: SecurityAnalysis::default(), recommendations: Vec::new(), health: HealthScore::default() };
+    };
+    notification_manager.check_and_notify(metrics_history, config);
+    notification_manager.notify_alerts(state.percentiles.observe(metrics_history), config);
+    notification_manager.notify_alerts(evaluate_rules(process_rules, &latest.process_metrics), config);
+
+    let mut security = perform_security_analysis(metrics_history, &state.baselines, config);
+    let spawning: Vec<Finding> = security
+        .findings
+        .iter()
+        .filter(|finding| finding.category == FindingCategory::ProcessSpawning)
+        .cloned()
+        .collect();
+    notification_manager.notify_urgent(&spawning, config);
+    security.add_findings(state.anomalies.observe(metrics_history));
+    security.add_findings(state.leaks.observe(metrics_history));
+    if state.disks.record(latest) {
+        state.disk_forecasts = state.disks.forecasts();
+    }
+    security.add_findings(forecast_findings(&state.disk_forecasts, &config.forecast));
+    notification_manager.notify_alerts(forecast_alerts(&state.disk_forecasts, config), config);
+    let recommendations = generate_recommendations(metrics_history, &security, config);
+    if config.baseline.enabled {
+        state.baselines.observe(metrics_history);
+    }
+    notification_manager.notify_findings(&security.findings, config);
+    let health = health_score(metrics_history, security.findings.iter().chain(&recommendations), config);
+    Analysis { security, recommendations, health }
+}
+
+fn open_history_store(config: &Config) -> Option<HistoryStore> {
+    if !config.history.enabled {
+        return None;
+    }
+    match HistoryStore::open_configured(&config.history) {
+        Ok(store) => {
+            log::info!("Recording history to {}", store.dir().display());
+            Some(store)
+        }
+        Err(e) => {
+            log::warn!("History disabled: {e}");
+            None
+        }
+    }
+}
+
+/// Picks up the per-process memory, disk usage and recent metric values the store recorded
+/// before a restart.
+fn seed_from_history(state: &mut SampleState, store: &HistoryStore, config: &Config) {
+    let now = SystemTime::now();
+    if config.leak_detection.enabled {
+        let window = Duration::from_secs_f64(config.leak_detection.window_hours.max(0.0) * 3600.0);
+        match store.read_range(now.checked_sub(window), None) {
+            Ok(samples) => samples.iter().for_each(|sample| state.leaks.seed(sample)),
+            Err(e) => log::warn!("Failed to read history for leak detection: {e}"),
+        }
+    }
+    if config.forecast.enabled {
+        state.disks.seed_history(store, now);
+        state.disk_forecasts = state.disks.forecasts();
+    }
+    let longest = config.percentile_alerts.iter().map(|alert| alert.window_mins).fold(0.0, f64::max);
+    if longest > 0.0 {
+        match store.read_range(now.checked_sub(Duration::from_secs_f64(longest * 60.0)), None) {
+            Ok(samples) => state.percentiles.seed(&samples),
+            Err(e) => log::warn!("Failed to read history for percentile alerts: {e}"),
+        }
+    }
+}
+
+fn save_baselines(baselines: &Baselines, config: &Config) {
+    if !config.baseline.enabled {
+        return;
+    }
+    let Some(path) = baseline_path(&config.baseline) else {
+        return;
+    };
+    if let Err(e) = baselines.save(&path) {
+        log::warn!("Failed to save baselines to {}: {e}", path.display());
+    }
+}
+
+fn log_finding(finding: &Finding) {
+    match finding.severity {
+        Severity::Critical => log::error!("{}: {}", finding.category.title(), finding.summary),
+        Severity::Warning => log::warn!("{}: {}", finding.category.title(), finding.summary),
+        Severity::Info => log::info!("{}: {}", finding.category.title(), finding.summary),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::notifications::AlertKind;
     use crate::types::*;
-    use std::collections::HashMap;
-    use std::time::Instant;
 
     fn make_test_metrics(cpu: f32, mem_used: u64, mem_total: u64) -> SystemMetrics {
         SystemMetrics {
-            timestamp: Instant::now(),
-            cpu_usage: vec![cpu],
             memory_usage: mem_used,
             memory_total: mem_total,
-            swap_usage: 0,
-            swap_total: 0,
-            network_rx: 0,
-            network_tx: 0,
-            disk_usage: HashMap::new(),
-            process_metrics: Vec::new(),
-            temp_files: TempFileMetrics { total_size: 0, files: Vec::new() },
-            temperature: TemperatureMetrics {
-                cpu_temp: None,
-                gpu_temp: None,
-                components: HashMap::new(),
-            },
+            ..SystemMetrics::sample(0, cpu)
         }
     }
 
@@ -108,14 +391,49 @@ mod tests {
         let mut manager = NotificationManager::new(config.notifications.cooldown_secs);
 
         let low_metrics = make_test_metrics(10.0, 30, 100);
-        manager.check_and_notify(&low_metrics, &config);
+        manager.check_and_notify(std::slice::from_ref(&low_metrics), &config);
         assert!(manager.last_sent_times().is_empty());
 
         let high_metrics = make_test_metrics(95.0, 90, 100);
-        manager.check_and_notify(&high_metrics, &config);
+        manager.check_and_notify(std::slice::from_ref(&high_metrics), &config);
         assert!(!manager.last_sent_times().is_empty());
     }
 
+    #[test]
+    fn slow_checks_run_off_the_sampling_thread() {
+        let running = Arc::new(AtomicBool::new(true));
+        let (sender, receiver) = mpsc::channel();
+        let started = Instant::now();
+        spawn_check("slow", &running, Duration::from_millis(10), &sender, || {
+            std::thread::sleep(Duration::from_millis(200));
+            vec![Alert {
+                kind: AlertKind::Endpoint("api".to_string()),
+                active: true,
+                title: "Endpoint down".to_string(),
+                body: String::new(),
+                recovery: None,
+            }]
+        });
+        drop(sender);
+        assert!(started.elapsed() < Duration::from_millis(100));
+        assert!(receiver.try_recv().is_err());
+
+        let alerts = receiver.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert_eq!(alerts[0].kind, AlertKind::Endpoint("api".to_string()));
+
+        running.store(false, Ordering::SeqCst);
+        while receiver.recv_timeout(Duration::from_secs(5)).is_ok() {}
+    }
+
+    #[test]
+    fn score_bands() {
+        assert_eq!(score_band(100), "healthy");
+        assert_eq!(score_band(80), "healthy");
+        assert_eq!(score_band(79), "degraded");
+        assert_eq!(score_band(50), "degraded");
+        assert_eq!(score_band(49), "poor");
+    }
+
     #[test]
     fn daemon_config_defaults_are_sensible() {
         let config = Config::default();
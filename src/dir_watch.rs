@@ -0,0 +1,349 @@
+use std::collections::{HashMap, VecDeque};
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+use humansize::{format_size, BINARY};
+use crate::config::WatchDirConfig;
+use crate::metrics::walk_files;
+use crate::notifications::{Alert, AlertKind};
+
+/// Growth rates span up to this many recent scans, so one burst between two scans does not
+/// set the rate on its own.
+const GROWTH_WINDOW_SCANS: usize = 4;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DirScan {
+    pub total_size: u64,
+    pub file_count: u64,
+    pub top_contributors: Vec<(String, u64)>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DirReport {
+    pub path: PathBuf,
+    pub scan: DirScan,
+    pub growth_bytes_per_hour: Option<f64>,
+    pub growth_files_per_hour: Option<f64>,
+}
+
+#[derive(Default)]
+pub struct DirWatcher {
+    previous: HashMap<PathBuf, VecDeque<(Instant, DirScan)>>,
+}
+
+impl DirWatcher {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn run_due<'a>(
+        &mut self,
+        watches: &'a [WatchDirConfig],
+        now: Instant,
+    ) -> Vec<(&'a WatchDirConfig, DirReport)> {
+        let mut reports = Vec::new();
+        for watch in watches {
+            let path = expand_home(&watch.path);
+            let due = self.previous.get(&path).and_then(VecDeque::back).is_none_or(|(last, _)| {
+                now.duration_since(*last).as_secs() >= watch.scan_interval_secs
+            });
+            if !due {
+                continue;
+            }
+            if !path.exists() {
+                log::debug!("Watched directory {} does not exist", path.display());
+                continue;
+            }
+
+            let scan = scan_directory(&path, watch.top);
+            reports.push((watch, self.record(path, scan, now)));
+        }
+        reports
+    }
+
+    /// Records `scan` and measures growth from the oldest scan still in the window.
+    pub fn record(&mut self, path: PathBuf, scan: DirScan, now: Instant) -> DirReport {
+        let window = self.previous.entry(path.clone()).or_default();
+        let rates = window.front().and_then(|(first, oldest)| {
+            let hours = now.duration_since(*first).as_secs_f64() / 3600.0;
+            (hours > 0.0).then(|| {
+                (
+                    (scan.total_size as f64 - oldest.total_size as f64) / hours,
+                    (scan.file_count as f64 - oldest.file_count as f64) / hours,
+                )
+            })
+        });
+        window.push_back((now, scan.clone()));
+        if window.len() > GROWTH_WINDOW_SCANS {
+            window.pop_front();
+        }
+
+        DirReport {
+            path,
+            scan,
+            growth_bytes_per_hour: rates.map(|(bytes, _)| bytes),
+            growth_files_per_hour: rates.map(|(_, files)| files),
+        }
+    }
+}
+
+pub fn scan_directory(root: &Path, top: usize) -> DirScan {
+    let mut total_size = 0u64;
+    let mut file_count = 0u64;
+    let mut by_child: HashMap<PathBuf, u64> = HashMap::new();
+
+    for (entry, metadata) in walk_files(root) {
+        let size = metadata.len();
+        total_size += size;
+        file_count += 1;
+
+        let child = entry
+            .path()
+            .strip_prefix(root)
+            .ok()
+            .and_then(|relative| relative.components().next())
+            .map(|first| root.join(first))
+            .unwrap_or_else(|| entry.path().to_path_buf());
+        *by_child.entry(child).or_default() += size;
+    }
+
+    let mut top_contributors: Vec<(String, u64)> = by_child
+        .into_iter()
+        .map(|(path, size)| (path.to_string_lossy().into_owned(), size))
+        .collect();
+    top_contributors.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    top_contributors.truncate(top);
+
+    DirScan {
+        total_size,
+        file_count,
+        top_contributors,
+    }
+}
+
+pub fn evaluate_report(watch: &WatchDirConfig, report: &DirReport) -> Vec<Alert> {
+    let path = report.path.display().to_string();
+    let contributors = format_contributors(&report.scan.top_contributors);
+    let mut alerts = Vec::new();
+
+    if let Some(max_mb) = watch.max_size_mb {
+        let limit = (max_mb * 1024.0 * 1024.0) as u64;
+        alerts.push(Alert {
+            kind: AlertKind::Directory(format!("{path}:size")),
+            active: report.scan.total_size > limit,
+            title: format!("Directory Too Large: {path}"),
+            body: format!(
+                "{} (threshold: {})\n{contributors}",
+                format_size(report.scan.total_size, BINARY),
+                format_size(limit, BINARY),
+            ),
+            recovery: None,
+        });
+    }
+
+    if let Some(max_files) = watch.max_files {
+        alerts.push(Alert {
+            kind: AlertKind::Directory(format!("{path}:files")),
+            active: report.scan.file_count > max_files,
+            title: format!("Too Many Files: {path}"),
+            body: format!("{} files (threshold: {max_files})", report.scan.file_count),
+            recovery: None,
+        });
+    }
+
+    if let (Some(max_mb), Some(growth)) = (watch.max_growth_mb_per_hour, report.growth_bytes_per_hour) {
+        let limit = max_mb * 1024.0 * 1024.0;
+        alerts.push(Alert {
+            kind: AlertKind::Directory(format!("{path}:growth")),
+            active: growth > limit,
+            title: format!("Directory Growing Fast: {path}"),
+            body: format!(
+                "Growing {}/h (threshold: {}/h)\n{contributors}",
+                format_size(growth.max(0.0) as u64, BINARY),
+                format_size(limit as u64, BINARY),
+            ),
+            recovery: None,
+        });
+    }
+
+    if let (Some(max), Some(growth)) = (watch.max_files_growth_per_hour, report.growth_files_per_hour) {
+        alerts.push(Alert {
+            kind: AlertKind::Directory(format!("{path}:files_growth")),
+            active: growth > max,
+            title: format!("Files Piling Up: {path}"),
+            body: format!("{growth:.0} new files/h (threshold: {max}/h)\n{contributors}"),
+            recovery: None,
+        });
+    }
+
+    alerts
+}
+
+fn format_contributors(contributors: &[(String, u64)]) -> String {
+    contributors
+        .iter()
+        .map(|(path, size)| format!("{}: {}", format_size(*size, BINARY), path))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+pub fn expand_home(path: &Path) -> PathBuf {
+    match path.strip_prefix("~") {
+        Ok(rest) => dirs::home_dir().map(|home| home.join(rest)).unwrap_or_else(|| path.to_path_buf()),
+        Err(_) => path.to_path_buf(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    const MB: u64 = 1024 * 1024;
+
+    fn watch(path: &Path) -> WatchDirConfig {
+        WatchDirConfig {
+            path: path.to_path_buf(),
+            max_size_mb: Some(1.0),
+            max_files: Some(2),
+            max_growth_mb_per_hour: Some(10.0),
+            max_files_growth_per_hour: Some(1000.0),
+            scan_interval_secs: 300,
+            top: 2,
+        }
+    }
+
+    fn scan(total_size: u64, file_count: u64) -> DirScan {
+        DirScan {
+            total_size,
+            file_count,
+            top_contributors: vec![("big".to_string(), total_size)],
+        }
+    }
+
+    #[test]
+    fn scan_groups_sizes_by_top_level_child() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("cache")).unwrap();
+        std::fs::write(dir.path().join("cache/a.bin"), vec![0u8; 300]).unwrap();
+        std::fs::write(dir.path().join("cache/b.bin"), vec![0u8; 300]).unwrap();
+        std::fs::write(dir.path().join("notes.txt"), vec![0u8; 100]).unwrap();
+        std::fs::write(dir.path().join("tiny.txt"), vec![0u8; 1]).unwrap();
+
+        let result = scan_directory(dir.path(), 2);
+
+        assert_eq!(result.total_size, 701);
+        assert_eq!(result.file_count, 4);
+        assert_eq!(result.top_contributors, vec![
+            (dir.path().join("cache").to_string_lossy().into_owned(), 600),
+            (dir.path().join("notes.txt").to_string_lossy().into_owned(), 100),
+        ]);
+    }
+
+    #[test]
+    fn size_growth_is_averaged_over_the_scan_window() {
+        let mut watcher = DirWatcher::new();
+        let start = Instant::now();
+        let path = PathBuf::from("/data");
+        let at = |minutes: u64| start + Duration::from_secs(minutes * 60);
+
+        let first = watcher.record(path.clone(), scan(100 * MB, 1), start);
+        assert_eq!(first.growth_bytes_per_hour, None);
+
+        let second = watcher.record(path.clone(), scan(150 * MB, 1), at(30));
+        assert_eq!(second.growth_bytes_per_hour, Some(100.0 * MB as f64));
+
+        // A quiet scan after the burst still counts it, averaged over the hour so far.
+        let third = watcher.record(path.clone(), scan(150 * MB, 1), at(60));
+        assert_eq!(third.growth_bytes_per_hour, Some(50.0 * MB as f64));
+
+        // Once the window is full, the oldest scans drop out and the burst with them.
+        watcher.record(path.clone(), scan(150 * MB, 1), at(90));
+        let fifth = watcher.record(path.clone(), scan(150 * MB, 1), at(120));
+        assert_eq!(fifth.growth_bytes_per_hour, Some(25.0 * MB as f64));
+        let sixth = watcher.record(path, scan(150 * MB, 1), at(150));
+        assert_eq!(sixth.growth_bytes_per_hour, Some(0.0));
+    }
+
+    #[test]
+    fn file_count_growth_is_averaged_over_the_scan_window() {
+        let mut watcher = DirWatcher::new();
+        let start = Instant::now();
+        let path = PathBuf::from("/data");
+        let at = |minutes: u64| start + Duration::from_secs(minutes * 60);
+
+        assert_eq!(watcher.record(path.clone(), scan(MB, 100), start).growth_files_per_hour, None);
+        assert_eq!(watcher.record(path.clone(), scan(MB, 1100), at(15)).growth_files_per_hour, Some(4000.0));
+        let third = watcher.record(path, scan(MB, 1100), at(30));
+        assert_eq!(third.growth_files_per_hour, Some(2000.0));
+        assert_eq!(third.growth_bytes_per_hour, Some(0.0));
+
+        let active: Vec<_> = evaluate_report(&watch(Path::new("/data")), &third)
+            .into_iter()
+            .filter(|alert| alert.active)
+            .map(|alert| alert.kind)
+            .collect();
+        assert_eq!(active, vec![
+            AlertKind::Directory("/data:files".to_string()),
+            AlertKind::Directory("/data:files_growth".to_string()),
+        ]);
+    }
+
+    #[test]
+    fn thresholds_raise_directory_alerts() {
+        let config = watch(Path::new("/data"));
+        let report = DirReport {
+            path: PathBuf::from("/data"),
+            scan: DirScan {
+                total_size: 2 * MB,
+                file_count: 1,
+                top_contributors: vec![("/data/cache".to_string(), 2 * MB)],
+            },
+            growth_bytes_per_hour: Some(20.0 * MB as f64),
+            growth_files_per_hour: Some(0.0),
+        };
+
+        let alerts = evaluate_report(&config, &report);
+        let active: Vec<_> = alerts.iter().filter(|a| a.active).map(|a| a.kind.clone()).collect();
+
+        assert_eq!(active, vec![
+            AlertKind::Directory("/data:size".to_string()),
+            AlertKind::Directory("/data:growth".to_string()),
+        ]);
+        assert!(alerts[2].body.contains("/data/cache"));
+    }
+
+    #[test]
+    fn growth_alert_needs_two_scans() {
+        let config = watch(Path::new("/data"));
+        let report = DirReport {
+            path: PathBuf::from("/data"),
+            scan: scan(0, 1),
+            growth_bytes_per_hour: None,
+            growth_files_per_hour: None,
+        };
+
+        assert!(!evaluate_report(&config, &report)
+            .iter()
+            .any(|a| matches!(&a.kind, AlertKind::Directory(key) if key.ends_with("growth"))));
+    }
+
+    #[test]
+    fn scheduler_skips_until_interval_elapses() {
+        let dir = tempfile::tempdir().unwrap();
+        let watches = vec![watch(dir.path())];
+        let mut watcher = DirWatcher::new();
+        let start = Instant::now();
+
+        assert_eq!(watcher.run_due(&watches, start).len(), 1);
+        assert!(watcher.run_due(&watches, start + Duration::from_secs(10)).is_empty());
+        assert_eq!(watcher.run_due(&watches, start + Duration::from_secs(300)).len(), 1);
+    }
+
+    #[test]
+    fn tilde_expands_to_home() {
+        if let Some(home) = dirs::home_dir() {
+            assert_eq!(expand_home(Path::new("~/Downloads")), home.join("Downloads"));
+        }
+        assert_eq!(expand_home(Path::new("/var/log")), PathBuf::from("/var/log"));
+    }
+}
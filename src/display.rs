@@ -1,9 +1,15 @@
 use std::collections::HashMap;
 use sysinfo::{System, SystemExt, ProcessExt, CpuExt};
 use humansize::{format_size, BINARY};
-use crate::types::{SystemMetrics, SecurityAnalysis};
+use crate::types::{Finding, SystemMetrics, SecurityAnalysis, SECURITY_CATEGORIES};
 use crate::config::Config;
-use crate::analysis::{analyze_cpu_trend, analyze_memory_trend, analyze_network_trend, classify_usage_pattern};
+use crate::health_score::HealthScore;
+use crate::attribution::describe_peak;
+use crate::history_query::{format_utc, HistorySummary};
+use crate::recording::{RecordingHeader, ReplayOutcome};
+use crate::report::{build_performance_report, format_bytes, format_rate, format_signed_size, PerformanceReport};
+use crate::snapshot::{Change, SnapshotDiff, SnapshotSide, Unit};
+use crate::ports::ListeningPort;
 
 pub fn display_process_summary(sys: &mut System) {
     sys.refresh_all();
@@ -114,27 +120,7 @@ pub fn display_temp_files(metrics: &SystemMetrics) {
 
 pub fn display_performance_analysis(metrics_history: &[SystemMetrics]) {
     println!("\n=== Performance Analysis ===");
-    
-    let cpu_trend = analyze_cpu_trend(metrics_history);
-    println!("\nCPU Usage Trends:");
-    for (core, trend) in cpu_trend.iter().enumerate() {
-        println!("Core {}: {:.2}% avg, Pattern: {}", 
-                core, 
-                trend.average, 
-                classify_usage_pattern(trend.pattern));
-    }
-
-    let memory_trend = analyze_memory_trend(metrics_history);
-    println!("\nMemory Usage:");
-    println!("Average: {}", format_size(memory_trend.average as u64, BINARY));
-    println!("Peak: {}", format_size(memory_trend.peak as u64, BINARY));
-    println!("Pattern: {}", classify_usage_pattern(memory_trend.pattern));
-
-    let network_trend = analyze_network_trend(metrics_history);
-    println!("\nNetwork Activity:");
-    println!("Avg Throughput: ↓{}ps, ↑{}ps",
-             format_size(network_trend.rx_rate as u64, BINARY),
-             format_size(network_trend.tx_rate as u64, BINARY));
+    print_trends(&build_performance_report(metrics_history));
 
     // Just show summary of temp files
     let latest_metrics = metrics_history.last().unwrap();
@@ -144,41 +130,251 @@ pub fn display_performance_analysis(metrics_history: &[SystemMetrics]) {
     println!("Use 'show-temp-files' command to view detailed listing");
 }
 
-pub fn display_security_analysis(analysis: &SecurityAnalysis) {    
+pub fn display_history_summary(summary: &HistorySummary) {
+    println!("=== History: {} to {} UTC ({}) ===",
+             format_utc(summary.since),
+             format_utc(summary.until),
+             summary.source);
+
+    let Some(performance) = &summary.performance else {
+        println!("\nNot enough history in this window (need at least two samples).");
+        return;
+    };
+    println!("Samples: {}", performance.samples);
+    print_trends(performance);
+}
+
+fn print_trends(performance: &PerformanceReport) {
+    println!("\nCPU Usage Trends:");
+    for core in &performance.cpu_cores {
+        println!("Core {}: {:.2}% avg, {:.2}% peak, {}",
+                core.core,
+                core.average_percent,
+                core.peak_percent,
+                core.shape.describe(&format!("{:+.2}%", core.shape.slope_per_min)));
+        println!("        {}", core.stats.describe(|value| format!("{value:.1}%")));
+    }
+
+    for (heading, trend) in [("Memory Usage", &performance.memory), ("Swap Usage", &performance.swap)] {
+        println!("\n{heading}:");
+        println!("Average: {}", format_size(trend.average_bytes as u64, BINARY));
+        println!("Peak: {}", format_size(trend.peak_bytes as u64, BINARY));
+        println!("Trend: {}", trend.shape.describe(&format_signed_size(trend.shape.slope_per_min)));
+        println!("Distribution: {}", trend.stats.describe(format_bytes));
+    }
+
+    let network = &performance.network;
+    println!("\nNetwork Activity:");
+    println!("Avg Throughput: ↓{}ps, ↑{}ps",
+             format_size(network.rx_rate as u64, BINARY),
+             format_size(network.tx_rate as u64, BINARY));
+    println!("Peak Throughput: ↓{}ps, ↑{}ps",
+             format_size(network.rx_peak as u64, BINARY),
+             format_size(network.tx_peak as u64, BINARY));
+    println!("Receive: {}", network.rx.describe(&format!("{}/s", format_signed_size(network.rx.slope_per_min))));
+    println!("Transmit: {}", network.tx.describe(&format!("{}/s", format_signed_size(network.tx.slope_per_min))));
+    println!("Receive distribution: {}", network.rx_stats.describe(format_rate));
+    println!("Transmit distribution: {}", network.tx_stats.describe(format_rate));
 
-    if !analysis.unusual_network_activity.is_empty() {
-        println!("\nUnusual Network Activity:");
-        for activity in &analysis.unusual_network_activity {
-            println!("- {}", activity);
+    let disk_io = &performance.disk_io;
+    println!("\nDisk I/O:");
+    println!("Avg Throughput: {}, Peak: {}", format_rate(disk_io.average), format_rate(disk_io.peak));
+    println!("Trend: {}", disk_io.shape.describe(&format!("{}/s", format_signed_size(disk_io.shape.slope_per_min))));
+    println!("Distribution: {}", disk_io.stats.describe(format_rate));
+
+    if let Some(temperature) = &performance.temperature {
+        println!("\nCPU Temperature:");
+        println!("Average: {:.1}°C, Peak: {:.1}°C", temperature.average_celsius, temperature.peak_celsius);
+        println!("Trend: {}", temperature.shape.describe(&format!("{:+.2}°C", temperature.shape.slope_per_min)));
+        println!("Distribution: {}", temperature.stats.describe(|value| format!("{value:.1}°C")));
+    }
+
+    if !performance.peaks.is_empty() {
+        println!("\nPeaks:");
+        for peak in &performance.peaks {
+            println!("{} at {} UTC", describe_peak(peak), format_utc(peak.at));
         }
     }
+}
+
+pub fn display_replay(header: &RecordingHeader, outcome: &ReplayOutcome) {
+    println!("=== Replay: {} sample(s) over {}, recorded {} UTC on {} every {}s ===",
+             outcome.samples,
+             format_offset(outcome.span),
+             format_utc(header.started_at),
+             header.host_name.as_deref().unwrap_or("unknown host"),
+             header.interval_secs);
+
+    println!("\nNotifications:");
+    if outcome.notifications.is_empty() {
+        println!("None would have been sent.");
+    }
+    for event in &outcome.notifications {
+        let kind = if event.recovery { "recovered" } else { "alert" };
+        println!("[+{}] {kind}: {} - {}",
+                 format_offset(event.offset),
+                 event.title,
+                 event.body.replace('\n', " / "));
+    }
+
+    if !outcome.findings.is_empty() {
+        println!("\nFindings (samples reported):");
+        for (id, samples) in &outcome.findings {
+            println!("- {id}: {samples}");
+        }
+    }
+}
+
+fn format_offset(offset: std::time::Duration) -> String {
+    let secs = offset.as_secs();
+    format!("{:02}:{:02}:{:02}", secs / 3600, secs % 3600 / 60, secs % 60)
+}
+
+/// Process names listed per side before the rest are summarized.
+const DIFF_NAME_LIMIT: usize = 20;
+
+pub fn display_snapshot_diff(diff: &SnapshotDiff) {
+    println!("=== Snapshot diff: {} -> {} ===", describe_side(&diff.before), describe_side(&diff.after));
+    println!("Significant changes are marked with '!' ({} found).", diff.significant_changes());
+
+    println!("\nResources:");
+    for change in &diff.resources {
+        print_change(change);
+    }
+
+    println!("\nTop Processes (by memory):");
+    for process in &diff.top_processes {
+        println!("{} {:<24} {}, CPU {}, instances {}",
+                 marker(process.significant()),
+                 process.name,
+                 format_transition(&process.memory),
+                 format_transition(&process.cpu),
+                 format_transition(&process.instances));
+    }
+
+    print_names("New processes", &diff.new_processes);
+    print_names("Removed processes", &diff.removed_processes);
+
+    println!("\nListening Ports:");
+    if diff.opened_ports.is_empty() && diff.closed_ports.is_empty() {
+        println!("  No changes");
+    }
+    for port in &diff.opened_ports {
+        println!("! opened {}", format_port(port));
+    }
+    for port in &diff.closed_ports {
+        println!("! closed {}", format_port(port));
+    }
+
+    println!("\nDisk Usage:");
+    for change in &diff.disks {
+        print_change(change);
+    }
 
-    if !analysis.high_resource_usage.is_empty() {
-        println!("\nHigh Resource Usage:");
-        for usage in &analysis.high_resource_usage {
-            println!("- {}", usage);
+    if !diff.temperatures.is_empty() {
+        println!("\nTemperatures:");
+        for change in &diff.temperatures {
+            print_change(change);
         }
     }
+}
 
-    if !analysis.zombie_processes.is_empty() {
-        println!("\nZombie Processes:");
-        for zombie in &analysis.zombie_processes {
-            println!("- {}", zombie);
+fn describe_side(side: &SnapshotSide) -> String {
+    format!("{} ({} UTC)", side.host_name.as_deref().unwrap_or("unknown host"), format_utc(side.taken_at))
+}
+
+fn marker(significant: bool) -> char {
+    if significant { '!' } else { ' ' }
+}
+
+fn print_change(change: &Change) {
+    println!("{} {:<24} {}", marker(change.significant), change.label, format_transition(change));
+}
+
+fn format_transition(change: &Change) -> String {
+    let value = |value: Option<f64>| value.map_or_else(|| "-".to_string(), |value| format_unit(change.unit, value));
+    let delta = match change.delta() {
+        // Deltas that round to nothing are left out.
+        Some(delta) if format_unit(change.unit, delta.abs()) != format_unit(change.unit, 0.0) => {
+            let sign = if delta > 0.0 { '+' } else { '-' };
+            format!(" ({sign}{})", format_unit(change.unit, delta.abs()))
         }
+        _ => String::new(),
+    };
+    format!("{} -> {}{delta}", value(change.before), value(change.after))
+}
+
+fn format_unit(unit: Unit, value: f64) -> String {
+    match unit {
+        Unit::Percent => format!("{value:.1}%"),
+        Unit::Bytes => format_size(value as u64, BINARY),
+        Unit::Celsius => format!("{value:.1}°C"),
+        Unit::Count => format!("{value:.0}"),
     }
+}
+
+fn format_port(port: &ListeningPort) -> String {
+    let owner = match (&port.process, port.pid) {
+        (Some(process), Some(pid)) => format!(" ({process}, pid {pid})"),
+        (None, Some(pid)) => format!(" (pid {pid})"),
+        _ => String::new(),
+    };
+    format!("{} {}:{}{owner}", port.protocol, port.address, port.port)
+}
+
+fn print_names(heading: &str, names: &[String]) {
+    if names.is_empty() {
+        return;
+    }
+    println!("\n{heading} ({}):", names.len());
+    let shown: Vec<&str> = names.iter().take(DIFF_NAME_LIMIT).map(String::as_str).collect();
+    let more = names.len().saturating_sub(DIFF_NAME_LIMIT);
+    if more > 0 {
+        println!("  {} and {more} more", shown.join(", "));
+    } else {
+        println!("  {}", shown.join(", "));
+    }
+}
 
-    if !analysis.swap_pressure.is_empty() {
-        println!("\nSwap Pressure:");
-        for swap in &analysis.swap_pressure {
-            println!("- {}", swap);
+pub fn display_security_analysis(analysis: &SecurityAnalysis) {
+    for category in SECURITY_CATEGORIES {
+        let mut findings = analysis.in_category(category).peekable();
+        if findings.peek().is_none() {
+            continue;
         }
+        println!("\n{}:", category.title());
+        for finding in findings {
+            println!("- {}", finding.summary);
+        }
+    }
+}
+
+pub fn display_health_score(health: &HealthScore) {
+    println!("=== Health Score: {}/100 ===", health.score);
+    for component in health.deductions() {
+        println!(
+            "  {} -{:.0}: {} (sub-score {:.0}/100)",
+            component.factor.label(),
+            component.deduction,
+            component.reason,
+            component.score
+        );
     }
+    println!();
 }
 
-pub fn display_recommendations(recommendations: &[String]) {
+pub fn display_recommendations(recommendations: &[Finding]) {
     println!("\n=== System Recommendations ===");
     for recommendation in recommendations {
-        println!("{}", recommendation);
+        println!("{}", format_finding(recommendation));
+    }
+}
+
+/// One-line rendering shared by the CLI and the daemon log.
+pub fn format_finding(finding: &Finding) -> String {
+    match &finding.action {
+        Some(action) => format!("* {}: {} - {action}", finding.severity.label(), finding.summary),
+        None => format!("* {}: {}", finding.severity.label(), finding.summary),
     }
 }
 
@@ -0,0 +1,360 @@
+//! Time-to-full forecasts per mount, from a robust trend over recent disk usage.
+
+use std::collections::{BTreeMap, VecDeque};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use humansize::{format_size, BINARY};
+use serde::Serialize;
+use crate::analysis::theil_sen_fit;
+use crate::config::{Config, ForecastConfig};
+use crate::history::{history_dir, HistoryStore};
+use crate::notifications::{Alert, AlertKind};
+use crate::rollup::{Resolution, Rollup};
+use crate::types::{Finding, FindingCategory, Severity, Subject, SystemMetrics};
+
+/// Usage is kept at most once per interval, which bounds the pairwise Theil-Sen fit.
+const POINT_INTERVAL: Duration = Duration::from_secs(15 * 60);
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DiskForecast {
+    pub mount: String,
+    pub used: u64,
+    pub total: u64,
+    /// Trend in bytes per day; negative when the mount is emptying.
+    pub growth_per_day: f64,
+    /// Time until the mount is full at this rate, when it is growing.
+    pub full_in: Option<Duration>,
+}
+
+struct MountSeries {
+    total: u64,
+    /// Sample time and used bytes, oldest first.
+    points: VecDeque<(SystemTime, u64)>,
+}
+
+/// Disk usage per mount over the last `window_days`.
+pub struct DiskTracker {
+    config: ForecastConfig,
+    mounts: BTreeMap<String, MountSeries>,
+}
+
+impl DiskTracker {
+    pub fn new(config: &ForecastConfig) -> Self {
+        Self { config: config.clone(), mounts: BTreeMap::new() }
+    }
+
+    /// A tracker holding `samples`, oldest first.
+    pub fn from_samples(samples: &[SystemMetrics], config: &ForecastConfig) -> Self {
+        let mut tracker = Self::new(config);
+        for sample in samples {
+            tracker.seed(sample);
+        }
+        if let Some(latest) = samples.last() {
+            tracker.record(latest);
+        }
+        tracker
+    }
+
+    /// Records a sample, forgetting mounts that are no longer present. Returns whether the
+    /// trend changed, i.e. whether [`Self::forecasts`] is worth recomputing.
+    pub fn record(&mut self, metrics: &SystemMetrics) -> bool {
+        if !self.config.enabled {
+            return false;
+        }
+        let mounts = self.mounts.len();
+        let added = self.seed(metrics);
+        self.mounts.retain(|mount, _| metrics.disk_usage.contains_key(mount));
+        added || self.mounts.len() != mounts
+    }
+
+    /// Adds a sample without forgetting absent mounts. Returns whether any mount gained a
+    /// point.
+    pub fn seed(&mut self, metrics: &SystemMetrics) -> bool {
+        if !self.config.enabled {
+            return false;
+        }
+        let mut added = false;
+        for (mount, disk) in &metrics.disk_usage {
+            added |= self.add_point(mount, disk.total, metrics.recorded_at, disk.used);
+        }
+        added
+    }
+
+    /// Seeds the last `window_days` from the history store's hourly rollups, which is
+    /// plenty for a trend measured in days and far less to read than raw samples.
+    pub fn seed_history(&mut self, store: &HistoryStore, now: SystemTime) {
+        if !self.config.enabled {
+            return;
+        }
+        let window = Duration::from_secs_f64(self.config.window_days.max(0.0) * 86_400.0);
+        let since = now.checked_sub(window).unwrap_or(UNIX_EPOCH);
+        match store.read_rollups(Resolution::Hour, Some(since), Some(now)) {
+            Ok(rollups) => rollups.iter().for_each(|rollup| self.seed_rollup(rollup)),
+            Err(e) => log::warn!("Failed to read history for disk forecasts: {e}"),
+        }
+    }
+
+    fn seed_rollup(&mut self, rollup: &Rollup) {
+        for (mount, disk) in &rollup.disks {
+            self.add_point(mount, disk.total, rollup.start, disk.used.avg as u64);
+        }
+    }
+
+    fn add_point(&mut self, mount: &str, total: u64, at: SystemTime, used: u64) -> bool {
+        if total == 0 {
+            return false;
+        }
+        let window = Duration::from_secs_f64(self.config.window_days.max(0.0) * 86_400.0);
+        let series = self.mounts.entry(mount.to_string()).or_insert_with(|| MountSeries {
+            total,
+            points: VecDeque::new(),
+        });
+        // A resized filesystem starts over.
+        if series.total != total {
+            series.total = total;
+            series.points.clear();
+        }
+        let due = series
+            .points
+            .back()
+            .is_none_or(|(last, _)| at.duration_since(*last).is_ok_and(|elapsed| elapsed >= POINT_INTERVAL));
+        if due {
+            series.points.push_back((at, used));
+        } else if let Some(last) = series.points.back_mut() {
+            // Keep the latest usage in the newest point so the forecast starts from it.
+            last.1 = used;
+        }
+        while series.points.front().is_some_and(|(start, _)| at.duration_since(*start).unwrap_or_default() > window) {
+            series.points.pop_front();
+        }
+        due
+    }
+
+    /// Forecasts for the mounts with at least `min_hours` of history.
+    pub fn forecasts(&self) -> Vec<DiskForecast> {
+        let min_span = Duration::from_secs_f64(self.config.min_hours.max(0.0) * 3600.0);
+        self.mounts
+            .iter()
+            .filter_map(|(mount, series)| {
+                let (start, _) = *series.points.front()?;
+                let &(end, used) = series.points.back()?;
+                if end.duration_since(start).unwrap_or_default() < min_span {
+                    return None;
+                }
+                let points: Vec<(f64, f64)> = series
+                    .points
+                    .iter()
+                    .map(|(at, used)| (at.duration_since(start).unwrap_or_default().as_secs_f64() / 86_400.0, *used as f64))
+                    .collect();
+                let growth_per_day = theil_sen_fit(&points)?.slope;
+                Some(DiskForecast {
+                    mount: mount.clone(),
+                    used,
+                    total: series.total,
+                    growth_per_day,
+                    full_in: (growth_per_day > 0.0).then(|| {
+                        let days = series.total.saturating_sub(used) as f64 / growth_per_day;
+                        Duration::from_secs_f64(days * 86_400.0)
+                    }),
+                })
+            })
+            .collect()
+    }
+}
+
+/// Forecasts from the history store's last `window_days` followed by `recent`, for
+/// commands that do not run long enough to see a trend themselves.
+pub fn forecast_with_history(recent: &[SystemMetrics], config: &Config) -> Vec<DiskForecast> {
+    if !config.forecast.enabled {
+        return Vec::new();
+    }
+    let mut tracker = DiskTracker::new(&config.forecast);
+    if let Some(dir) = history_dir(&config.history).filter(|_| config.history.enabled) {
+        tracker.seed_history(&HistoryStore::open_read_only(&dir, &config.history), SystemTime::now());
+    }
+    for sample in recent {
+        tracker.record(sample);
+    }
+    tracker.forecasts()
+}
+
+/// A finding per mount forecast to fill up within `horizon_days`.
+pub fn forecast_findings(forecasts: &[DiskForecast], config: &ForecastConfig) -> Vec<Finding> {
+    forecasts
+        .iter()
+        .filter(|forecast| within_horizon(forecast, config))
+        .map(|forecast| {
+            let full_in = forecast.full_in.unwrap_or_default();
+            Finding {
+                id: format!("disk_full:{}", forecast.mount),
+                category: FindingCategory::DiskSpace,
+                // Notified through [`forecast_alerts`], which also announces recovery.
+                severity: Severity::Warning,
+                subject: Subject::Mount { path: forecast.mount.clone() },
+                summary: format!(
+                    "{} will be full in {} (growing {}/day, {} free)",
+                    forecast.mount,
+                    format_eta(full_in),
+                    format_size(forecast.growth_per_day as u64, BINARY),
+                    format_size(forecast.total.saturating_sub(forecast.used), BINARY),
+                ),
+                value: Some(full_in.as_secs_f64() / 86_400.0),
+                threshold: Some(config.horizon_days),
+                action: Some("Free up space or find what is writing to this mount".to_string()),
+                anomaly: None,
+            }
+        })
+        .collect()
+}
+
+/// One alert per forecast mount, active while it is forecast to fill within `horizon_days`.
+pub fn forecast_alerts(forecasts: &[DiskForecast], config: &Config) -> Vec<Alert> {
+    if !config.notifications.disk_alert {
+        return Vec::new();
+    }
+    forecasts
+        .iter()
+        .map(|forecast| Alert {
+            kind: AlertKind::DiskForecast(forecast.mount.clone()),
+            active: within_horizon(forecast, &config.forecast),
+            title: format!("Disk Filling Up: {}", forecast.mount),
+            body: format!(
+                "{} will be full in {} at {}/day",
+                forecast.mount,
+                forecast.full_in.map(format_eta).unwrap_or_default(),
+                format_size(forecast.growth_per_day.max(0.0) as u64, BINARY),
+            ),
+            recovery: Some((
+                format!("Disk No Longer Filling: {}", forecast.mount),
+                format!("{} is no longer forecast to fill within {} days", forecast.mount, config.forecast.horizon_days),
+            )),
+        })
+        .collect()
+}
+
+fn within_horizon(forecast: &DiskForecast, config: &ForecastConfig) -> bool {
+    forecast
+        .full_in
+        .is_some_and(|full_in| full_in.as_secs_f64() <= config.horizon_days * 86_400.0)
+}
+
+/// `~3 days`, `~5 hours` or `~20 minutes`.
+pub fn format_eta(duration: Duration) -> String {
+    let minutes = duration.as_secs() / 60;
+    match minutes {
+        0..=1 => "~1 minute".to_string(),
+        2..=119 => format!("~{minutes} minutes"),
+        120..=2879 => format!("~{} hours", (minutes + 30) / 60),
+        _ => format!("~{} days", (minutes + 720) / 1440),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::HistoryConfig;
+    use crate::types::*;
+
+    const GIB: u64 = 1024 * 1024 * 1024;
+
+    fn sample(minute: u64, disks: &[(&str, u64)]) -> SystemMetrics {
+        SystemMetrics {
+            disk_usage: disks
+                .iter()
+                .map(|(mount, used)| {
+                    (mount.to_string(), DiskMetrics { total: 100 * GIB, used: *used, read_rate: 0.0, write_rate: 0.0 })
+                })
+                .collect(),
+            ..SystemMetrics::sample(minute * 60, 10.0)
+        }
+    }
+
+    /// A day of samples every five minutes: /home gains 10 GiB a day from 50 GiB with
+    /// one large temporary file, / stays put.
+    fn day() -> Vec<SystemMetrics> {
+        (0..=288)
+            .map(|i| {
+                let minute = i * 5;
+                let download = if (600..700).contains(&minute) { 30 * GIB } else { 0 };
+                let home = 50 * GIB + 10 * GIB * minute / 1440 + download;
+                sample(minute, &[("/home", home), ("/", 20 * GIB)])
+            })
+            .collect()
+    }
+
+    #[test]
+    fn growing_mounts_are_forecast_to_fill() {
+        let forecasts = DiskTracker::from_samples(&day(), &ForecastConfig::default()).forecasts();
+        assert_eq!(forecasts.len(), 2);
+
+        let root = &forecasts[0];
+        assert_eq!(root.mount, "/");
+        assert_eq!(root.growth_per_day, 0.0);
+        assert_eq!(root.full_in, None);
+
+        let home = &forecasts[1];
+        assert!((home.growth_per_day / GIB as f64 - 10.0).abs() < 0.1);
+        let days = home.full_in.unwrap().as_secs_f64() / 86_400.0;
+        assert!((days - 4.0).abs() < 0.1, "{days}");
+        assert_eq!(format_eta(home.full_in.unwrap()), "~4 days");
+    }
+
+    #[test]
+    fn history_is_seeded_from_hourly_rollups() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut store = HistoryStore::open(dir.path(), &HistoryConfig::default()).unwrap();
+        let samples = day();
+        for sample in &samples {
+            store.append(sample).unwrap();
+        }
+
+        let mut tracker = DiskTracker::new(&ForecastConfig::default());
+        tracker.seed_history(&store, samples.last().unwrap().recorded_at);
+        let hours = store.read_rollups(Resolution::Hour, None, None).unwrap();
+        assert_eq!(tracker.mounts["/home"].points.len(), hours.len());
+
+        let forecasts = tracker.forecasts();
+        assert_eq!(forecasts[1].mount, "/home");
+        assert!((forecasts[1].growth_per_day / GIB as f64 - 10.0).abs() < 0.5, "{}", forecasts[1].growth_per_day);
+    }
+
+    #[test]
+    fn short_histories_and_resized_mounts_are_not_forecast() {
+        let samples = day();
+        assert!(DiskTracker::from_samples(&samples[..60], &ForecastConfig::default()).forecasts().is_empty());
+
+        let mut tracker = DiskTracker::from_samples(&samples, &ForecastConfig::default());
+        let mut resized = sample(1445, &[("/home", 60 * GIB)]);
+        resized.disk_usage.get_mut("/home").unwrap().total = 200 * GIB;
+        tracker.record(&resized);
+        assert!(tracker.forecasts().is_empty());
+        assert_eq!(tracker.mounts.len(), 1);
+    }
+
+    #[test]
+    fn forecasts_within_the_horizon_alert_and_report() {
+        let forecasts = DiskTracker::from_samples(&day(), &ForecastConfig::default()).forecasts();
+        let mut config = Config::default();
+
+        let findings = forecast_findings(&forecasts, &config.forecast);
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].id, "disk_full:/home");
+        assert_eq!(findings[0].severity, Severity::Warning);
+        assert!(findings[0].summary.starts_with("/home will be full in ~4 days"));
+
+        let alerts = forecast_alerts(&forecasts, &config);
+        let active: Vec<bool> = alerts.iter().map(|alert| alert.active).collect();
+        assert_eq!(active, vec![false, true]);
+
+        config.forecast.horizon_days = 3.0;
+        assert!(forecast_findings(&forecasts, &config.forecast).is_empty());
+        assert!(forecast_alerts(&forecasts, &config).iter().all(|alert| !alert.active));
+    }
+
+    #[test]
+    fn eta_is_rounded_to_a_readable_unit() {
+        assert_eq!(format_eta(Duration::from_secs(10)), "~1 minute");
+        assert_eq!(format_eta(Duration::from_secs(45 * 60)), "~45 minutes");
+        assert_eq!(format_eta(Duration::from_secs(5 * 3600 + 1200)), "~5 hours");
+        assert_eq!(format_eta(Duration::from_secs(3 * 86_400)), "~3 days");
+    }
+}
@@ -0,0 +1,323 @@
+use std::collections::{HashMap, VecDeque};
+use std::io::{BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpStream, ToSocketAddrs};
+use std::time::{Duration, Instant};
+use crate::config::HealthCheckConfig;
+use crate::notifications::{Alert, AlertKind};
+
+const MAX_LATENCY_HISTORY: usize = 60;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CheckResult {
+    pub name: String,
+    pub latency: Option<Duration>,
+    pub error: Option<String>,
+}
+
+impl CheckResult {
+    pub fn is_healthy(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+#[derive(Default)]
+pub struct CheckHistory {
+    pub latencies_ms: VecDeque<Option<f64>>,
+    pub consecutive_failures: u32,
+}
+
+impl CheckHistory {
+    pub fn average_latency_ms(&self) -> Option<f64> {
+        let samples: Vec<f64> = self.latencies_ms.iter().flatten().copied().collect();
+        if samples.is_empty() {
+            return None;
+        }
+        Some(samples.iter().sum::<f64>() / samples.len() as f64)
+    }
+
+    pub fn availability(&self) -> f64 {
+        if self.latencies_ms.is_empty() {
+            return 1.0;
+        }
+        let up = self.latencies_ms.iter().filter(|sample| sample.is_some()).count();
+        up as f64 / self.latencies_ms.len() as f64
+    }
+}
+
+#[derive(Default)]
+pub struct HealthMonitor {
+    history: HashMap<String, CheckHistory>,
+}
+
+impl HealthMonitor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn run(&mut self, checks: &[HealthCheckConfig]) -> Vec<CheckResult> {
+        let results: Vec<CheckResult> = checks.iter().map(run_check).collect();
+        for result in &results {
+            self.record(result);
+        }
+        results
+    }
+
+    pub fn record(&mut self, result: &CheckResult) {
+        let history = self.history.entry(result.name.clone()).or_default();
+        let sample = if result.is_healthy() {
+            history.consecutive_failures = 0;
+            result.latency.map(|latency| latency.as_secs_f64() * 1000.0)
+        } else {
+            history.consecutive_failures += 1;
+            None
+        };
+
+        history.latencies_ms.push_back(sample);
+        if history.latencies_ms.len() > MAX_LATENCY_HISTORY {
+            history.latencies_ms.pop_front();
+        }
+    }
+
+    pub fn history(&self, name: &str) -> Option<&CheckHistory> {
+        self.history.get(name)
+    }
+}
+
+pub fn run_check(check: &HealthCheckConfig) -> CheckResult {
+    let timeout = Duration::from_millis(check.timeout_ms);
+    let started = Instant::now();
+
+    let outcome = match (&check.http, &check.tcp) {
+        (Some(url), _) => http_get(url, timeout).and_then(|status| {
+            if status == check.expected_status {
+                Ok(())
+            } else {
+                Err(format!("HTTP {status} (expected {})", check.expected_status))
+            }
+        }),
+        (None, Some(address)) => tcp_connect(address, timeout).map(|_| ()),
+        (None, None) => Err("no `tcp` or `http` target configured".to_string()),
+    };
+    let latency = started.elapsed();
+
+    let error = match outcome {
+        Err(e) => Some(e),
+        Ok(()) => check
+            .max_latency_ms
+            .filter(|budget| latency > Duration::from_millis(*budget))
+            .map(|budget| format!("responded in {}ms (budget: {budget}ms)", latency.as_millis())),
+    };
+
+    CheckResult {
+        name: check.name.clone(),
+        latency: Some(latency),
+        error,
+    }
+}
+
+fn tcp_connect(address: &str, timeout: Duration) -> Result<TcpStream, String> {
+    let addrs: Vec<SocketAddr> = address
+        .to_socket_addrs()
+        .map_err(|e| format!("cannot resolve {address}: {e}"))?
+        .collect();
+
+    let mut last_error = format!("{address} resolved to no addresses");
+    for addr in addrs {
+        match TcpStream::connect_timeout(&addr, timeout) {
+            Ok(stream) => return Ok(stream),
+            Err(e) => last_error = format!("connect to {addr} failed: {e}"),
+        }
+    }
+    Err(last_error)
+}
+
+fn http_get(url: &str, timeout: Duration) -> Result<u16, String> {
+    let (host, port, path) = parse_http_url(url)?;
+    let mut stream = tcp_connect(&format!("{host}:{port}"), timeout)?;
+    stream.set_read_timeout(Some(timeout)).map_err(|e| e.to_string())?;
+    stream.set_write_timeout(Some(timeout)).map_err(|e| e.to_string())?;
+
+    let request = format!(
+        "GET {path} HTTP/1.1\r\nHost: {host}\r\nUser-Agent: system-monitor/{}\r\nConnection: close\r\n\r\n",
+        env!("CARGO_PKG_VERSION"),
+    );
+    stream.write_all(request.as_bytes()).map_err(|e| format!("request failed: {e}"))?;
+
+    let mut status_line = String::new();
+    BufReader::new(stream)
+        .read_line(&mut status_line)
+        .map_err(|e| format!("no response: {e}"))?;
+
+    status_line
+        .split_whitespace()
+        .nth(1)
+        .and_then(|code| code.parse().ok())
+        .ok_or_else(|| format!("malformed status line: {:?}", status_line.trim_end()))
+}
+
+fn parse_http_url(url: &str) -> Result<(String, u16, String), String> {
+    if url.starts_with("https://") {
+        return Err("https endpoints are not supported; use a tcp check instead".to_string());
+    }
+    let rest = url
+        .strip_prefix("http://")
+        .ok_or_else(|| format!("unsupported URL: {url}"))?;
+
+    let (authority, path) = match rest.find('/') {
+        Some(index) => (&rest[..index], &rest[index..]),
+        None => (rest, "/"),
+    };
+    let (host, port) = match authority.rsplit_once(':') {
+        Some((host, port)) if !port.contains(']') => {
+            let port = port.parse().map_err(|_| format!("invalid port in {url}"))?;
+            (host, port)
+        }
+        _ => (authority, 80),
+    };
+    if host.is_empty() {
+        return Err(format!("missing host in {url}"));
+    }
+
+    Ok((host.to_string(), port, path.to_string()))
+}
+
+pub fn evaluate_results(results: &[CheckResult]) -> Vec<Alert> {
+    results
+        .iter()
+        .map(|result| Alert {
+            kind: AlertKind::Endpoint(result.name.clone()),
+            active: !result.is_healthy(),
+            title: format!("Endpoint Down: {}", result.name),
+            body: result.error.clone().unwrap_or_default(),
+            recovery: Some((
+                format!("Endpoint Recovered: {}", result.name),
+                format!(
+                    "{} is responding again ({}ms)",
+                    result.name,
+                    result.latency.map(|latency| latency.as_millis()).unwrap_or_default(),
+                ),
+            )),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+    use std::net::TcpListener;
+
+    fn tcp_check(address: String) -> HealthCheckConfig {
+        HealthCheckConfig {
+            name: "db".to_string(),
+            tcp: Some(address),
+            http: None,
+            expected_status: 200,
+            max_latency_ms: None,
+            timeout_ms: 1000,
+        }
+    }
+
+    fn http_check(url: String) -> HealthCheckConfig {
+        HealthCheckConfig {
+            name: "api".to_string(),
+            tcp: None,
+            http: Some(url),
+            expected_status: 200,
+            max_latency_ms: None,
+            timeout_ms: 1000,
+        }
+    }
+
+    fn serve_once(response: &'static str, delay: Duration) -> SocketAddr {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut buffer = [0u8; 1024];
+            let _ = stream.read(&mut buffer);
+            std::thread::sleep(delay);
+            let _ = stream.write_all(response.as_bytes());
+        });
+        addr
+    }
+
+    fn closed_port() -> SocketAddr {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        listener.local_addr().unwrap()
+    }
+
+    #[test]
+    fn tcp_check_succeeds_against_listener() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let result = run_check(&tcp_check(listener.local_addr().unwrap().to_string()));
+        assert!(result.is_healthy(), "error: {:?}", result.error);
+    }
+
+    #[test]
+    fn tcp_check_fails_on_closed_port() {
+        let result = run_check(&tcp_check(closed_port().to_string()));
+        assert!(!result.is_healthy());
+    }
+
+    #[test]
+    fn http_check_accepts_expected_status() {
+        let addr = serve_once("HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n", Duration::ZERO);
+        let result = run_check(&http_check(format!("http://{addr}/health")));
+        assert!(result.is_healthy(), "error: {:?}", result.error);
+    }
+
+    #[test]
+    fn http_check_rejects_unexpected_status() {
+        let addr = serve_once("HTTP/1.1 503 Service Unavailable\r\n\r\n", Duration::ZERO);
+        let result = run_check(&http_check(format!("http://{addr}/")));
+        assert_eq!(result.error.as_deref(), Some("HTTP 503 (expected 200)"));
+    }
+
+    #[test]
+    fn http_check_enforces_latency_budget() {
+        let addr = serve_once("HTTP/1.1 200 OK\r\n\r\n", Duration::from_millis(100));
+        let mut check = http_check(format!("http://{addr}/"));
+        check.max_latency_ms = Some(10);
+
+        let result = run_check(&check);
+        assert!(result.error.unwrap().contains("budget: 10ms"));
+    }
+
+    #[test]
+    fn parses_http_urls() {
+        assert_eq!(parse_http_url("http://localhost:8080/health").unwrap(),
+            ("localhost".to_string(), 8080, "/health".to_string()));
+        assert_eq!(parse_http_url("http://example.local").unwrap(),
+            ("example.local".to_string(), 80, "/".to_string()));
+        assert!(parse_http_url("https://example.com").is_err());
+        assert!(parse_http_url("ftp://example.com").is_err());
+    }
+
+    #[test]
+    fn history_tracks_latency_and_failures() {
+        let mut monitor = HealthMonitor::new();
+        let ok = CheckResult { name: "db".to_string(), latency: Some(Duration::from_millis(20)), error: None };
+        let down = CheckResult { name: "db".to_string(), latency: None, error: Some("refused".to_string()) };
+
+        monitor.record(&ok);
+        monitor.record(&down);
+        monitor.record(&down);
+
+        let history = monitor.history("db").unwrap();
+        assert_eq!(history.consecutive_failures, 2);
+        assert_eq!(history.average_latency_ms(), Some(20.0));
+        assert!((history.availability() - 1.0 / 3.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn failed_checks_raise_alerts_with_recovery_message() {
+        let down = CheckResult { name: "db".to_string(), latency: None, error: Some("refused".to_string()) };
+        let alerts = evaluate_results(&[down]);
+
+        assert!(alerts[0].active);
+        assert_eq!(alerts[0].kind, AlertKind::Endpoint("db".to_string()));
+        assert_eq!(alerts[0].body, "refused");
+        assert!(alerts[0].recovery.as_ref().unwrap().0.contains("Recovered"));
+    }
+}
@@ -0,0 +1,291 @@
+//! One 0-100 health number per host, from weighted sub-scores for CPU saturation, memory
+//! pressure, swap, disk space, temperatures and the severity of the findings.
+
+use std::collections::BTreeMap;
+use serde::{Deserialize, Serialize};
+use crate::config::Config;
+use crate::types::{Finding, Severity, SystemMetrics};
+
+/// Points each finding takes off the findings sub-score.
+const CRITICAL_PENALTY: f64 = 50.0;
+const WARNING_PENALTY: f64 = 15.0;
+const INFO_PENALTY: f64 = 5.0;
+
+/// What a sub-score measures.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HealthFactor {
+    /// Average CPU usage over the history.
+    Cpu,
+    /// Memory in use.
+    Memory,
+    /// Swap in use.
+    Swap,
+    /// The fullest mount.
+    Disk,
+    /// The hottest sensor.
+    Temperature,
+    /// The severity of the findings.
+    Findings,
+}
+
+impl HealthFactor {
+    /// Human-readable name, e.g. `Memory pressure`.
+    pub fn label(self) -> &'static str {
+        match self {
+            HealthFactor::Cpu => "CPU saturation",
+            HealthFactor::Memory => "Memory pressure",
+            HealthFactor::Swap => "Swap",
+            HealthFactor::Disk => "Disk space",
+            HealthFactor::Temperature => "Temperatures",
+            HealthFactor::Findings => "Findings",
+        }
+    }
+}
+
+/// One weighted part of the health score.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HealthComponent {
+    /// What the sub-score measures.
+    pub factor: HealthFactor,
+    /// Configured weight, from `[health_score]`.
+    pub weight: f64,
+    /// 0-100, higher is healthier.
+    pub score: f64,
+    /// Points this component takes off the overall score.
+    pub deduction: f64,
+    /// What the sub-score is based on, e.g. `Memory at 91%`.
+    pub reason: String,
+}
+
+/// The weighted health score and the sub-scores behind it. Factors without data (no swap,
+/// no sensors) are left out and the remaining weights share the score.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HealthScore {
+    /// 0-100, higher is healthier.
+    pub score: u8,
+    /// The weighted sub-scores behind `score`.
+    pub components: Vec<HealthComponent>,
+}
+
+impl Default for HealthScore {
+    fn default() -> Self {
+        Self { score: 100, components: Vec::new() }
+    }
+}
+
+impl HealthScore {
+    fn from_components(mut components: Vec<HealthComponent>) -> Self {
+        let total_weight: f64 = components.iter().map(|component| component.weight).sum();
+        if total_weight <= 0.0 {
+            return Self { score: 100, components };
+        }
+        for component in &mut components {
+            component.deduction = component.weight / total_weight * (100.0 - component.score);
+        }
+        let lost: f64 = components.iter().map(|component| component.deduction).sum();
+        Self { score: (100.0 - lost).round().clamp(0.0, 100.0) as u8, components }
+    }
+
+    /// Components that cost points, biggest deduction first.
+    pub fn deductions(&self) -> Vec<&HealthComponent> {
+        let mut deductions: Vec<&HealthComponent> =
+            self.components.iter().filter(|component| component.deduction >= 0.5).collect();
+        deductions.sort_by(|a, b| b.deduction.total_cmp(&a.deduction));
+        deductions
+    }
+
+    /// Re-scores the findings component, such as after findings are added to an analysis.
+    pub fn rescore_findings<'a>(&mut self, findings: impl IntoIterator<Item = &'a Finding>) {
+        let mut components = std::mem::take(&mut self.components);
+        if let Some(component) = components.iter_mut().find(|component| component.factor == HealthFactor::Findings) {
+            *component = findings_component(findings, component.weight);
+        }
+        *self = Self::from_components(components);
+    }
+
+    /// `87/100 (Memory pressure -8: Memory at 91%)`.
+    pub fn describe(&self) -> String {
+        let deductions: Vec<String> = self
+            .deductions()
+            .iter()
+            .map(|component| format!("{} -{:.0}: {}", component.factor.label(), component.deduction, component.reason))
+            .collect();
+        if deductions.is_empty() {
+            format!("{}/100", self.score)
+        } else {
+            format!("{}/100 ({})", self.score, deductions.join("; "))
+        }
+    }
+}
+
+/// Scores `metrics_history` and `findings` with the weights in `[health_score]`. CPU is the
+/// average over the history; memory, swap, disks and temperatures come from the latest sample.
+pub fn health_score<'a>(
+    metrics_history: &[SystemMetrics],
+    findings: impl IntoIterator<Item = &'a Finding>,
+    config: &Config,
+) -> HealthScore {
+    let Some(latest) = metrics_history.last() else {
+        return HealthScore::default();
+    };
+    let weights = &config.health_score;
+    let mut components = Vec::new();
+
+    let cpu_samples: Vec<f64> = metrics_history
+        .iter()
+        .filter(|sample| !sample.cpu_usage.is_empty())
+        .map(|sample| f64::from(sample.cpu_usage.iter().sum::<f32>()) / sample.cpu_usage.len() as f64)
+        .collect();
+    if !cpu_samples.is_empty() {
+        let average = cpu_samples.iter().sum::<f64>() / cpu_samples.len() as f64;
+        components.push(component(HealthFactor::Cpu, weights.cpu, ramp(average, 60.0, 100.0), format!("CPU averaged {average:.0}%")));
+    }
+
+    if latest.memory_total > 0 {
+        let percent = latest.memory_usage as f64 / latest.memory_total as f64 * 100.0;
+        components.push(component(HealthFactor::Memory, weights.memory, ramp(percent, 70.0, 98.0), format!("Memory at {percent:.0}%")));
+    }
+
+    if latest.swap_total > 0 {
+        let percent = latest.swap_usage as f64 / latest.swap_total as f64 * 100.0;
+        components.push(component(HealthFactor::Swap, weights.swap, ramp(percent, 10.0, 80.0), format!("Swap at {percent:.0}%")));
+    }
+
+    let fullest = latest
+        .disk_usage
+        .iter()
+        .filter(|(_, disk)| disk.total > 0)
+        .map(|(mount, disk)| (mount, disk.used as f64 / disk.total as f64 * 100.0))
+        .max_by(|a, b| a.1.total_cmp(&b.1));
+    if let Some((mount, percent)) = fullest {
+        components.push(component(HealthFactor::Disk, weights.disk, ramp(percent, 80.0, 98.0), format!("{mount} at {percent:.0}%")));
+    }
+
+    let hottest = latest
+        .temperature
+        .components
+        .values()
+        .chain(&latest.temperature.cpu_temp)
+        .map(|reading| f64::from(reading.celsius))
+        .reduce(f64::max);
+    if let Some(celsius) = hottest {
+        let limit = config.thresholds.temperature_celsius;
+        components.push(component(
+            HealthFactor::Temperature,
+            weights.temperature,
+            ramp(celsius, limit - 20.0, limit + 5.0),
+            format!("Hottest sensor at {celsius:.0}°C"),
+        ));
+    }
+
+    components.push(findings_component(findings, weights.findings));
+    HealthScore::from_components(components.into_iter().filter(|component| component.weight > 0.0).collect())
+}
+
+/// Each distinct finding id counts once, at its most severe.
+fn findings_component<'a>(findings: impl IntoIterator<Item = &'a Finding>, weight: f64) -> HealthComponent {
+    let mut severities: BTreeMap<&str, Severity> = BTreeMap::new();
+    for finding in findings {
+        let severity = severities.entry(&finding.id).or_insert(finding.severity);
+        *severity = (*severity).max(finding.severity);
+    }
+    let count = |level: Severity| severities.values().filter(|&&severity| severity == level).count();
+    let (critical, warning, info) = (count(Severity::Critical), count(Severity::Warning), count(Severity::Info));
+    let penalty = critical as f64 * CRITICAL_PENALTY + warning as f64 * WARNING_PENALTY + info as f64 * INFO_PENALTY;
+    let reason = if severities.is_empty() {
+        "No findings".to_string()
+    } else {
+        format!("{critical} critical, {warning} warning, {info} info")
+    };
+    component(HealthFactor::Findings, weight, (100.0 - penalty).max(0.0), reason)
+}
+
+fn component(factor: HealthFactor, weight: f64, score: f64, reason: String) -> HealthComponent {
+    HealthComponent { factor, weight: weight.max(0.0), score, deduction: 0.0, reason }
+}
+
+/// 100 at or below `good`, 0 at or above `bad`, linear in between.
+fn ramp(value: f64, good: f64, bad: f64) -> f64 {
+    ((bad - value) / (bad - good)).clamp(0.0, 1.0) * 100.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::HealthScoreConfig;
+    use crate::types::*;
+    use std::collections::HashMap;
+
+    const GIB: u64 = 1024 * 1024 * 1024;
+
+    fn sample(cpu: f32, memory_gib: u64, disk_percent: u64) -> SystemMetrics {
+        SystemMetrics {
+            cpu_usage: vec![cpu, cpu],
+            memory_usage: memory_gib * GIB,
+            memory_total: 100 * GIB,
+            disk_usage: HashMap::from([(
+                "/".to_string(),
+                DiskMetrics { total: 100 * GIB, used: disk_percent * GIB, read_rate: 0.0, write_rate: 0.0 },
+            )]),
+            ..SystemMetrics::default()
+        }
+    }
+
+    fn finding(id: &str, severity: Severity) -> Finding {
+        Finding {
+            id: id.to_string(),
+            category: FindingCategory::Memory,
+            severity,
+            subject: Subject::System,
+            summary: id.to_string(),
+            value: None,
+            threshold: None,
+            action: None,
+            anomaly: None,
+        }
+    }
+
+    #[test]
+    fn idle_host_scores_full_marks() {
+        let score = health_score(&[sample(10.0, 30, 40)], &[], &Config::default());
+        assert_eq!(score.score, 100);
+        assert!(score.deductions().is_empty());
+        // No swap and no sensors, so only CPU, memory, disk and findings are scored.
+        assert_eq!(score.components.len(), 4);
+        assert_eq!(score.describe(), "100/100");
+    }
+
+    #[test]
+    fn deductions_are_weighted_and_biggest_first() {
+        let config = Config::default();
+        let findings = [finding("memory_pressure", Severity::Critical), finding("memory_pressure", Severity::Warning)];
+        let score = health_score(&[sample(10.0, 98, 89)], &findings, &config);
+
+        // CPU 20, memory 20, disk 15 and findings 25 share the score; swap and temperature
+        // have no data.
+        let deductions = score.deductions();
+        assert_eq!(deductions.len(), 3);
+        assert_eq!((deductions[0].factor, deductions[0].score, deductions[0].deduction), (HealthFactor::Memory, 0.0, 25.0));
+        assert_eq!(deductions[1].factor, HealthFactor::Findings);
+        assert_eq!(deductions[1].reason, "1 critical, 0 warning, 0 info");
+        assert_eq!(deductions[2].factor, HealthFactor::Disk);
+        assert_eq!(deductions[2].reason, "/ at 89%");
+        assert_eq!(score.score, 50);
+        assert!(score.describe().starts_with("50/100 (Memory pressure -25: Memory at 98%; Findings -16:"));
+    }
+
+    #[test]
+    fn weights_are_configurable_and_findings_can_be_rescored() {
+        let config = Config {
+            health_score: HealthScoreConfig { cpu: 0.0, memory: 1.0, swap: 0.0, disk: 0.0, temperature: 0.0, findings: 1.0 },
+            ..Config::default()
+        };
+        let mut score = health_score(&[sample(100.0, 84, 99)], &[], &config);
+        assert_eq!(score.components.len(), 2);
+        assert_eq!(score.score, 75);
+
+        score.rescore_findings(&[finding("swap_pressure", Severity::Warning)]);
+        assert_eq!(score.score, 68);
+    }
+}
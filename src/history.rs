@@ -0,0 +1,736 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use crate::config::{self, HistoryConfig};
+use crate::dir_watch::expand_home;
+use crate::rollup::{build_rollups, Resolution, Rollup};
+use crate::types::SystemMetrics;
+
+const SEGMENT_PREFIX: &str = "samples-";
+const SEGMENT_SUFFIX: &str = ".jsonl";
+/// Raw segments are rotated at least hourly so retention can drop whole files.
+const SEGMENT_MAX_AGE: Duration = Duration::from_secs(3600);
+/// Raw samples are preferred for ranges up to this long; longer ranges use rollups.
+const RAW_QUERY_SPAN: Duration = Duration::from_secs(6 * 3600);
+/// Upper bound on points a rollup query should return before a coarser resolution is used.
+const MAX_QUERY_POINTS: u64 = 2_000;
+const MB: f64 = 1024.0 * 1024.0;
+
+/// One append-only file of JSON lines, named after the time of its first record.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Segment {
+    pub path: PathBuf,
+    pub start: SystemTime,
+    pub size: u64,
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct CompactionStats {
+    pub segments_removed: usize,
+    pub bytes_freed: u64,
+}
+
+struct ActiveSegment {
+    path: PathBuf,
+    start: SystemTime,
+    file: File,
+    size: u64,
+}
+
+/// A directory of segments. Every record is written as a single line and synced before
+/// `append` returns, so a crash can at worst leave a partial last line, which is cut off
+/// the next time the log opens.
+struct SegmentLog {
+    dir: PathBuf,
+    segment_max_bytes: u64,
+    segment_max_age: Duration,
+    active: Option<ActiveSegment>,
+}
+
+impl SegmentLog {
+    /// A log that is only read, leaving any partial last line for the writer to repair.
+    fn read_only(dir: PathBuf) -> Self {
+        Self {
+            dir,
+            segment_max_bytes: 0,
+            segment_max_age: Duration::ZERO,
+            active: None,
+        }
+    }
+
+    fn open(dir: PathBuf, segment_max_bytes: u64, segment_max_age: Duration) -> io::Result<Self> {
+        fs::create_dir_all(&dir)?;
+
+        let active = match list_segments(&dir)?.pop() {
+            Some(last) => {
+                repair_tail(&last.path)?;
+                let file = OpenOptions::new().append(true).open(&last.path)?;
+                let size = file.metadata()?.len();
+                Some(ActiveSegment {
+                    path: last.path,
+                    start: last.start,
+                    file,
+                    size,
+                })
+            }
+            None => None,
+        };
+
+        Ok(Self {
+            dir,
+            segment_max_bytes,
+            segment_max_age,
+            active,
+        })
+    }
+
+    fn append<T: Serialize>(&mut self, time: SystemTime, record: &T) -> io::Result<()> {
+        let mut line = serde_json::to_vec(record).map_err(io::Error::other)?;
+        line.push(b'\n');
+
+        let rotate = self.active.as_ref().is_none_or(|active| {
+            let full = active.size > 0 && active.size + line.len() as u64 > self.segment_max_bytes;
+            let old = time
+                .duration_since(active.start)
+                .is_ok_and(|age| age >= self.segment_max_age);
+            full || old
+        });
+        if rotate {
+            let path = self.dir.join(segment_name(time));
+            let file = OpenOptions::new().create(true).append(true).open(&path)?;
+            let size = file.metadata()?.len();
+            self.active = Some(ActiveSegment {
+                path,
+                start: time,
+                file,
+                size,
+            });
+        }
+
+        let active = self.active.as_mut().expect("segment opened above");
+        active.file.write_all(&line)?;
+        active.file.sync_data()?;
+        active.size += line.len() as u64;
+        Ok(())
+    }
+
+    /// Records with `since <= time_of(record) <= until`, in file order. Unreadable lines are skipped.
+    fn read<T: DeserializeOwned>(
+        &self,
+        since: Option<SystemTime>,
+        until: Option<SystemTime>,
+        time_of: impl Fn(&T) -> SystemTime,
+    ) -> io::Result<Vec<T>> {
+        let segments = list_segments(&self.dir)?;
+        let mut records = Vec::new();
+
+        for (index, segment) in segments.iter().enumerate() {
+            let next_start = segments.get(index + 1).map(|next| next.start);
+            if until.is_some_and(|until| segment.start > until)
+                || since.zip(next_start).is_some_and(|(since, next)| next <= since)
+            {
+                continue;
+            }
+
+            let reader = BufReader::new(File::open(&segment.path)?);
+            for (number, line) in reader.lines().enumerate() {
+                let line = line?;
+                match serde_json::from_str::<T>(&line) {
+                    Ok(record) => {
+                        let time = time_of(&record);
+                        if since.is_none_or(|since| time >= since) && until.is_none_or(|until| time <= until) {
+                            records.push(record);
+                        }
+                    }
+                    Err(e) => log::debug!("Skipping {}:{}: {e}", segment.path.display(), number + 1),
+                }
+            }
+        }
+        Ok(records)
+    }
+
+    /// The last readable record of the newest segment.
+    fn last<T: DeserializeOwned>(&self) -> io::Result<Option<T>> {
+        let Some(active) = &self.active else {
+            return Ok(None);
+        };
+        let contents = fs::read_to_string(&active.path)?;
+        Ok(contents.lines().rev().find_map(|line| serde_json::from_str(line).ok()))
+    }
+
+    /// Drops segments whose records are all older than `retention`, then the oldest segments
+    /// until the log fits `max_size_bytes`. The segment being written to is never removed.
+    fn compact(
+        &self,
+        now: SystemTime,
+        retention: Duration,
+        max_size_bytes: Option<u64>,
+        stats: &mut CompactionStats,
+    ) -> io::Result<()> {
+        let segments = list_segments(&self.dir)?;
+        let active_path = self.active.as_ref().map(|active| active.path.clone());
+        let mut kept = Vec::new();
+
+        for (index, segment) in segments.iter().enumerate() {
+            let is_active = active_path.as_ref() == Some(&segment.path);
+            let expired = segments
+                .get(index + 1)
+                .and_then(|next| now.duration_since(next.start).ok())
+                .is_some_and(|age| age > retention);
+            if expired && !is_active {
+                remove_segment(segment, stats)?;
+            } else {
+                kept.push((segment, is_active));
+            }
+        }
+
+        let Some(max_size_bytes) = max_size_bytes else {
+            return Ok(());
+        };
+        let mut total: u64 = kept.iter().map(|(segment, _)| segment.size).sum();
+        for (segment, is_active) in kept {
+            if total <= max_size_bytes || is_active {
+                break;
+            }
+            total -= segment.size;
+            remove_segment(segment, stats)?;
+        }
+        Ok(())
+    }
+}
+
+struct RollupLog {
+    resolution: Resolution,
+    log: SegmentLog,
+    retention: Duration,
+    /// Start of the first window not rolled up yet.
+    next_window: Option<SystemTime>,
+}
+
+/// Append-only on-disk store of samples, downsampled into 1m/1h/1d rollups as windows close.
+pub struct HistoryStore {
+    dir: PathBuf,
+    raw: SegmentLog,
+    retention: Duration,
+    max_size_bytes: u64,
+    process_limit: usize,
+    rollups: Vec<RollupLog>,
+    read_only: bool,
+}
+
+impl HistoryStore {
+    pub fn open(dir: &Path, config: &HistoryConfig) -> io::Result<Self> {
+        let segment_max_bytes = (config.segment_size_mb * MB) as u64;
+        let raw = SegmentLog::open(dir.to_path_buf(), segment_max_bytes, SEGMENT_MAX_AGE)?;
+
+        let mut rollups = Vec::new();
+        for resolution in Resolution::ALL {
+            // Keep roughly a thousand windows per file.
+            let log = SegmentLog::open(
+                dir.join("rollups").join(resolution.name()),
+                segment_max_bytes,
+                resolution.duration() * 1000,
+            )?;
+            let next_window = log.last::<Rollup>()?.map(|rollup| rollup.start + resolution.duration());
+            rollups.push(RollupLog {
+                resolution,
+                log,
+                retention: rollup_retention(config, resolution),
+                next_window,
+            });
+        }
+
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            raw,
+            retention: days(config.retention_days),
+            max_size_bytes: (config.max_size_mb * MB) as u64,
+            process_limit: config.process_limit,
+            rollups,
+            read_only: false,
+        })
+    }
+
+    /// Opens the store for queries only, e.g. while the daemon is writing to it.
+    pub fn open_read_only(dir: &Path, config: &HistoryConfig) -> Self {
+        let rollups = Resolution::ALL
+            .into_iter()
+            .map(|resolution| RollupLog {
+                resolution,
+                log: SegmentLog::read_only(dir.join("rollups").join(resolution.name())),
+                retention: rollup_retention(config, resolution),
+                next_window: None,
+            })
+            .collect();
+
+        Self {
+            dir: dir.to_path_buf(),
+            raw: SegmentLog::read_only(dir.to_path_buf()),
+            retention: days(config.retention_days),
+            max_size_bytes: (config.max_size_mb * MB) as u64,
+            process_limit: config.process_limit,
+            rollups,
+            read_only: true,
+        }
+    }
+
+    /// Opens the store at `[history] path`, or under the user's data directory.
+    pub fn open_configured(config: &HistoryConfig) -> Result<Self, String> {
+        let dir = history_dir(config).ok_or("cannot determine a data directory")?;
+        Self::open(&dir, config).map_err(|e| format!("{}: {e}", dir.display()))
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Writes a sample, then rolls up any windows it closes.
+    pub fn append(&mut self, metrics: &SystemMetrics) -> io::Result<()> {
+        if self.read_only {
+            return Err(io::Error::new(io::ErrorKind::PermissionDenied, "history store is open read-only"));
+        }
+        let mut sample = metrics.clone();
+        sample.process_metrics.sort_by_key(|process| std::cmp::Reverse(process.memory_usage));
+        sample.process_metrics.truncate(self.process_limit);
+        sample.temp_files.files.clear();
+
+        self.raw.append(sample.recorded_at, &sample)?;
+        self.roll_up(sample.recorded_at)
+    }
+
+    fn roll_up(&mut self, now: SystemTime) -> io::Result<()> {
+        let first_raw = list_segments(&self.raw.dir)?.first().map(|segment| segment.start);
+
+        for rollup_log in &mut self.rollups {
+            let resolution = rollup_log.resolution;
+            let current = resolution.window_start(now);
+            let Some(from) = rollup_log.next_window.or(first_raw.map(|start| resolution.window_start(start))) else {
+                continue;
+            };
+            if from >= current {
+                continue;
+            }
+
+            let mut samples = self.raw.read::<SystemMetrics>(Some(from), Some(current), |s| s.recorded_at)?;
+            samples.retain(|sample| sample.recorded_at < current);
+            samples.sort_by_key(|sample| sample.recorded_at);
+            for rollup in build_rollups(&samples, resolution) {
+                rollup_log.log.append(rollup.start, &rollup)?;
+            }
+            rollup_log.next_window = Some(current);
+        }
+        Ok(())
+    }
+
+    /// Raw samples recorded within `[since, until]`, oldest first.
+    pub fn read_range(&self, since: Option<SystemTime>, until: Option<SystemTime>) -> io::Result<Vec<SystemMetrics>> {
+        let mut samples = self.raw.read::<SystemMetrics>(since, until, |sample| sample.recorded_at)?;
+        samples.sort_by_key(|sample| sample.recorded_at);
+        rebase_timestamps(&mut samples);
+        Ok(samples)
+    }
+
+    /// Rollups whose window starts within `[since, until]`, oldest first.
+    pub fn read_rollups(
+        &self,
+        resolution: Resolution,
+        since: Option<SystemTime>,
+        until: Option<SystemTime>,
+    ) -> io::Result<Vec<Rollup>> {
+        let log = &self.rollup_log(resolution).log;
+        let mut rollups = log.read::<Rollup>(since.map(|since| resolution.window_start(since)), until, |r| r.start)?;
+        rollups.sort_by_key(|rollup| rollup.start);
+        rollups.dedup_by_key(|rollup| rollup.start);
+        Ok(rollups)
+    }
+
+    /// The finest data that still covers `since` and keeps the point count reasonable;
+    /// `None` means raw samples.
+    pub fn choose_resolution(&self, since: SystemTime, until: SystemTime, now: SystemTime) -> Option<Resolution> {
+        let age = now.duration_since(since).unwrap_or_default();
+        let span = until.duration_since(since).unwrap_or_default();
+        if span <= RAW_QUERY_SPAN && age <= self.retention {
+            return None;
+        }
+
+        let fits = |log: &&RollupLog| {
+            age <= log.retention && span.as_secs() / log.resolution.duration().as_secs() <= MAX_QUERY_POINTS
+        };
+        self.rollups
+            .iter()
+            .find(fits)
+            .or(self.rollups.last())
+            .map(|log| log.resolution)
+    }
+
+    /// The coarsest rollup no wider than `step` that still covers `since`. Falls back to raw
+    /// samples for sub-minute steps, and to the finest covering rollup past raw retention.
+    pub fn resolution_for_step(&self, since: SystemTime, step: Duration, now: SystemTime) -> Option<Resolution> {
+        let age = now.duration_since(since).unwrap_or_default();
+        let covers = |log: &&RollupLog| age <= log.retention;
+        if let Some(log) = self.rollups.iter().rev().filter(covers).find(|log| log.resolution.duration() <= step) {
+            return Some(log.resolution);
+        }
+        if age <= self.retention {
+            return None;
+        }
+        self.rollups
+            .iter()
+            .find(covers)
+            .or(self.rollups.last())
+            .map(|log| log.resolution)
+    }
+
+    /// Samples covering `[since, until]` at the resolution picked by [`Self::choose_resolution`].
+    /// Rollups are materialized as one sample per window holding the window's averages,
+    /// so the result can go straight into `analyze_cpu_trend` and friends.
+    pub fn query(&self, since: SystemTime, until: SystemTime) -> io::Result<(Option<Resolution>, Vec<SystemMetrics>)> {
+        let resolution = self.choose_resolution(since, until, SystemTime::now());
+        let samples = match resolution {
+            None => self.read_range(Some(since), Some(until))?,
+            Some(resolution) => {
+                let mut samples: Vec<SystemMetrics> = self
+                    .read_rollups(resolution, Some(since), Some(until))?
+                    .iter()
+                    .map(Rollup::to_metrics)
+                    .collect();
+                rebase_timestamps(&mut samples);
+                samples
+            }
+        };
+        Ok((resolution, samples))
+    }
+
+    /// Applies retention to raw samples and each rollup resolution, then the size cap to
+    /// raw samples. The files being written to are never removed.
+    pub fn compact(&mut self, now: SystemTime) -> io::Result<CompactionStats> {
+        if self.read_only {
+            return Err(io::Error::new(io::ErrorKind::PermissionDenied, "history store is open read-only"));
+        }
+        let mut stats = CompactionStats::default();
+        self.raw.compact(now, self.retention, Some(self.max_size_bytes), &mut stats)?;
+        for rollup_log in &self.rollups {
+            rollup_log.log.compact(now, rollup_log.retention, None, &mut stats)?;
+        }
+        Ok(stats)
+    }
+
+    fn rollup_log(&self, resolution: Resolution) -> &RollupLog {
+        self.rollups
+            .iter()
+            .find(|log| log.resolution == resolution)
+            .expect("every resolution has a log")
+    }
+}
+
+fn rollup_retention(config: &HistoryConfig, resolution: Resolution) -> Duration {
+    days(match resolution {
+        Resolution::Minute => config.minute_retention_days,
+        Resolution::Hour => config.hour_retention_days,
+        Resolution::Day => config.day_retention_days,
+    })
+}
+
+fn days(days: f64) -> Duration {
+    Duration::from_secs_f64(days.max(0.0) * 86_400.0)
+}
+
+pub fn history_dir(config: &HistoryConfig) -> Option<PathBuf> {
+    config
+        .path
+        .as_deref()
+        .map(expand_home)
+        .or_else(config::default_history_dir)
+}
+
+/// Deserialized samples carry no meaningful `Instant`; rebuild them from the wall-clock
+/// offsets so duration-based analysis works on stored history.
+pub fn rebase_timestamps(samples: &mut [SystemMetrics]) {
+    let Some(first) = samples.first().map(|sample| sample.recorded_at) else {
+        return;
+    };
+    let anchor = Instant::now();
+    for sample in samples {
+        let offset = sample.recorded_at.duration_since(first).unwrap_or_default();
+        sample.timestamp = anchor + offset;
+    }
+}
+
+pub fn list_segments(dir: &Path) -> io::Result<Vec<Segment>> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+    let mut segments = Vec::new();
+    for entry in entries {
+        let entry = entry?;
+        let name = entry.file_name();
+        let Some(millis) = name
+            .to_str()
+            .and_then(|name| name.strip_prefix(SEGMENT_PREFIX))
+            .and_then(|rest| rest.strip_suffix(SEGMENT_SUFFIX))
+            .and_then(|millis| millis.parse::<u64>().ok())
+        else {
+            continue;
+        };
+        segments.push(Segment {
+            path: entry.path(),
+            start: UNIX_EPOCH + Duration::from_millis(millis),
+            size: entry.metadata()?.len(),
+        });
+    }
+    segments.sort_by_key(|segment| segment.start);
+    Ok(segments)
+}
+
+fn segment_name(start: SystemTime) -> String {
+    let millis = start.duration_since(UNIX_EPOCH).unwrap_or_default().as_millis();
+    format!("{SEGMENT_PREFIX}{millis:013}{SEGMENT_SUFFIX}")
+}
+
+/// Cuts a partially written last line left behind by a crash.
+fn repair_tail(path: &Path) -> io::Result<()> {
+    let mut contents = Vec::new();
+    File::open(path)?.read_to_end(&mut contents)?;
+    if contents.last().is_none_or(|&byte| byte == b'\n') {
+        return Ok(());
+    }
+
+    let keep = contents.iter().rposition(|&byte| byte == b'\n').map_or(0, |index| index + 1);
+    log::warn!(
+        "Discarding {} bytes of incomplete history in {}",
+        contents.len() - keep,
+        path.display()
+    );
+    let file = OpenOptions::new().write(true).open(path)?;
+    file.set_len(keep as u64)?;
+    file.sync_all()
+}
+
+fn remove_segment(segment: &Segment, stats: &mut CompactionStats) -> io::Result<()> {
+    fs::remove_file(&segment.path)?;
+    stats.segments_removed += 1;
+    stats.bytes_freed += segment.size;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::*;
+
+    fn config() -> HistoryConfig {
+        HistoryConfig {
+            retention_days: 1.0,
+            ..HistoryConfig::default()
+        }
+    }
+
+    fn at(secs: u64) -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(1_700_000_000 + secs)
+    }
+
+    fn sample(recorded_at: SystemTime, processes: usize) -> SystemMetrics {
+        SystemMetrics {
+            recorded_at,
+            memory_usage: 50,
+            process_metrics: (0..processes)
+                .map(|i| ProcessMetrics {
+                    name: format!("proc{i}"),
+                    pid: sysinfo::Pid::from(i + 1),
+                    cpu_usage: 0.0,
+                    memory_usage: i as u64,
+                    disk_usage: 0,
+                    cmd: Vec::new(),
+                    user: None,
+                    zombie: false,
+                    start_time: 0,
+                    threads: None,
+                    parent: None,
+                })
+                .collect(),
+            ..SystemMetrics::sample(0, 10.0)
+        }
+    }
+
+    #[test]
+    fn appended_samples_survive_reopen() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut store = HistoryStore::open(dir.path(), &config()).unwrap();
+        store.append(&sample(at(0), 30)).unwrap();
+        store.append(&sample(at(60), 0)).unwrap();
+        drop(store);
+
+        let store = HistoryStore::open(dir.path(), &config()).unwrap();
+        let samples = store.read_range(None, None).unwrap();
+
+        assert_eq!(samples.len(), 2);
+        assert_eq!(samples[0].process_metrics.len(), 20);
+        assert_eq!(samples[0].process_metrics[0].name, "proc29");
+        assert_eq!(samples[1].timestamp.duration_since(samples[0].timestamp), Duration::from_secs(60));
+    }
+
+    #[test]
+    fn torn_last_line_is_discarded_on_open() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut store = HistoryStore::open(dir.path(), &config()).unwrap();
+        store.append(&sample(at(0), 0)).unwrap();
+        drop(store);
+
+        let segment = list_segments(dir.path()).unwrap().remove(0);
+        let mut file = OpenOptions::new().append(true).open(&segment.path).unwrap();
+        file.write_all(b"{\"schema_version\":1,\"recor").unwrap();
+        drop(file);
+
+        let mut store = HistoryStore::open(dir.path(), &config()).unwrap();
+        store.append(&sample(at(10), 0)).unwrap();
+
+        assert_eq!(store.read_range(None, None).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn segments_rotate_hourly_and_ranges_filter() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut store = HistoryStore::open(dir.path(), &config()).unwrap();
+        for minute in 0..150 {
+            store.append(&sample(at(minute * 60), 0)).unwrap();
+        }
+
+        assert_eq!(list_segments(dir.path()).unwrap().len(), 3);
+        let window = store.read_range(Some(at(3600)), Some(at(3600 + 600))).unwrap();
+        assert_eq!(window.len(), 11);
+        assert_eq!(window[0].recorded_at, at(3600));
+    }
+
+    #[test]
+    fn compaction_applies_retention_and_size_cap() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut store = HistoryStore::open(dir.path(), &config()).unwrap();
+        for hour in 0..30 {
+            store.append(&sample(at(hour * 3600), 0)).unwrap();
+        }
+
+        let stats = store.compact(at(30 * 3600)).unwrap();
+        assert_eq!(stats.segments_removed, 5);
+        assert_eq!(list_segments(dir.path()).unwrap()[0].start, at(5 * 3600));
+
+        store.max_size_bytes = 0;
+        store.compact(at(30 * 3600)).unwrap();
+        let remaining = list_segments(dir.path()).unwrap();
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].start, at(29 * 3600));
+    }
+
+    #[test]
+    fn closed_windows_are_rolled_up_and_resume_after_reopen() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut store = HistoryStore::open(dir.path(), &config()).unwrap();
+        for minute in 0..90 {
+            store.append(&sample(at(minute * 60), 0)).unwrap();
+        }
+        drop(store);
+
+        let mut store = HistoryStore::open(dir.path(), &config()).unwrap();
+        for minute in 90..125 {
+            store.append(&sample(at(minute * 60), 0)).unwrap();
+        }
+
+        let minutes = store.read_rollups(Resolution::Minute, None, None).unwrap();
+        assert_eq!(minutes.len(), 124);
+        assert!(minutes.windows(2).all(|pair| pair[1].start > pair[0].start));
+
+        let hours = store.read_rollups(Resolution::Hour, None, None).unwrap();
+        let open_hour = Resolution::Hour.window_start(at(124 * 60));
+        assert_eq!(hours[0].start, Resolution::Hour.window_start(at(0)));
+        assert_eq!(
+            hours.iter().map(|rollup| rollup.samples).sum::<usize>(),
+            (0..125).filter(|minute| at(minute * 60) < open_hour).count()
+        );
+    }
+
+    #[test]
+    fn long_ranges_are_served_from_rollups() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = HistoryStore::open(dir.path(), &config()).unwrap();
+        let now = at(100 * 86_400);
+
+        assert_eq!(store.choose_resolution(now - Duration::from_secs(3600), now, now), None);
+        assert_eq!(
+            store.choose_resolution(now - Duration::from_secs(86_400), now, now),
+            Some(Resolution::Minute)
+        );
+        assert_eq!(
+            store.choose_resolution(now - Duration::from_secs(60 * 86_400), now, now),
+            Some(Resolution::Hour)
+        );
+        assert_eq!(
+            store.choose_resolution(now - Duration::from_secs(400 * 86_400), now, now),
+            Some(Resolution::Day)
+        );
+    }
+
+    #[test]
+    fn explicit_steps_use_the_coarsest_fitting_rollup() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = HistoryStore::open(dir.path(), &config()).unwrap();
+        let now = at(100 * 86_400);
+        let hour_ago = now - Duration::from_secs(3600);
+        let minutes = |m: u64| Duration::from_secs(m * 60);
+
+        assert_eq!(store.resolution_for_step(hour_ago, Duration::from_secs(10), now), None);
+        assert_eq!(store.resolution_for_step(hour_ago, minutes(5), now), Some(Resolution::Minute));
+        assert_eq!(store.resolution_for_step(hour_ago, minutes(120), now), Some(Resolution::Hour));
+        assert_eq!(
+            store.resolution_for_step(now - Duration::from_secs(60 * 86_400), minutes(5), now),
+            Some(Resolution::Hour)
+        );
+    }
+
+    #[test]
+    fn read_only_stores_query_without_writing() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut store = HistoryStore::open(dir.path(), &config()).unwrap();
+        store.append(&sample(at(0), 0)).unwrap();
+        drop(store);
+
+        let mut reader = HistoryStore::open_read_only(dir.path(), &config());
+        assert_eq!(reader.read_range(None, None).unwrap().len(), 1);
+        assert!(reader.append(&sample(at(60), 0)).is_err());
+        assert!(reader.compact(at(0)).is_err());
+
+        let missing = HistoryStore::open_read_only(&dir.path().join("missing"), &config());
+        assert!(missing.read_range(None, None).unwrap().is_empty());
+    }
+
+    #[test]
+    fn query_materializes_rollups_for_analysis() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut store = HistoryStore::open(dir.path(), &config()).unwrap();
+        let start = SystemTime::now() - Duration::from_secs(2 * 86_400);
+        for minute in 0..(36 * 60) {
+            store.append(&sample(start + Duration::from_secs(minute * 60), 0)).unwrap();
+        }
+
+        let (resolution, samples) = store.query(start, start + Duration::from_secs(30 * 3600)).unwrap();
+        assert_eq!(resolution, Some(Resolution::Minute));
+        assert!(samples.len() >= 30 * 60);
+        assert_eq!(samples[0].memory_usage, 50);
+        assert!(samples.windows(2).all(|pair| pair[1].timestamp > pair[0].timestamp));
+    }
+
+    #[test]
+    fn corrupt_lines_are_skipped() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut store = HistoryStore::open(dir.path(), &config()).unwrap();
+        store.append(&sample(at(0), 0)).unwrap();
+        let segment = list_segments(dir.path()).unwrap().remove(0);
+        OpenOptions::new().append(true).open(&segment.path).unwrap().write_all(b"garbage\n").unwrap();
+        store.append(&sample(at(5), 0)).unwrap();
+
+        assert_eq!(store.read_range(None, None).unwrap().len(), 2);
+    }
+}
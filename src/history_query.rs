@@ -0,0 +1,646 @@
+//! Reads stored history back as bucketed series and trend summaries (`system-monitor history`).
+
+use std::fmt::Write as _;
+use std::io;
+use std::str::FromStr;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use humansize::{format_size, BINARY};
+use serde::{Deserialize, Serialize};
+use crate::analysis::percentile;
+use crate::history::HistoryStore;
+use crate::report::{build_performance_report, PerformanceReport, REPORT_SCHEMA_VERSION};
+use crate::rollup::{Resolution, Rollup, Stat};
+use crate::types::SystemMetrics;
+
+/// A series that can be read from samples, rollups and baselines.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Metric {
+    /// Average across cores, in percent.
+    Cpu,
+    /// Memory in use, in percent.
+    Memory,
+    /// Swap in use, in percent.
+    Swap,
+    /// Receive rate in bytes per second.
+    NetworkRx,
+    /// Transmit rate in bytes per second.
+    NetworkTx,
+    /// Usage of the fullest mount, in percent.
+    Disk,
+    /// Bytes read and written per second by all processes.
+    DiskIo,
+    /// CPU temperature in °C.
+    Temperature,
+}
+
+impl Metric {
+    /// Every metric, in display order.
+    pub const ALL: [Metric; 8] = [
+        Metric::Cpu,
+        Metric::Memory,
+        Metric::Swap,
+        Metric::NetworkRx,
+        Metric::NetworkTx,
+        Metric::Disk,
+        Metric::DiskIo,
+        Metric::Temperature,
+    ];
+
+    /// The kebab-case name used on the command line and in JSON.
+    pub fn name(self) -> &'static str {
+        match self {
+            Metric::Cpu => "cpu",
+            Metric::Memory => "memory",
+            Metric::Swap => "swap",
+            Metric::NetworkRx => "network-rx",
+            Metric::NetworkTx => "network-tx",
+            Metric::Disk => "disk",
+            Metric::DiskIo => "disk-io",
+            Metric::Temperature => "temperature",
+        }
+    }
+
+    /// `%`, `B/s` or `°C`.
+    pub fn unit(self) -> &'static str {
+        match self {
+            Metric::Cpu | Metric::Memory | Metric::Swap | Metric::Disk => "%",
+            Metric::NetworkRx | Metric::NetworkTx | Metric::DiskIo => "B/s",
+            Metric::Temperature => "°C",
+        }
+    }
+
+    /// What the metric measures, for finding summaries.
+    pub fn label(self) -> &'static str {
+        match self {
+            Metric::Cpu => "CPU usage",
+            Metric::Memory => "Memory usage",
+            Metric::Swap => "Swap usage",
+            Metric::NetworkRx => "Network receive rate",
+            Metric::NetworkTx => "Network transmit rate",
+            Metric::Disk => "Disk usage",
+            Metric::DiskIo => "Disk I/O rate",
+            Metric::Temperature => "CPU temperature",
+        }
+    }
+
+    /// Smallest spread anomalies are scored with, so a perfectly steady series does not
+    /// turn ordinary jitter into a large score.
+    pub fn noise_floor(self) -> f64 {
+        match self {
+            Metric::NetworkRx | Metric::NetworkTx | Metric::DiskIo => 64.0 * 1024.0,
+            _ => 1.0,
+        }
+    }
+
+    /// The value for the last sample, if it has one.
+    pub fn latest_value(self, samples: &[SystemMetrics]) -> Option<f64> {
+        let latest = samples.last()?;
+        let (at, value) = self.sample_values(&samples[samples.len().saturating_sub(2)..]).pop()?;
+        (at == latest.recorded_at).then_some(value)
+    }
+
+    /// One value per raw sample. Network rates need the previous sample, so the first
+    /// sample has none; samples without the series (no disks, no sensor) are skipped.
+    pub fn sample_values(self, samples: &[SystemMetrics]) -> Vec<(SystemTime, f64)> {
+        let mut values = Vec::new();
+        let mut previous: Option<&SystemMetrics> = None;
+        for sample in samples {
+            let value = match self {
+                Metric::Cpu => Some(
+                    sample.cpu_usage.iter().map(|&usage| f64::from(usage)).sum::<f64>()
+                        / sample.cpu_usage.len().max(1) as f64,
+                ),
+                Metric::Memory => Some(percent(sample.memory_usage as f64, sample.memory_total)),
+                Metric::Swap => Some(percent(sample.swap_usage as f64, sample.swap_total)),
+                Metric::NetworkRx | Metric::NetworkTx | Metric::DiskIo => previous
+                    .and_then(|previous| sample.recorded_at.duration_since(previous.recorded_at).ok())
+                    .filter(|elapsed| !elapsed.is_zero())
+                    .map(|elapsed| {
+                        let bytes = match self {
+                            Metric::NetworkRx => sample.network_rx,
+                            Metric::NetworkTx => sample.network_tx,
+                            _ => sample.disk_io,
+                        };
+                        bytes as f64 / elapsed.as_secs_f64()
+                    }),
+                Metric::Disk => sample
+                    .disk_usage
+                    .values()
+                    .map(|disk| percent(disk.used as f64, disk.total))
+                    .max_by(f64::total_cmp),
+                Metric::Temperature => sample.temperature.cpu_temp.as_ref().map(|t| f64::from(t.celsius)),
+            };
+            if let Some(value) = value {
+                values.push((sample.recorded_at, value));
+            }
+            previous = Some(sample);
+        }
+        values
+    }
+
+    /// The series' stats within one rollup window, in this metric's unit.
+    pub fn rollup_stat(self, rollup: &Rollup) -> Option<Stat> {
+        match self {
+            Metric::Cpu => Some(rollup.cpu_total),
+            Metric::Memory => Some(scale(rollup.memory_used, rollup.memory_total)),
+            Metric::Swap => Some(scale(rollup.swap_used, rollup.swap_total)),
+            Metric::NetworkRx => rollup.network_rx,
+            Metric::NetworkTx => rollup.network_tx,
+            Metric::Disk => rollup
+                .disks
+                .values()
+                .map(|disk| scale(disk.used, disk.total))
+                .max_by(|a, b| a.avg.total_cmp(&b.avg)),
+            Metric::DiskIo => rollup.disk_io,
+            Metric::Temperature => rollup.cpu_temp,
+        }
+    }
+
+    /// `value` with its unit, e.g. `42.0%` or `1.5 MiB/s`.
+    pub fn format_value(self, value: f64) -> String {
+        match self {
+            Metric::NetworkRx | Metric::NetworkTx | Metric::DiskIo => {
+                format!("{}/s", format_size(value.max(0.0) as u64, BINARY))
+            }
+            _ => format!("{value:.1}{}", self.unit()),
+        }
+    }
+}
+
+impl FromStr for Metric {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Metric::ALL
+            .into_iter()
+            .find(|metric| metric.name() == s)
+            .ok_or_else(|| {
+                let names: Vec<_> = Metric::ALL.iter().map(|metric| metric.name()).collect();
+                format!("unknown metric '{s}' (expected one of: {})", names.join(", "))
+            })
+    }
+}
+
+fn percent(used: f64, total: u64) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        used / total as f64 * 100.0
+    }
+}
+
+fn scale(stat: Stat, total: u64) -> Stat {
+    Stat {
+        min: percent(stat.min, total),
+        avg: percent(stat.avg, total),
+        max: percent(stat.max, total),
+        p95: percent(stat.p95, total),
+    }
+}
+
+/// How finely to bucket a series.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Granularity {
+    /// Whatever resolution the store picks for the window's length.
+    Auto,
+    /// Every raw sample on its own.
+    Raw,
+    /// Fixed buckets aligned to the Unix epoch.
+    Every(Duration),
+}
+
+impl FromStr for Granularity {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "auto" => Ok(Granularity::Auto),
+            "raw" => Ok(Granularity::Raw),
+            _ => parse_duration(s).map(Granularity::Every),
+        }
+    }
+}
+
+/// Parses durations such as `90s`, `5m`, `6h`, `2d`, `1w` or `1h30m`.
+pub fn parse_duration(s: &str) -> Result<Duration, String> {
+    let invalid = || format!("invalid duration '{s}' (expected e.g. 30s, 5m, 6h, 2d or 1h30m)");
+    let mut total = 0u64;
+    let mut digits = String::new();
+    for c in s.trim().chars() {
+        if c.is_ascii_digit() {
+            digits.push(c);
+            continue;
+        }
+        let unit = match c {
+            's' => 1,
+            'm' => 60,
+            'h' => 3600,
+            'd' => 86_400,
+            'w' => 7 * 86_400,
+            _ => return Err(invalid()),
+        };
+        let count: u64 = digits.parse().map_err(|_| invalid())?;
+        total = count
+            .checked_mul(unit)
+            .and_then(|secs| total.checked_add(secs))
+            .ok_or_else(invalid)?;
+        digits.clear();
+    }
+    if !digits.is_empty() || total == 0 {
+        return Err(invalid());
+    }
+    Ok(Duration::from_secs(total))
+}
+
+/// Parses `now`, a duration before `now` (`6h`), or Unix seconds (`1700000000`).
+pub fn parse_time(s: &str, now: SystemTime) -> Result<SystemTime, String> {
+    let s = s.trim();
+    if s == "now" {
+        return Ok(now);
+    }
+    if let Ok(secs) = s.parse::<u64>() {
+        return Ok(UNIX_EPOCH + Duration::from_secs(secs));
+    }
+    let ago = parse_duration(s).map_err(|_| {
+        format!("invalid time '{s}' (expected 'now', a duration such as 6h, or Unix seconds)")
+    })?;
+    now.checked_sub(ago).ok_or_else(|| format!("'{s}' reaches before the Unix epoch"))
+}
+
+/// `YYYY-MM-DD HH:MM:SS` in UTC.
+pub fn format_utc(time: SystemTime) -> String {
+    let secs = time.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+    let (days, rest) = (secs / 86_400, secs % 86_400);
+
+    // Days since the epoch to a civil date (Howard Hinnant's algorithm).
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{year:04}-{month:02}-{day:02} {:02}:{:02}:{:02}",
+        rest / 3600,
+        rest % 3600 / 60,
+        rest % 60
+    )
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Bucket {
+    pub start: SystemTime,
+    pub samples: usize,
+    #[serde(flatten)]
+    pub stat: Stat,
+}
+
+/// Merges buckets into epoch-aligned buckets of `step`. Min, max and the sample-weighted
+/// average are exact; p95 is taken over the merged p95 values, which is exact for raw
+/// samples and an approximation for rollups.
+pub fn rebucket(points: &[Bucket], step: Duration) -> Vec<Bucket> {
+    let step_secs = step.as_secs().max(1);
+    let mut buckets: Vec<Bucket> = Vec::new();
+    let mut start = 0;
+    while start < points.len() {
+        let window = bucket_start(points[start].start, step_secs);
+        let end = points[start..]
+            .iter()
+            .position(|point| bucket_start(point.start, step_secs) != window)
+            .map_or(points.len(), |offset| start + offset);
+        buckets.push(merge(window, &points[start..end]));
+        start = end;
+    }
+    buckets
+}
+
+fn bucket_start(time: SystemTime, step_secs: u64) -> SystemTime {
+    let secs = time.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+    UNIX_EPOCH + Duration::from_secs(secs - secs % step_secs)
+}
+
+fn merge(start: SystemTime, points: &[Bucket]) -> Bucket {
+    let samples: usize = points.iter().map(|point| point.samples).sum();
+    let weighted: f64 = points.iter().map(|point| point.stat.avg * point.samples as f64).sum();
+    let mut p95s: Vec<f64> = points.iter().map(|point| point.stat.p95).collect();
+    p95s.sort_by(f64::total_cmp);
+
+    Bucket {
+        start,
+        samples,
+        stat: Stat {
+            min: points.iter().map(|point| point.stat.min).fold(f64::INFINITY, f64::min),
+            avg: weighted / samples.max(1) as f64,
+            max: points.iter().map(|point| point.stat.max).fold(f64::NEG_INFINITY, f64::max),
+            p95: percentile(&p95s, 95.0),
+        },
+    }
+}
+
+/// Where a query's data came from: `"raw"` or a rollup resolution such as `"1m"`.
+fn source_name(source: Option<Resolution>) -> &'static str {
+    source.map_or("raw", Resolution::name)
+}
+
+#[derive(Debug, Serialize)]
+pub struct HistorySeries {
+    pub schema_version: u32,
+    pub metric: Metric,
+    pub unit: &'static str,
+    pub source: &'static str,
+    pub step_secs: Option<u64>,
+    pub since: SystemTime,
+    pub until: SystemTime,
+    pub buckets: Vec<Bucket>,
+}
+
+/// One metric over `[since, until]`. Explicit steps read the coarsest stored resolution
+/// that fits inside a bucket and merge it into buckets of that size.
+pub fn query_series(
+    store: &HistoryStore,
+    metric: Metric,
+    since: SystemTime,
+    until: SystemTime,
+    granularity: Granularity,
+    now: SystemTime,
+) -> io::Result<HistorySeries> {
+    let source = match granularity {
+        Granularity::Auto => store.choose_resolution(since, until, now),
+        Granularity::Raw => None,
+        Granularity::Every(step) => store.resolution_for_step(since, step, now),
+    };
+
+    let points: Vec<Bucket> = match source {
+        None => {
+            // One sample before the window so the first network rate has a baseline.
+            let lookback = since.checked_sub(Duration::from_secs(3600)).unwrap_or(since);
+            let samples = store.read_range(Some(lookback), Some(until))?;
+            metric
+                .sample_values(&samples)
+                .into_iter()
+                .filter(|&(time, _)| time >= since)
+                .map(|(start, value)| Bucket {
+                    start,
+                    samples: 1,
+                    stat: Stat { min: value, avg: value, max: value, p95: value },
+                })
+                .collect()
+        }
+        Some(resolution) => store
+            .read_rollups(resolution, Some(since), Some(until))?
+            .iter()
+            .filter_map(|rollup| {
+                metric.rollup_stat(rollup).map(|stat| Bucket {
+                    start: rollup.start,
+                    samples: rollup.samples,
+                    stat,
+                })
+            })
+            .collect(),
+    };
+
+    let (step_secs, buckets) = match granularity {
+        Granularity::Every(step) => (Some(step.as_secs()), rebucket(&points, step)),
+        _ => (source.map(|resolution| resolution.duration().as_secs()), points),
+    };
+
+    Ok(HistorySeries {
+        schema_version: REPORT_SCHEMA_VERSION,
+        metric,
+        unit: metric.unit(),
+        source: source_name(source),
+        step_secs,
+        since,
+        until,
+        buckets,
+    })
+}
+
+pub fn render_table(series: &HistorySeries) -> String {
+    let mut out = format!(
+        "{} ({}) from {} to {} UTC, source: {}\n",
+        series.metric.name(),
+        series.unit,
+        format_utc(series.since),
+        format_utc(series.until),
+        series.source
+    );
+    if series.buckets.is_empty() {
+        out.push_str("No history recorded in this window.");
+        return out;
+    }
+
+    let _ = write!(
+        out,
+        "\n{:<19}  {:>7}  {:>12}  {:>12}  {:>12}  {:>12}",
+        "Time (UTC)", "Samples", "Min", "Avg", "Max", "P95"
+    );
+    let format = |value| series.metric.format_value(value);
+    for bucket in &series.buckets {
+        let _ = write!(
+            out,
+            "\n{:<19}  {:>7}  {:>12}  {:>12}  {:>12}  {:>12}",
+            format_utc(bucket.start),
+            bucket.samples,
+            format(bucket.stat.min),
+            format(bucket.stat.avg),
+            format(bucket.stat.max),
+            format(bucket.stat.p95)
+        );
+    }
+    out
+}
+
+pub fn render_csv(series: &HistorySeries) -> String {
+    let mut out = String::from("time,unix_secs,samples,min,avg,max,p95");
+    for bucket in &series.buckets {
+        let _ = write!(
+            out,
+            "\n{},{},{},{},{},{},{}",
+            format_utc(bucket.start).replace(' ', "T") + "Z",
+            bucket.start.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs(),
+            bucket.samples,
+            bucket.stat.min,
+            bucket.stat.avg,
+            bucket.stat.max,
+            bucket.stat.p95
+        );
+    }
+    out
+}
+
+/// The regular trend analysis run over a stored window.
+#[derive(Serialize)]
+pub struct HistorySummary {
+    pub schema_version: u32,
+    pub since: SystemTime,
+    pub until: SystemTime,
+    pub source: &'static str,
+    /// `None` when the window holds fewer than two samples.
+    pub performance: Option<PerformanceReport>,
+}
+
+pub fn summarize(store: &HistoryStore, since: SystemTime, until: SystemTime) -> io::Result<HistorySummary> {
+    let (source, samples) = store.query(since, until)?;
+    Ok(HistorySummary {
+        schema_version: REPORT_SCHEMA_VERSION,
+        since,
+        until,
+        source: source_name(source),
+        performance: (samples.len() >= 2).then(|| build_performance_report(&samples)),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::HistoryConfig;
+    use crate::types::*;
+    use std::collections::HashMap;
+
+    fn at(secs: u64) -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(1_700_000_000 - 1_700_000_000 % 86_400 + secs)
+    }
+
+    fn sample(secs: u64, cpu: f32, network_rx: u64) -> SystemMetrics {
+        SystemMetrics {
+            recorded_at: at(secs),
+            cpu_usage: vec![cpu, cpu + 10.0],
+            memory_usage: 25,
+            network_rx,
+            disk_usage: HashMap::from([
+                ("/".to_string(), DiskMetrics { total: 100, used: 40, read_rate: 0.0, write_rate: 0.0 }),
+                ("/data".to_string(), DiskMetrics { total: 100, used: 90, read_rate: 0.0, write_rate: 0.0 }),
+            ]),
+            ..SystemMetrics::sample(0, cpu)
+        }
+    }
+
+    fn point(secs: u64, samples: usize, min: f64, avg: f64, max: f64) -> Bucket {
+        Bucket {
+            start: at(secs),
+            samples,
+            stat: Stat { min, avg, max, p95: max },
+        }
+    }
+
+    #[test]
+    fn durations_and_times_parse() {
+        assert_eq!(parse_duration("5m"), Ok(Duration::from_secs(300)));
+        assert_eq!(parse_duration("1h30m"), Ok(Duration::from_secs(5400)));
+        assert_eq!(parse_duration("2w"), Ok(Duration::from_secs(14 * 86_400)));
+        assert!(parse_duration("5").is_err());
+        assert!(parse_duration("5x").is_err());
+        assert!(parse_duration("0m").is_err());
+
+        let now = at(86_400);
+        assert_eq!(parse_time("now", now), Ok(now));
+        assert_eq!(parse_time("6h", now), Ok(at(86_400 - 6 * 3600)));
+        assert_eq!(parse_time("1700000000", now), Ok(UNIX_EPOCH + Duration::from_secs(1_700_000_000)));
+        assert!(parse_time("yesterday", now).is_err());
+
+        assert_eq!("5m".parse(), Ok(Granularity::Every(Duration::from_secs(300))));
+        assert_eq!("raw".parse(), Ok(Granularity::Raw));
+        assert_eq!("network-rx".parse(), Ok(Metric::NetworkRx));
+        assert!("gpu".parse::<Metric>().is_err());
+    }
+
+    #[test]
+    fn utc_formatting_handles_dates() {
+        assert_eq!(format_utc(UNIX_EPOCH), "1970-01-01 00:00:00");
+        assert_eq!(format_utc(UNIX_EPOCH + Duration::from_secs(1_700_000_000)), "2023-11-14 22:13:20");
+        assert_eq!(format_utc(UNIX_EPOCH + Duration::from_secs(951_782_400)), "2000-02-29 00:00:00");
+    }
+
+    #[test]
+    fn sample_values_cover_each_metric() {
+        let samples = [sample(0, 10.0, 0), sample(10, 30.0, 500)];
+
+        assert_eq!(Metric::Cpu.sample_values(&samples), vec![(at(0), 15.0), (at(10), 35.0)]);
+        assert_eq!(Metric::Memory.sample_values(&samples)[0].1, 25.0);
+        assert_eq!(Metric::Swap.sample_values(&samples)[0].1, 0.0);
+        assert_eq!(Metric::NetworkRx.sample_values(&samples), vec![(at(10), 50.0)]);
+        assert_eq!(Metric::Disk.sample_values(&samples)[0].1, 90.0);
+        assert!(Metric::Temperature.sample_values(&samples).is_empty());
+    }
+
+    #[test]
+    fn rebucketing_weights_averages_by_samples() {
+        let points = [
+            point(0, 1, 10.0, 10.0, 10.0),
+            point(60, 3, 5.0, 30.0, 50.0),
+            point(300, 2, 1.0, 2.0, 3.0),
+        ];
+        let buckets = rebucket(&points, Duration::from_secs(300));
+
+        assert_eq!(buckets.len(), 2);
+        assert_eq!(buckets[0].start, at(0));
+        assert_eq!(buckets[0].samples, 4);
+        assert_eq!(buckets[0].stat, Stat { min: 5.0, avg: 25.0, max: 50.0, p95: 50.0 });
+        assert_eq!(buckets[1].start, at(300));
+        assert_eq!(buckets[1].stat.avg, 2.0);
+    }
+
+    #[test]
+    fn series_are_read_from_the_store_and_rendered() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = HistoryConfig::default();
+        let mut store = HistoryStore::open(dir.path(), &config).unwrap();
+        for i in 0..10 {
+            store.append(&sample(i * 60, i as f32, 0)).unwrap();
+        }
+
+        let step = Granularity::Every(Duration::from_secs(300));
+        let series = query_series(&store, Metric::Cpu, at(0), at(600), step, at(600)).unwrap();
+        assert_eq!(series.source, "1m");
+        assert_eq!(series.step_secs, Some(300));
+        // Minutes 0-4 and the rolled-up part of 5-9 (the last minute is still open).
+        assert_eq!(series.buckets.len(), 2);
+        assert_eq!(series.buckets[0].stat.avg, 7.0);
+        assert_eq!(series.buckets[1].samples, 4);
+
+        let raw = query_series(&store, Metric::Cpu, at(0), at(600), Granularity::Raw, at(600)).unwrap();
+        assert_eq!(raw.source, "raw");
+        assert_eq!(raw.buckets.len(), 10);
+
+        let csv = render_csv(&series);
+        let mut lines = csv.lines();
+        assert_eq!(lines.next(), Some("time,unix_secs,samples,min,avg,max,p95"));
+        assert!(lines.next().unwrap().ends_with(",5,5,7,9,9"));
+
+        let table = render_table(&series);
+        assert!(table.contains("source: 1m"));
+        assert!(table.contains("7.0%"));
+
+        let json: serde_json::Value = serde_json::from_str(&crate::report::to_json(&series)).unwrap();
+        assert_eq!(json["metric"], "cpu");
+        assert_eq!(json["buckets"][0]["p95"], 9.0);
+    }
+
+    #[test]
+    fn summaries_need_two_samples() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut store = HistoryStore::open(dir.path(), &HistoryConfig::default()).unwrap();
+        let now = SystemTime::now();
+        let mut first = sample(0, 20.0, 0);
+        first.recorded_at = now - Duration::from_secs(120);
+        store.append(&first).unwrap();
+
+        let since = now - Duration::from_secs(3600);
+        assert!(summarize(&store, since, now).unwrap().performance.is_none());
+
+        let mut second = sample(0, 40.0, 6000);
+        second.recorded_at = now - Duration::from_secs(60);
+        store.append(&second).unwrap();
+        let summary = summarize(&store, since, now).unwrap();
+        assert_eq!(summary.source, "raw");
+        let performance = summary.performance.unwrap();
+        assert_eq!(performance.samples, 2);
+        assert_eq!(performance.cpu_cores[0].average_percent, 30.0);
+    }
+}
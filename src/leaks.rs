@@ -0,0 +1,288 @@
+//! Per-process memory tracking that flags processes whose memory climbs steadily.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::time::{Duration, SystemTime};
+use humansize::{format_size, BINARY};
+use sysinfo::PidExt;
+use crate::analysis::{linear_fit, LinearFit};
+use crate::config::LeakConfig;
+use crate::types::{Finding, FindingCategory, Severity, Subject, SystemMetrics};
+
+const MB: f64 = 1024.0 * 1024.0;
+
+/// A process whose memory has grown along a steady trend line.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LeakSuspect {
+    pub pid: u32,
+    pub name: String,
+    pub memory: u64,
+    pub growth_mb_per_hour: f64,
+    pub r_squared: f64,
+    /// How long the process has been tracked, up to the fitting window.
+    pub tracked: Duration,
+    /// When available memory runs out if the growth continues.
+    pub exhausts_in: Option<Duration>,
+}
+
+struct ProcessSeries {
+    name: String,
+    /// Sample time and memory in bytes, oldest first.
+    points: VecDeque<(SystemTime, u64)>,
+}
+
+impl ProcessSeries {
+    fn tracked(&self) -> Duration {
+        match (self.points.front(), self.points.back()) {
+            (Some((first, _)), Some((last, _))) => last.duration_since(*first).unwrap_or_default(),
+            _ => Duration::ZERO,
+        }
+    }
+
+    /// Trend in MB per hour.
+    fn fit(&self) -> Option<LinearFit> {
+        let (start, _) = *self.points.front()?;
+        let points: Vec<(f64, f64)> = self
+            .points
+            .iter()
+            .map(|(at, memory)| {
+                let hours = at.duration_since(start).unwrap_or_default().as_secs_f64() / 3600.0;
+                (hours, *memory as f64 / MB)
+            })
+            .collect();
+        linear_fit(&points)
+    }
+}
+
+/// Memory of every running process over the last `window_hours`, keyed by pid and start
+/// time so a reused pid starts a new series.
+pub struct LeakTracker {
+    config: LeakConfig,
+    processes: HashMap<(u32, u64), ProcessSeries>,
+}
+
+impl LeakTracker {
+    pub fn new(config: &LeakConfig) -> Self {
+        Self { config: config.clone(), processes: HashMap::new() }
+    }
+
+    /// Records the latest sample's processes, forgetting those that have exited.
+    pub fn record(&mut self, metrics: &SystemMetrics) {
+        if self.config.enabled {
+            let seen = self.add(metrics);
+            self.processes.retain(|key, _| seen.contains(key));
+        }
+    }
+
+    /// Adds a stored sample, such as one recorded before a restart. Stored samples only
+    /// hold the largest processes, so absent ones are kept until the next [`Self::record`].
+    pub fn seed(&mut self, metrics: &SystemMetrics) {
+        if self.config.enabled {
+            self.add(metrics);
+        }
+    }
+
+    fn add(&mut self, metrics: &SystemMetrics) -> HashSet<(u32, u64)> {
+        let window = Duration::from_secs_f64(self.config.window_hours.max(0.0) * 3600.0);
+        let mut seen = HashSet::with_capacity(metrics.process_metrics.len());
+        for process in metrics.process_metrics.iter().filter(|process| !process.zombie) {
+            let key = (process.pid.as_u32(), process.start_time);
+            let series = self.processes.entry(key).or_insert_with(|| ProcessSeries {
+                name: process.name.clone(),
+                points: VecDeque::new(),
+            });
+            series.points.push_back((metrics.recorded_at, process.memory_usage));
+            while series
+                .points
+                .front()
+                .is_some_and(|(at, _)| metrics.recorded_at.duration_since(*at).unwrap_or_default() > window)
+            {
+                series.points.pop_front();
+            }
+            seen.insert(key);
+        }
+        seen
+    }
+
+    /// Processes tracked for at least `min_hours` whose memory grows at `min_growth_mb_per_hour`
+    /// or more along a line that fits at least `min_r_squared`, fastest first.
+    pub fn suspects(&self, available_memory: u64) -> Vec<LeakSuspect> {
+        let min_tracked = Duration::from_secs_f64(self.config.min_hours.max(0.0) * 3600.0);
+        let mut suspects: Vec<LeakSuspect> = self
+            .processes
+            .iter()
+            .filter(|(_, series)| series.tracked() >= min_tracked)
+            .filter_map(|(&(pid, _), series)| {
+                let fit = series.fit()?;
+                if fit.slope < self.config.min_growth_mb_per_hour || fit.r_squared < self.config.min_r_squared {
+                    return None;
+                }
+                let bytes_per_hour = fit.slope * MB;
+                Some(LeakSuspect {
+                    pid,
+                    name: series.name.clone(),
+                    memory: series.points.back().map(|(_, memory)| *memory).unwrap_or_default(),
+                    growth_mb_per_hour: fit.slope,
+                    r_squared: fit.r_squared,
+                    tracked: series.tracked(),
+                    exhausts_in: (bytes_per_hour > 0.0)
+                        .then(|| Duration::from_secs_f64(available_memory as f64 / bytes_per_hour * 3600.0)),
+                })
+            })
+            .collect();
+        suspects.sort_by(|a, b| b.growth_mb_per_hour.total_cmp(&a.growth_mb_per_hour).then(a.pid.cmp(&b.pid)));
+        suspects
+    }
+
+    /// Records the latest sample in `metrics_history` and returns a finding per suspected leak.
+    pub fn observe(&mut self, metrics_history: &[SystemMetrics]) -> Vec<Finding> {
+        let Some(latest) = metrics_history.last() else {
+            return Vec::new();
+        };
+        self.record(latest);
+        if !self.config.enabled {
+            return Vec::new();
+        }
+        let available = latest.memory_total.saturating_sub(latest.memory_usage);
+        self.suspects(available)
+            .into_iter()
+            .map(|suspect| leak_finding(&suspect, self.config.min_growth_mb_per_hour))
+            .collect()
+    }
+}
+
+fn leak_finding(suspect: &LeakSuspect, min_growth_mb_per_hour: f64) -> Finding {
+    let exhaustion = match suspect.exhausts_in {
+        Some(duration) => format!("; available memory runs out in {} at this rate", format_hours(duration)),
+        None => String::new(),
+    };
+    Finding {
+        id: format!("leak:{}", suspect.pid),
+        category: FindingCategory::MemoryLeak,
+        severity: Severity::Critical,
+        subject: Subject::Process { pid: suspect.pid, name: suspect.name.clone() },
+        summary: format!(
+            "{} (PID: {}) grew to {} at {:.1} MB/hour over {}{exhaustion}",
+            suspect.name,
+            suspect.pid,
+            format_size(suspect.memory, BINARY),
+            suspect.growth_mb_per_hour,
+            format_hours(suspect.tracked),
+        ),
+        value: Some(suspect.growth_mb_per_hour),
+        threshold: Some(min_growth_mb_per_hour),
+        action: Some("Restart the process before it exhausts memory, and report the leak upstream".to_string()),
+        anomaly: None,
+    }
+}
+
+fn format_hours(duration: Duration) -> String {
+    let hours = duration.as_secs_f64() / 3600.0;
+    if hours >= 48.0 {
+        format!("{:.1} days", hours / 24.0)
+    } else {
+        format!("{hours:.1}h")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::*;
+
+    const MIB: u64 = 1024 * 1024;
+
+    fn process(pid: usize, start_time: u64, memory_usage: u64) -> ProcessMetrics {
+        ProcessMetrics {
+            name: format!("proc{pid}"),
+            pid: sysinfo::Pid::from(pid),
+            cpu_usage: 0.0,
+            memory_usage,
+            disk_usage: 0,
+            cmd: Vec::new(),
+            user: None,
+            zombie: false,
+            start_time,
+            threads: None,
+            parent: None,
+        }
+    }
+
+    fn sample(minute: u64, process_metrics: Vec<ProcessMetrics>) -> SystemMetrics {
+        SystemMetrics {
+            memory_usage: 6 * 1024 * MIB,
+            memory_total: 8 * 1024 * MIB,
+            process_metrics,
+            ..SystemMetrics::sample(minute * 60, 10.0)
+        }
+    }
+
+    /// Three hours, one sample every five minutes: pid 1 leaks 1 MiB a minute, pid 2 is
+    /// steady and pid 3 swings up and down.
+    fn tracked() -> (LeakTracker, Vec<Finding>) {
+        let mut tracker = LeakTracker::new(&LeakConfig::default());
+        let mut findings = Vec::new();
+        for minute in (0..=180).step_by(5) {
+            let swing = if minute % 10 == 0 { 0 } else { 900 * MIB };
+            findings = tracker.observe(&[sample(minute, vec![
+                process(1, 100, 200 * MIB + minute * MIB),
+                process(2, 100, 300 * MIB),
+                process(3, 100, 100 * MIB + swing + minute * MIB / 4),
+            ])]);
+        }
+        (tracker, findings)
+    }
+
+    #[test]
+    fn steady_growth_is_reported_with_rate_and_exhaustion() {
+        let (tracker, findings) = tracked();
+
+        let suspects = tracker.suspects(2 * 1024 * MIB);
+        assert_eq!(suspects.len(), 1);
+        assert_eq!(suspects[0].pid, 1);
+        assert!((suspects[0].growth_mb_per_hour - 60.0).abs() < 1e-6);
+        assert_eq!(suspects[0].tracked, Duration::from_secs(3 * 3600));
+        // 2 GiB free at 60 MiB an hour.
+        let exhausts_in = suspects[0].exhausts_in.unwrap().as_secs_f64() / 3600.0;
+        assert!((exhausts_in - 2048.0 / 60.0).abs() < 1e-6);
+
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].id, "leak:1");
+        assert_eq!(findings[0].severity, Severity::Critical);
+        assert!(findings[0].summary.contains("60.0 MB/hour"));
+        assert!(findings[0].summary.contains("runs out in 34.1h"));
+    }
+
+    #[test]
+    fn short_lived_and_restarted_processes_are_not_reported() {
+        let mut tracker = LeakTracker::new(&LeakConfig::default());
+        for minute in (0..=90).step_by(5) {
+            tracker.observe(&[sample(minute, vec![process(1, 100, 200 * MIB + minute * MIB)])]);
+        }
+        assert!(tracker.suspects(u64::MAX).is_empty());
+
+        // The pid is reused by a new process, which starts a new series.
+        for minute in (95..=240).step_by(5) {
+            tracker.observe(&[sample(minute, vec![process(1, 200, 200 * MIB + minute * MIB)])]);
+        }
+        assert_eq!(tracker.suspects(u64::MAX)[0].tracked, Duration::from_secs(145 * 60));
+        assert_eq!(tracker.processes.len(), 1);
+    }
+
+    #[test]
+    fn seeded_history_carries_over_gaps() {
+        let mut tracker = LeakTracker::new(&LeakConfig::default());
+        for minute in (0..=150).step_by(5) {
+            // Stored samples only hold the largest processes, so pid 1 drops out now and then.
+            let mut processes = vec![process(2, 100, 300 * MIB)];
+            if minute % 20 != 0 {
+                processes.push(process(1, 100, 200 * MIB + minute * MIB));
+            }
+            tracker.seed(&sample(minute, processes));
+        }
+        assert_eq!(tracker.processes.len(), 2);
+
+        let findings = tracker.observe(&[sample(155, vec![process(1, 100, 355 * MIB)])]);
+        assert_eq!(findings.len(), 1);
+        assert_eq!(tracker.processes.len(), 1);
+    }
+}
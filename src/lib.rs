@@ -0,0 +1,207 @@
+//! Collectors, trend analysis, alerting and reporting behind the `system-monitor` CLI.
+//!
+//! The top-level functions cover the common flow: collect samples, analyze them, evaluate
+//! alerts and render a report. Every type reachable from them is re-exported here too;
+//! the modules behind them are internal to the crate.
+//!
+//! ```no_run
+//! use system_monitor::{
+//!     analyze_with_baselines, build_report, collect_snapshot, new_system, render_report, Baselines, Config,
+//!     MetricsScope, ReportFormat,
+//! };
+//!
+//! let config = Config::default();
+//! let mut sys = new_system();
+//! let history = vec![collect_snapshot(&mut sys, MetricsScope::Light)];
+//!
+//! let baselines = Baselines::load_configured(&config.baseline);
+//! let analysis = analyze_with_baselines(&history, &baselines, &config);
+//! for finding in &analysis.security.findings {
+//!     println!("{:?}: {}", finding.severity, finding.summary);
+//! }
+//!
+//! let report = build_report(&sys, &history, analysis);
+//! println!("{}", render_report(&report, ReportFormat::Markdown));
+//! ```
+
+#![warn(missing_docs)]
+
+use std::thread;
+use std::time::Duration;
+use sysinfo::{System, SystemExt};
+
+pub(crate) mod analysis;
+pub(crate) mod attribution;
+mod cli;
+pub(crate) mod baseline;
+pub(crate) mod collectors;
+pub(crate) mod config;
+pub(crate) mod coolant;
+pub(crate) mod cores;
+pub(crate) mod daemon;
+pub(crate) mod dir_watch;
+pub(crate) mod display;
+pub(crate) mod forecast;
+pub(crate) mod health_checks;
+pub(crate) mod health_score;
+pub(crate) mod history;
+pub(crate) mod history_query;
+pub(crate) mod leaks;
+pub(crate) mod log_watch;
+pub(crate) mod metrics;
+pub(crate) mod notifications;
+pub(crate) mod percentile_alerts;
+pub(crate) mod ports;
+pub(crate) mod process_rules;
+pub(crate) mod recording;
+pub(crate) mod render;
+pub(crate) mod report;
+pub(crate) mod rollup;
+pub(crate) mod security;
+pub(crate) mod snapshot;
+pub(crate) mod spawns;
+pub(crate) mod temp_manager;
+pub(crate) mod types;
+#[cfg(target_os = "macos")]
+mod temperature;
+
+/// Re-exported so callers use the same `System` type the API takes.
+pub use sysinfo;
+
+pub use cli::run_cli;
+pub use attribution::{Contributor, Peak};
+pub use baseline::{Baselines, Deviation, SlotStats};
+pub use config::{
+    AnomalyConfig, AnomalyMode, BaselineConfig, CollectorConfig, Config, CoolantConfig, CustomThreshold, DaemonConfig,
+    ForecastConfig, HealthCheckConfig, HealthScoreConfig, HistoryConfig, LeakConfig, LogPatternConfig, LogWatchConfig,
+    MonitoringConfig, NotificationConfig, PercentileAlertConfig, ProcessRuleConfig, SpawnConfig, ThresholdConfig,
+    WatchDirConfig,
+};
+pub use health_score::{HealthComponent, HealthFactor, HealthScore};
+pub use history_query::Metric;
+pub use notifications::{Alert, AlertKind, NotificationManager};
+pub use render::ReportFormat;
+pub use report::{
+    CoreTrend, HistoryPoint, MemoryTrend, MonitorReport, PerformanceReport, ProcessSummary, SystemInfo, TempFilesSummary,
+    TemperatureTrend, REPORT_SCHEMA_VERSION,
+};
+pub use rollup::{DiskRollup, Resolution, Rollup, Stat};
+pub use types::{
+    AnomalyScore, Direction, DiskMetrics, Finding, FindingCategory, MetricsScope, NetworkTrend, ProcessMetrics,
+    SecurityAnalysis, SeriesStats, Severity, Subject, SystemMetrics, TempFileInfo, TempFileMetrics, TemperatureMetrics,
+    TemperatureReading, TrendDirection, TrendShape, UsageTrend, METRICS_SCHEMA_VERSION,
+};
+
+/// Security findings plus the recommendations derived from them and the latest sample,
+/// and the health score they add up to.
+pub struct Analysis {
+    /// Findings from the security and anomaly checks.
+    pub security: SecurityAnalysis,
+    /// Findings about resource usage, with suggested actions.
+    pub recommendations: Vec<Finding>,
+    /// The 0-100 health score of the latest sample and all findings.
+    pub health: HealthScore,
+}
+
+impl Analysis {
+    /// Adds findings from checks that need more than the sampled history, such as disk
+    /// forecasts over the history store, to both the findings and the recommendations.
+    pub fn add_findings(&mut self, findings: Vec<Finding>) {
+        self.recommendations.extend(findings.iter().cloned());
+        types::sort_findings(&mut self.recommendations);
+        self.security.add_findings(findings);
+        self.health.rescore_findings(self.security.findings.iter().chain(&self.recommendations));
+    }
+}
+
+/// Creates a `System` with processes, disks, networks and sensors loaded.
+pub fn new_system() -> System {
+    let mut sys = System::new_all();
+    #[cfg(target_os = "macos")]
+    sys.refresh_all();
+    #[cfg(not(target_os = "macos"))]
+    sys.refresh_components_list();
+    sys
+}
+
+/// Refreshes twice, half a second apart, so per-process CPU usage has a baseline.
+pub fn prime_cpu_usage(sys: &mut System) {
+    sys.refresh_all();
+    thread::sleep(Duration::from_millis(500));
+    sys.refresh_all();
+}
+
+/// Takes a single sample.
+pub fn collect_snapshot(sys: &mut System, scope: MetricsScope) -> SystemMetrics {
+    metrics::collect_system_metrics(sys, scope)
+}
+
+/// Samples for `config.monitoring.duration_secs`, calling `between_samples` while waiting
+/// for the next sample.
+pub fn collect_history(sys: &mut System, config: &Config, mut between_samples: impl FnMut()) -> Vec<SystemMetrics> {
+    let sample_interval = Duration::from_secs(config.monitoring.sample_interval_secs.max(1));
+    let samples = (config.monitoring.duration_secs / sample_interval.as_secs()).max(1) as usize;
+
+    let mut history = Vec::with_capacity(samples);
+    for i in 0..samples {
+        history.push(collect_snapshot(sys, MetricsScope::Full));
+        if i + 1 < samples {
+            between_samples();
+            thread::sleep(sample_interval);
+        }
+    }
+    history
+}
+
+/// Runs the security analysis on the latest sample against the samples before it and
+/// derives recommendations from it. No learned baselines are used; see
+/// [`analyze_with_baselines`].
+pub fn analyze(history: &[SystemMetrics], config: &Config) -> Analysis {
+    analyze_with_baselines(history, &Baselines::default(), config)
+}
+
+/// Like [`analyze`], but also scores the latest sample against `baselines` (see
+/// [`Baselines::load_configured`]).
+pub fn analyze_with_baselines(history: &[SystemMetrics], baselines: &Baselines, config: &Config) -> Analysis {
+    let forecasts = forecast::DiskTracker::from_samples(history, &config.forecast).forecasts();
+    analyze_with_forecasts(history, baselines, &forecasts, config)
+}
+
+/// [`analyze_with_baselines`] with disk forecasts computed elsewhere, such as over the
+/// history store.
+pub(crate) fn analyze_with_forecasts(
+    history: &[SystemMetrics],
+    baselines: &Baselines,
+    forecasts: &[forecast::DiskForecast],
+    config: &Config,
+) -> Analysis {
+    let mut security = security::perform_security_analysis(history, baselines, config);
+    security.add_findings(analysis::detect_anomalies(history, &config.anomaly));
+    security.add_findings(forecast::forecast_findings(forecasts, &config.forecast));
+    let recommendations = security::generate_recommendations(history, &security, config);
+    let health = health_score::health_score(history, security.findings.iter().chain(&recommendations), config);
+    Analysis {
+        security,
+        recommendations,
+        health,
+    }
+}
+
+/// Built-in threshold alerts for one sample. Pass them to
+/// [`NotificationManager::notify_alerts`] to deliver them.
+pub fn evaluate_alerts(metrics: &SystemMetrics, config: &Config) -> Vec<Alert> {
+    notifications::threshold_alerts(metrics, config)
+}
+
+/// Assembles the full report document (also the `monitor --format json` output).
+pub fn build_report(sys: &System, history: &[SystemMetrics], analysis: Analysis) -> MonitorReport {
+    report::build_monitor_report(sys, history, analysis.security, analysis.recommendations, analysis.health)
+}
+
+/// Renders a report as Markdown or self-contained HTML.
+pub fn render_report(report: &MonitorReport, format: ReportFormat) -> String {
+    match format {
+        ReportFormat::Markdown => render::render_markdown(report),
+        ReportFormat::Html => render::render_html(report),
+    }
+}
@@ -0,0 +1,385 @@
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use regex::Regex;
+use crate::config::LogWatchConfig;
+use crate::notifications::{Alert, AlertKind};
+
+const MAX_LINE_CHARS: usize = 200;
+/// Bytes read per poll; a file growing faster than this is caught up over later polls.
+const MAX_READ_PER_POLL: u64 = 1024 * 1024;
+
+struct CompiledPattern {
+    name: String,
+    regex: Regex,
+    max_matches: u64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PatternHits {
+    pub path: PathBuf,
+    pub pattern: String,
+    pub count: u64,
+    pub max_matches: u64,
+    pub last_line: Option<String>,
+}
+
+pub struct LogWatcher {
+    path: PathBuf,
+    patterns: Vec<CompiledPattern>,
+    file: Option<File>,
+    identity: Option<FileIdentity>,
+    offset: u64,
+    partial: Vec<u8>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct FileIdentity {
+    dev: u64,
+    ino: u64,
+}
+
+impl LogWatcher {
+    pub fn new(config: &LogWatchConfig) -> Self {
+        let patterns = config
+            .patterns
+            .iter()
+            .filter_map(|pattern| match Regex::new(&pattern.regex) {
+                Ok(regex) => Some(CompiledPattern {
+                    name: pattern.name.clone(),
+                    regex,
+                    max_matches: pattern.max_matches,
+                }),
+                Err(e) => {
+                    log::warn!("Ignoring log pattern {} for {}: {e}", pattern.name, config.path.display());
+                    None
+                }
+            })
+            .collect();
+
+        let mut watcher = Self {
+            path: config.path.clone(),
+            patterns,
+            file: None,
+            identity: None,
+            offset: 0,
+            partial: Vec::new(),
+        };
+        // Only lines written after the daemon starts are of interest.
+        watcher.open(true);
+        watcher
+    }
+
+    fn open(&mut self, from_end: bool) {
+        self.partial.clear();
+        self.offset = 0;
+        self.file = File::open(&self.path).ok();
+        self.identity = self.file.as_ref().and_then(file_identity);
+
+        if from_end {
+            if let Some(file) = self.file.as_mut() {
+                self.offset = file.seek(SeekFrom::End(0)).unwrap_or(0);
+            }
+        }
+    }
+
+    pub fn poll(&mut self) -> Vec<PatternHits> {
+        let mut hits: Vec<PatternHits> = self
+            .patterns
+            .iter()
+            .map(|pattern| PatternHits {
+                path: self.path.clone(),
+                pattern: pattern.name.clone(),
+                count: 0,
+                max_matches: pattern.max_matches,
+                last_line: None,
+            })
+            .collect();
+
+        // Finish whatever was appended to the handle we hold before following a rotation,
+        // so lines written just before the rename aren't lost.
+        let (text, behind) = self.read_new();
+        self.scan(&text, &mut hits);
+
+        if !behind && self.rotated() {
+            log::info!("Log file {} was rotated, reopening", self.path.display());
+            self.open(false);
+            let (text, _) = self.read_new();
+            self.scan(&text, &mut hits);
+        }
+
+        hits
+    }
+
+    /// Complete lines appended since the last read, at most `MAX_READ_PER_POLL` bytes of
+    /// them, and whether the read stopped at that cap.
+    fn read_new(&mut self) -> (String, bool) {
+        let Some(file) = self.file.as_mut() else {
+            return (String::new(), false);
+        };
+
+        if file.seek(SeekFrom::Start(self.offset)).is_err() {
+            return (String::new(), false);
+        }
+        let before = self.partial.len();
+        let read = match file.take(MAX_READ_PER_POLL).read_to_end(&mut self.partial) {
+            Ok(read) => read as u64,
+            Err(e) => {
+                log::warn!("Failed to read {}: {e}", self.path.display());
+                self.partial.truncate(before);
+                return (String::new(), false);
+            }
+        };
+        self.offset += read;
+        let behind = read == MAX_READ_PER_POLL;
+        if behind {
+            log::debug!("{} grew by more than {MAX_READ_PER_POLL} bytes, reading the rest next poll", self.path.display());
+        }
+
+        // Split on bytes so a multi-byte character cut by the cap is decoded once complete.
+        let text = match self.partial.iter().rposition(|&byte| byte == b'\n') {
+            Some(end) => {
+                let complete: Vec<u8> = self.partial.drain(..=end).collect();
+                String::from_utf8_lossy(&complete).into_owned()
+            }
+            None => String::new(),
+        };
+        (text, behind)
+    }
+
+    fn rotated(&self) -> bool {
+        let Ok(metadata) = std::fs::metadata(&self.path) else {
+            return false;
+        };
+
+        if self.file.is_none() {
+            return true;
+        }
+        if metadata.len() < self.offset {
+            return true;
+        }
+        match (self.identity, metadata_identity(&metadata)) {
+            (Some(current), Some(on_disk)) => current != on_disk,
+            _ => false,
+        }
+    }
+
+    fn scan(&self, text: &str, hits: &mut [PatternHits]) {
+        for line in text.lines() {
+            for (pattern, hit) in self.patterns.iter().zip(hits.iter_mut()) {
+                if pattern.regex.is_match(line) {
+                    hit.count += 1;
+                    hit.last_line = Some(truncate_line(line));
+                }
+            }
+        }
+    }
+}
+
+pub fn build_watchers(configs: &[LogWatchConfig]) -> Vec<LogWatcher> {
+    configs.iter().map(LogWatcher::new).collect()
+}
+
+pub fn evaluate_hits(hits: &[PatternHits]) -> Vec<Alert> {
+    hits.iter()
+        .map(|hit| {
+            let key = format!("{}:{}", hit.path.display(), hit.pattern);
+            let file_name = display_name(&hit.path);
+            Alert {
+                kind: AlertKind::LogPattern(key),
+                active: hit.count > hit.max_matches,
+                title: format!("Log Alert: {} in {file_name}", hit.pattern),
+                body: format!(
+                    "{} match(es) this interval (allowed: {})\n{}",
+                    hit.count,
+                    hit.max_matches,
+                    hit.last_line.as_deref().unwrap_or_default(),
+                ),
+                recovery: None,
+            }
+        })
+        .collect()
+}
+
+fn display_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.display().to_string())
+}
+
+fn truncate_line(line: &str) -> String {
+    let trimmed = line.trim_end();
+    if trimmed.chars().count() <= MAX_LINE_CHARS {
+        return trimmed.to_string();
+    }
+    let mut shortened: String = trimmed.chars().take(MAX_LINE_CHARS).collect();
+    shortened.push('…');
+    shortened
+}
+
+fn file_identity(file: &File) -> Option<FileIdentity> {
+    file.metadata().ok().as_ref().and_then(metadata_identity)
+}
+
+#[cfg(unix)]
+fn metadata_identity(metadata: &std::fs::Metadata) -> Option<FileIdentity> {
+    use std::os::unix::fs::MetadataExt;
+    Some(FileIdentity {
+        dev: metadata.dev(),
+        ino: metadata.ino(),
+    })
+}
+
+#[cfg(not(unix))]
+fn metadata_identity(_metadata: &std::fs::Metadata) -> Option<FileIdentity> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::LogPatternConfig;
+    use std::io::Write;
+
+    fn watch_config(path: &Path, max_matches: u64) -> LogWatchConfig {
+        LogWatchConfig {
+            path: path.to_path_buf(),
+            patterns: vec![LogPatternConfig {
+                name: "oom".to_string(),
+                regex: "Out of memory".to_string(),
+                max_matches,
+            }],
+        }
+    }
+
+    fn append(path: &Path, text: &str) {
+        let mut file = std::fs::OpenOptions::new().create(true).append(true).open(path).unwrap();
+        file.write_all(text.as_bytes()).unwrap();
+    }
+
+    #[test]
+    fn ignores_existing_content_and_counts_new_matches() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("app.log");
+        append(&path, "Out of memory: old\n");
+
+        let mut watcher = LogWatcher::new(&watch_config(&path, 0));
+        append(&path, "ok\nOut of memory: killed 42\nOut of memory: killed 43\n");
+
+        let hits = watcher.poll();
+        assert_eq!(hits[0].count, 2);
+        assert_eq!(hits[0].last_line.as_deref(), Some("Out of memory: killed 43"));
+
+        assert_eq!(watcher.poll()[0].count, 0);
+    }
+
+    #[test]
+    fn partial_lines_wait_for_newline() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("app.log");
+        append(&path, "");
+
+        let mut watcher = LogWatcher::new(&watch_config(&path, 0));
+        append(&path, "Out of mem");
+        assert_eq!(watcher.poll()[0].count, 0);
+        append(&path, "ory\n");
+        assert_eq!(watcher.poll()[0].count, 1);
+    }
+
+    #[test]
+    fn large_appends_are_read_over_several_polls() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("app.log");
+        append(&path, "");
+
+        let mut watcher = LogWatcher::new(&watch_config(&path, 0));
+        let line = format!("Out of memory: {}\n", "é".repeat(100));
+        let lines = (MAX_READ_PER_POLL as usize * 3 / 2) / line.len();
+        append(&path, &line.repeat(lines));
+
+        let hits = watcher.poll();
+        let first = hits[0].count;
+        assert!(first > 0 && first < lines as u64);
+        assert!(!hits[0].last_line.as_deref().unwrap().contains('\u{FFFD}'));
+        let second = watcher.poll()[0].count;
+        assert_eq!(first + second, lines as u64);
+        assert_eq!(watcher.poll()[0].last_line, None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn follows_rename_rotation() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("app.log");
+        append(&path, "");
+
+        let mut watcher = LogWatcher::new(&watch_config(&path, 0));
+        append(&path, "Out of memory: before rotate\n");
+        std::fs::rename(&path, dir.path().join("app.log.1")).unwrap();
+        append(&path, "Out of memory: after rotate\n");
+
+        let hits = watcher.poll();
+        assert_eq!(hits[0].count, 2);
+        assert_eq!(hits[0].last_line.as_deref(), Some("Out of memory: after rotate"));
+    }
+
+    #[test]
+    fn follows_copytruncate_rotation() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("app.log");
+        append(&path, "a fairly long line that makes the file bigger\n");
+
+        let mut watcher = LogWatcher::new(&watch_config(&path, 0));
+        std::fs::write(&path, "Out of memory\n").unwrap();
+
+        assert_eq!(watcher.poll()[0].count, 1);
+    }
+
+    #[test]
+    fn missing_file_is_picked_up_once_created() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("later.log");
+
+        let mut watcher = LogWatcher::new(&watch_config(&path, 0));
+        assert_eq!(watcher.poll()[0].count, 0);
+
+        append(&path, "Out of memory\n");
+        assert_eq!(watcher.poll()[0].count, 1);
+    }
+
+    #[test]
+    fn alerts_fire_when_rate_exceeds_threshold() {
+        let hit = |count| PatternHits {
+            path: PathBuf::from("/var/log/kern.log"),
+            pattern: "io_errors".to_string(),
+            count,
+            max_matches: 3,
+            last_line: Some("blk_update_request: I/O error".to_string()),
+        };
+
+        let alerts = evaluate_hits(&[hit(3), hit(4)]);
+        assert!(!alerts[0].active);
+        assert!(alerts[1].active);
+        assert_eq!(alerts[1].kind, AlertKind::LogPattern("/var/log/kern.log:io_errors".to_string()));
+        assert!(alerts[1].body.contains("I/O error"));
+        assert!(alerts[1].title.contains("kern.log"));
+    }
+
+    #[test]
+    fn invalid_regex_is_skipped() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut config = watch_config(&dir.path().join("x.log"), 0);
+        config.patterns[0].regex = "(unclosed".to_string();
+
+        let mut watcher = LogWatcher::new(&config);
+        assert!(watcher.poll().is_empty());
+    }
+
+    #[test]
+    fn long_lines_are_truncated() {
+        let line = "x".repeat(500);
+        let truncated = truncate_line(&line);
+        assert_eq!(truncated.chars().count(), MAX_LINE_CHARS + 1);
+        assert!(truncated.ends_with('…'));
+    }
+}
@@ -1,17 +1,21 @@
-use walkdir::WalkDir;
+use walkdir::{DirEntry, WalkDir};
 use std::collections::HashMap;
-use std::path::PathBuf;
-use sysinfo::{System, SystemExt, ProcessExt, DiskExt, CpuExt, NetworkExt, NetworksExt, ComponentExt};
-use crate::types::{SystemMetrics, DiskMetrics, ProcessMetrics, TempFileMetrics, TempFileInfo, TemperatureMetrics, TemperatureReading, MetricsScope};
+use std::fs::Metadata;
+use std::path::{Path, PathBuf};
+use sysinfo::{System, SystemExt, ProcessExt, DiskExt, CpuExt, NetworkExt, NetworksExt, ComponentExt, UserExt, PidExt, ProcessStatus};
+use crate::types::{SystemMetrics, DiskMetrics, ProcessMetrics, TempFileMetrics, TempFileInfo, TemperatureMetrics, TemperatureReading, MetricsScope, METRICS_SCHEMA_VERSION};
 
 pub fn collect_system_metrics(sys: &mut System, scope: MetricsScope) -> SystemMetrics {
+    let process_metrics = collect_process_metrics(sys);
     let temp_files = match scope {
         MetricsScope::Full => collect_temp_metrics(),
         MetricsScope::Light => TempFileMetrics { total_size: 0, files: Vec::new() },
     };
 
     SystemMetrics {
+        schema_version: METRICS_SCHEMA_VERSION,
         timestamp: std::time::Instant::now(),
+        recorded_at: std::time::SystemTime::now(),
         cpu_usage: sys.cpus().iter().map(|cpu| cpu.cpu_usage()).collect(),
         memory_usage: sys.used_memory(),
         memory_total: sys.total_memory(),
@@ -19,8 +23,9 @@ pub fn collect_system_metrics(sys: &mut System, scope: MetricsScope) -> SystemMe
         swap_total: sys.total_swap(),
         network_rx: sys.networks().iter().map(|(_, data)| data.received()).sum(),
         network_tx: sys.networks().iter().map(|(_, data)| data.transmitted()).sum(),
+        disk_io: process_metrics.iter().map(|process| process.disk_usage).sum(),
         disk_usage: collect_disk_metrics(sys),
-        process_metrics: collect_process_metrics(sys),
+        process_metrics,
         temp_files,
         temperature: collect_temperature_metrics(sys),
     }
@@ -52,11 +57,35 @@ pub fn collect_process_metrics(sys: &mut System) -> Vec<ProcessMetrics> {
             pid: process.pid(),
             cpu_usage: process.cpu_usage(),
             memory_usage: process.memory(),
-            disk_usage: 0,
+            disk_usage: {
+                let usage = process.disk_usage();
+                usage.read_bytes + usage.written_bytes
+            },
+            cmd: process.cmd().to_vec(),
+            user: process
+                .user_id()
+                .and_then(|uid| sys.get_user_by_id(uid))
+                .map(|user| user.name().to_string()),
+            zombie: process.status() == ProcessStatus::Zombie,
+            start_time: process.start_time(),
+            threads: thread_count(process.pid()),
+            parent: process.parent().map(|parent| parent.as_u32()),
         })
         .collect()
 }
 
+#[cfg(target_os = "linux")]
+fn thread_count(pid: sysinfo::Pid) -> Option<u32> {
+    std::fs::read_dir(format!("/proc/{}/task", pid))
+        .ok()
+        .map(|entries| entries.count() as u32)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn thread_count(_pid: sysinfo::Pid) -> Option<u32> {
+    None
+}
+
 fn create_temp_reading(celsius: f32) -> TemperatureReading {
     TemperatureReading {
         celsius,
@@ -148,24 +177,16 @@ fn collect_temp_metrics() -> TempFileMetrics {
             continue;
         }
 
-        for entry in WalkDir::new(temp_path)
-            .min_depth(1)
-            .follow_links(false)
-            .into_iter()
-            .filter_map(|e| e.ok()) {
-                if let Ok(metadata) = entry.metadata() {
-                    if metadata.is_file() {
-                        let size = metadata.len();
-                        total_size += size;
-
-                        files.push(TempFileInfo {
-                            path: entry.path().to_string_lossy().into_owned(),
-                            size,
-                            last_modified: metadata.modified().ok(),
-                        });
-                    }
-                }
-            }
+        for (entry, metadata) in walk_files(&temp_path) {
+            let size = metadata.len();
+            total_size += size;
+
+            files.push(TempFileInfo {
+                path: entry.path().to_string_lossy().into_owned(),
+                size,
+                last_modified: metadata.modified().ok(),
+            });
+        }
     }
 
     files.sort_by_key(|file| std::cmp::Reverse(file.size));
@@ -176,6 +197,18 @@ fn collect_temp_metrics() -> TempFileMetrics {
     }
 }
 
+pub fn walk_files(root: &Path) -> impl Iterator<Item = (DirEntry, Metadata)> {
+    WalkDir::new(root)
+        .min_depth(1)
+        .follow_links(false)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter_map(|entry| {
+            let metadata = entry.metadata().ok()?;
+            metadata.is_file().then_some((entry, metadata))
+        })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1,79 +1,237 @@
 use std::collections::HashMap;
 use std::time::Instant;
+use crate::attribution::{contributors, format_contributors};
 use crate::config::Config;
-use crate::types::SystemMetrics;
+use crate::history_query::Metric;
+use crate::types::{Finding, Severity, SystemMetrics};
 
+/// What an alert is about; each kind notifies and recovers on its own.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum AlertKind {
+    /// Average CPU usage over `thresholds.cpu_percent`.
     Cpu,
+    /// Memory usage over `thresholds.memory_percent`.
     Memory,
+    /// A sensor over `thresholds.temperature_celsius`.
     Temperature,
+    /// A mount over `thresholds.disk_percent`.
     Disk,
+    /// A `[[collectors]]` threshold, by collector and metric.
+    Custom(String),
+    /// A `[[log_watches]]` pattern, by file and pattern.
+    LogPattern(String),
+    /// A `[[health_checks]]` endpoint, by name.
+    Endpoint(String),
+    /// A `[[process_rules]]` limit, by rule and check.
+    ProcessRule(String),
+    /// A `[[watch_dirs]]` limit, by path and check.
+    Directory(String),
+    /// A critical finding, by finding id.
+    Finding(String),
+    /// A mount forecast to fill up within the configured horizon.
+    DiskForecast(String),
+    /// A `[[percentile_alerts]]` rule.
+    Percentile(String),
 }
 
+/// The state of one alert in the latest check. Notifications go out when it becomes active
+/// and, if `recovery` is set, when it clears.
+pub struct Alert {
+    /// What the alert is about.
+    pub kind: AlertKind,
+    /// Whether the condition holds right now.
+    pub active: bool,
+    /// Notification title while active.
+    pub title: String,
+    /// Notification body while active.
+    pub body: String,
+    /// Title and body sent when the alert clears, if anything should be sent.
+    pub recovery: Option<(String, String)>,
+}
+
+impl Alert {
+    /// An active alert for a finding, with its suggested action in the body.
+    pub fn from_finding(finding: &Finding) -> Self {
+        let body = match &finding.action {
+            Some(action) => format!("{}\n{action}", finding.summary),
+            None => finding.summary.clone(),
+        };
+        Self {
+            kind: AlertKind::Finding(finding.id.clone()),
+            active: true,
+            title: format!("{}: {}", finding.severity.label(), finding.category.title()),
+            body,
+            recovery: None,
+        }
+    }
+}
+
+/// A notification captured by a [`NotificationManager::simulated`] manager instead of being shown.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Delivered {
+    pub at: Instant,
+    pub title: String,
+    pub body: String,
+    pub recovery: bool,
+}
+
+struct Simulation {
+    now: Instant,
+    delivered: Vec<Delivered>,
+}
+
+/// Sends desktop notifications when alerts become active, at most once per cooldown per
+/// alert kind, and remembers alert state between checks.
 pub struct NotificationManager {
     last_sent: HashMap<AlertKind, Instant>,
     previous_state: HashMap<AlertKind, bool>,
+    /// Finding values last sent by [`NotificationManager::notify_urgent`].
+    urgent_values: HashMap<AlertKind, f64>,
     cooldown_secs: u64,
+    simulation: Option<Simulation>,
 }
 
 impl NotificationManager {
+    /// A manager that waits `cooldown_secs` before repeating a notification.
     pub fn new(cooldown_secs: u64) -> Self {
         Self {
             last_sent: HashMap::new(),
             previous_state: HashMap::new(),
+            urgent_values: HashMap::new(),
             cooldown_secs,
+            simulation: None,
         }
     }
 
-    pub fn check_and_notify(&mut self, metrics: &SystemMetrics, config: &Config) {
-        if !config.notifications.enabled {
-            return;
+    /// A manager on a simulated clock starting at `start` that captures notifications
+    /// (see [`Self::take_delivered`]) instead of sending them. It captures them even when
+    /// `notifications.enabled` is off, so a replay shows what a config would raise.
+    pub(crate) fn simulated(cooldown_secs: u64, start: Instant) -> Self {
+        Self {
+            simulation: Some(Simulation { now: start, delivered: Vec::new() }),
+            ..Self::new(cooldown_secs)
         }
+    }
+
+    /// Moves the simulated clock; has no effect on a manager using the real clock.
+    pub(crate) fn advance_to(&mut self, now: Instant) {
+        if let Some(simulation) = &mut self.simulation {
+            simulation.now = now;
+        }
+    }
 
-        let alerts = self.evaluate_alerts(metrics, config);
+    /// Notifications captured since the last call. Always empty when not simulated.
+    pub(crate) fn take_delivered(&mut self) -> Vec<Delivered> {
+        self.simulation
+            .as_mut()
+            .map(|simulation| std::mem::take(&mut simulation.delivered))
+            .unwrap_or_default()
+    }
 
-        for (kind, is_alerting) in alerts {
-            let was_alerting = self.previous_state.get(&kind).copied().unwrap_or(false);
-            self.previous_state.insert(kind.clone(), is_alerting);
+    fn now(&self) -> Instant {
+        self.simulation.as_ref().map_or_else(Instant::now, |simulation| simulation.now)
+    }
 
-            if is_alerting && self.should_notify(&kind, was_alerting) {
-                let (title, body) = alert_message(&kind, metrics, config);
-                self.send_notification(&title, &body, kind);
+    /// Threshold alerts for the latest sample, naming the processes behind high CPU or
+    /// memory usage.
+    pub fn check_and_notify(&mut self, metrics_history: &[SystemMetrics], config: &Config) {
+        let Some(latest) = metrics_history.last() else {
+            return;
+        };
+        let mut alerts = threshold_alerts(latest, config);
+        for alert in alerts.iter_mut().filter(|alert| alert.active) {
+            let metric = match alert.kind {
+                AlertKind::Cpu => Metric::Cpu,
+                AlertKind::Memory => Metric::Memory,
+                _ => continue,
+            };
+            let culprits = contributors(metric, metrics_history, metrics_history.len() - 1);
+            if !culprits.is_empty() {
+                alert.body.push_str(&format!("\nTop processes: {}", format_contributors(metric, &culprits)));
             }
         }
+        self.notify_alerts(alerts, config);
     }
 
-    fn evaluate_alerts(&self, metrics: &SystemMetrics, config: &Config) -> Vec<(AlertKind, bool)> {
-        let mut alerts = Vec::new();
-
-        if config.notifications.cpu_alert {
-            let avg_cpu = metrics.cpu_usage.iter().sum::<f32>() / metrics.cpu_usage.len() as f32;
-            alerts.push((AlertKind::Cpu, f64::from(avg_cpu) > config.thresholds.cpu_percent));
+    /// Notifies alerts that became active (or recovered) since the previous call, subject
+    /// only to the cooldown and `notifications.enabled`. The per-kind toggles such as
+    /// `cpu_alert` are applied when the built-in alerts are evaluated, not here.
+    pub fn notify_alerts(&mut self, alerts: Vec<Alert>, config: &Config) {
+        if !self.delivers(config) {
+            return;
         }
 
-        if config.notifications.memory_alert {
-            let memory_percent = metrics.memory_usage as f64 / metrics.memory_total as f64 * 100.0;
-            alerts.push((AlertKind::Memory, memory_percent > config.thresholds.memory_percent));
+        for alert in alerts {
+            let was_alerting = self.previous_state.get(&alert.kind).copied().unwrap_or(false);
+            if self.update_state(&alert.kind, alert.active) {
+                self.send_notification(&alert.title, &alert.body, alert.kind);
+            } else if was_alerting && !alert.active {
+                if let Some((title, body)) = &alert.recovery {
+                    self.send_recovery(title, body);
+                }
+            }
         }
+    }
 
-        if config.notifications.temperature_alert {
-            let max_temp = metrics.temperature.components.values()
-                .map(|r| r.celsius)
-                .fold(0.0_f32, f32::max);
-            alerts.push((AlertKind::Temperature, f64::from(max_temp) > config.thresholds.temperature_celsius));
+    /// Notifies about critical findings; findings that disappear since the last call clear their state.
+    pub fn notify_findings(&mut self, findings: &[Finding], config: &Config) {
+        let mut alerts: Vec<Alert> = findings
+            .iter()
+            .filter(|finding| finding.severity == Severity::Critical)
+            .map(Alert::from_finding)
+            .collect();
+
+        let cleared: Vec<AlertKind> = self
+            .previous_state
+            .iter()
+            .filter(|(kind, active)| {
+                **active
+                    && matches!(kind, AlertKind::Finding(_))
+                    && !alerts.iter().any(|alert| &alert.kind == *kind)
+            })
+            .map(|(kind, _)| kind.clone())
+            .collect();
+        alerts.extend(cleared.into_iter().map(|kind| Alert {
+            kind,
+            active: false,
+            title: String::new(),
+            body: String::new(),
+            recovery: None,
+        }));
+
+        self.notify_alerts(alerts, config);
+    }
+
+    /// Notifies about critical findings straight away, ignoring the cooldown: when each first
+    /// appears, and again whenever its value has doubled since it was last sent. They count as
+    /// sent for [`Self::notify_findings`], which clears them once they disappear.
+    pub fn notify_urgent(&mut self, findings: &[Finding], config: &Config) {
+        if !self.delivers(config) {
+            return;
         }
 
-        if config.notifications.disk_alert {
-            let any_disk_high = metrics.disk_usage.values().any(|d| {
-                if d.total == 0 { return false; }
-                let percent = d.used as f64 / d.total as f64 * 100.0;
-                percent > config.thresholds.disk_percent
-            });
-            alerts.push((AlertKind::Disk, any_disk_high));
+        for finding in findings.iter().filter(|finding| finding.severity == Severity::Critical) {
+            let alert = Alert::from_finding(finding);
+            let was_alerting = self.previous_state.insert(alert.kind.clone(), true).unwrap_or(false);
+            let escalated = match (finding.value, self.urgent_values.get(&alert.kind)) {
+                (Some(value), Some(&sent)) => sent > 0.0 && value >= sent * 2.0,
+                _ => false,
+            };
+            if !was_alerting || escalated {
+                self.urgent_values.insert(alert.kind.clone(), finding.value.unwrap_or_default());
+                self.send_notification(&alert.title, &alert.body, alert.kind);
+            }
         }
+    }
+
+    fn delivers(&self, config: &Config) -> bool {
+        config.notifications.enabled || self.simulation.is_some()
+    }
 
-        alerts
+    fn update_state(&mut self, kind: &AlertKind, is_alerting: bool) -> bool {
+        let was_alerting = self.previous_state.get(kind).copied().unwrap_or(false);
+        self.previous_state.insert(kind.clone(), is_alerting);
+        is_alerting && self.should_notify(kind, was_alerting)
     }
 
     fn should_notify(&self, kind: &AlertKind, was_alerting: bool) -> bool {
@@ -82,25 +240,88 @@ impl NotificationManager {
         }
 
         match self.last_sent.get(kind) {
-            Some(last) => last.elapsed().as_secs() >= self.cooldown_secs,
+            Some(last) => self.now().saturating_duration_since(*last).as_secs() >= self.cooldown_secs,
             None => true,
         }
     }
 
     #[cfg(test)]
-    pub fn last_sent_times(&self) -> &HashMap<AlertKind, Instant> {
+    pub(crate) fn last_sent_times(&self) -> &HashMap<AlertKind, Instant> {
         &self.last_sent
     }
 
+    fn send_recovery(&mut self, title: &str, body: &str) {
+        match self.deliver(title, body, true) {
+            Ok(()) => log::info!("Recovery notification sent: {title}"),
+            Err(e) => log::warn!("Failed to send notification: {e}"),
+        }
+    }
+
     fn send_notification(&mut self, title: &str, body: &str, kind: AlertKind) {
-        match deliver_notification(title, body) {
+        match self.deliver(title, body, false) {
             Ok(()) => log::info!("Notification sent: {title}"),
             Err(e) => log::warn!("Failed to send notification: {e}"),
         }
-        self.last_sent.insert(kind, Instant::now());
+        self.last_sent.insert(kind, self.now());
+    }
+
+    fn deliver(&mut self, title: &str, body: &str, recovery: bool) -> Result<(), String> {
+        let Some(simulation) = &mut self.simulation else {
+            return deliver_notification(title, body);
+        };
+        simulation.delivered.push(Delivered {
+            at: simulation.now,
+            title: title.to_string(),
+            body: body.to_string(),
+            recovery,
+        });
+        Ok(())
     }
 }
 
+/// Built-in CPU, memory, temperature and disk alerts for one sample, as enabled in `[notifications]`.
+pub fn threshold_alerts(metrics: &SystemMetrics, config: &Config) -> Vec<Alert> {
+    alert_states(metrics, config)
+        .into_iter()
+        .map(|(kind, active)| {
+            let (title, body) = alert_message(&kind, metrics, config);
+            Alert { kind, active, title, body, recovery: None }
+        })
+        .collect()
+}
+
+fn alert_states(metrics: &SystemMetrics, config: &Config) -> Vec<(AlertKind, bool)> {
+    let mut alerts = Vec::new();
+
+    if config.notifications.cpu_alert {
+        let avg_cpu = metrics.cpu_usage.iter().sum::<f32>() / metrics.cpu_usage.len() as f32;
+        alerts.push((AlertKind::Cpu, f64::from(avg_cpu) > config.thresholds.cpu_percent));
+    }
+
+    if config.notifications.memory_alert {
+        let memory_percent = metrics.memory_usage as f64 / metrics.memory_total as f64 * 100.0;
+        alerts.push((AlertKind::Memory, memory_percent > config.thresholds.memory_percent));
+    }
+
+    if config.notifications.temperature_alert {
+        let max_temp = metrics.temperature.components.values()
+            .map(|r| r.celsius)
+            .fold(0.0_f32, f32::max);
+        alerts.push((AlertKind::Temperature, f64::from(max_temp) > config.thresholds.temperature_celsius));
+    }
+
+    if config.notifications.disk_alert {
+        let any_disk_high = metrics.disk_usage.values().any(|d| {
+            if d.total == 0 { return false; }
+            let percent = d.used as f64 / d.total as f64 * 100.0;
+            percent > config.thresholds.disk_percent
+        });
+        alerts.push((AlertKind::Disk, any_disk_high));
+    }
+
+    alerts
+}
+
 #[cfg(target_os = "macos")]
 fn deliver_notification(title: &str, body: &str) -> Result<(), String> {
     let script = format!(
@@ -167,6 +388,19 @@ fn alert_message(kind: &AlertKind, metrics: &SystemMetrics, config: &Config) ->
                 format!("Disk usage exceeds {}%", config.thresholds.disk_percent),
             )
         }
+        AlertKind::Custom(name)
+        | AlertKind::LogPattern(name)
+        | AlertKind::Endpoint(name)
+        | AlertKind::ProcessRule(name)
+        | AlertKind::Directory(name)
+        | AlertKind::Finding(name)
+        | AlertKind::DiskForecast(name)
+        | AlertKind::Percentile(name) => {
+            (
+                format!("Alert: {name}"),
+                format!("{name} crossed its configured threshold"),
+            )
+        }
     }
 }
 
@@ -174,26 +408,12 @@ fn alert_message(kind: &AlertKind, metrics: &SystemMetrics, config: &Config) ->
 mod tests {
     use super::*;
     use crate::types::*;
-    use std::collections::HashMap;
 
     fn make_metrics(cpu: f32, memory_usage: u64, memory_total: u64) -> SystemMetrics {
         SystemMetrics {
-            timestamp: Instant::now(),
-            cpu_usage: vec![cpu],
             memory_usage,
             memory_total,
-            swap_usage: 0,
-            swap_total: 0,
-            network_rx: 0,
-            network_tx: 0,
-            disk_usage: HashMap::new(),
-            process_metrics: Vec::new(),
-            temp_files: TempFileMetrics { total_size: 0, files: Vec::new() },
-            temperature: TemperatureMetrics {
-                cpu_temp: None,
-                gpu_temp: None,
-                components: HashMap::new(),
-            },
+            ..SystemMetrics::sample(0, cpu)
         }
     }
 
@@ -203,32 +423,29 @@ mod tests {
 
     #[test]
     fn no_alerts_when_below_thresholds() {
-        let manager = NotificationManager::new(300);
         let metrics = make_metrics(10.0, 40, 100);
         let config = default_config();
 
-        let alerts = manager.evaluate_alerts(&metrics, &config);
+        let alerts = alert_states(&metrics, &config);
         assert!(alerts.iter().all(|(_, alerting)| !alerting));
     }
 
     #[test]
     fn high_cpu_triggers_alert() {
-        let manager = NotificationManager::new(300);
         let metrics = make_metrics(95.0, 40, 100);
         let config = default_config();
 
-        let alerts = manager.evaluate_alerts(&metrics, &config);
+        let alerts = alert_states(&metrics, &config);
         let cpu_alert = alerts.iter().find(|(k, _)| *k == AlertKind::Cpu);
         assert_eq!(cpu_alert, Some(&(AlertKind::Cpu, true)));
     }
 
     #[test]
     fn high_memory_triggers_alert() {
-        let manager = NotificationManager::new(300);
         let metrics = make_metrics(10.0, 85, 100);
         let config = default_config();
 
-        let alerts = manager.evaluate_alerts(&metrics, &config);
+        let alerts = alert_states(&metrics, &config);
         let mem_alert = alerts.iter().find(|(k, _)| *k == AlertKind::Memory);
         assert_eq!(mem_alert, Some(&(AlertKind::Memory, true)));
     }
@@ -240,21 +457,30 @@ mod tests {
         let mut config = default_config();
         config.notifications.enabled = false;
 
-        manager.check_and_notify(&metrics, &config);
+        manager.check_and_notify(std::slice::from_ref(&metrics), &config);
         assert!(manager.last_sent.is_empty());
     }
 
     #[test]
     fn disabled_cpu_alert_skips_cpu() {
-        let manager = NotificationManager::new(300);
         let metrics = make_metrics(95.0, 40, 100);
         let mut config = default_config();
         config.notifications.cpu_alert = false;
 
-        let alerts = manager.evaluate_alerts(&metrics, &config);
+        let alerts = alert_states(&metrics, &config);
         assert!(!alerts.iter().any(|(k, _)| *k == AlertKind::Cpu));
     }
 
+    #[test]
+    fn threshold_alerts_carry_messages() {
+        let alerts = threshold_alerts(&make_metrics(95.0, 40, 100), &default_config());
+        let cpu = alerts.iter().find(|alert| alert.kind == AlertKind::Cpu).unwrap();
+
+        assert!(cpu.active);
+        assert_eq!(cpu.title, "High CPU Usage");
+        assert!(!alerts.iter().find(|alert| alert.kind == AlertKind::Memory).unwrap().active);
+    }
+
     #[test]
     fn should_notify_on_first_transition() {
         let manager = NotificationManager::new(300);
@@ -270,13 +496,12 @@ mod tests {
 
     #[test]
     fn custom_thresholds_respected() {
-        let manager = NotificationManager::new(300);
         let metrics = make_metrics(80.0, 60, 100);
         let mut config = default_config();
         config.thresholds.cpu_percent = 70.0;
         config.thresholds.memory_percent = 50.0;
 
-        let alerts = manager.evaluate_alerts(&metrics, &config);
+        let alerts = alert_states(&metrics, &config);
 
         let cpu_alert = alerts.iter().find(|(k, _)| *k == AlertKind::Cpu);
         assert_eq!(cpu_alert, Some(&(AlertKind::Cpu, true)));
@@ -287,7 +512,6 @@ mod tests {
 
     #[test]
     fn high_temperature_triggers_alert() {
-        let manager = NotificationManager::new(300);
         let mut metrics = make_metrics(10.0, 40, 100);
         metrics.temperature.components.insert(
             "CPU".to_string(),
@@ -295,14 +519,13 @@ mod tests {
         );
         let config = default_config();
 
-        let alerts = manager.evaluate_alerts(&metrics, &config);
+        let alerts = alert_states(&metrics, &config);
         let temp_alert = alerts.iter().find(|(k, _)| *k == AlertKind::Temperature);
         assert_eq!(temp_alert, Some(&(AlertKind::Temperature, true)));
     }
 
     #[test]
     fn high_disk_triggers_alert() {
-        let manager = NotificationManager::new(300);
         let mut metrics = make_metrics(10.0, 40, 100);
         metrics.disk_usage.insert("/".to_string(), DiskMetrics {
             total: 1000,
@@ -312,7 +535,7 @@ mod tests {
         });
         let config = default_config();
 
-        let alerts = manager.evaluate_alerts(&metrics, &config);
+        let alerts = alert_states(&metrics, &config);
         let disk_alert = alerts.iter().find(|(k, _)| *k == AlertKind::Disk);
         assert_eq!(disk_alert, Some(&(AlertKind::Disk, true)));
     }
@@ -333,6 +556,181 @@ mod tests {
         assert!(body.contains("80%"), "body was: {body}");
     }
 
+    #[test]
+    fn custom_alerts_notify_once_per_transition() {
+        let mut manager = NotificationManager::simulated(300, Instant::now());
+        let config = default_config();
+        let alert = |active| Alert {
+            kind: AlertKind::Custom("queue.depth".to_string()),
+            active,
+            title: "Queue depth".to_string(),
+            body: "depth is 500".to_string(),
+            recovery: None,
+        };
+
+        manager.notify_alerts(vec![alert(true)], &config);
+        manager.notify_alerts(vec![alert(true)], &config);
+        let delivered = manager.take_delivered();
+        assert_eq!(delivered.len(), 1);
+        assert_eq!((delivered[0].title.as_str(), delivered[0].body.as_str()), ("Queue depth", "depth is 500"));
+
+        manager.notify_alerts(vec![alert(false)], &config);
+        assert!(!manager.previous_state[&AlertKind::Custom("queue.depth".to_string())]);
+        assert!(manager.take_delivered().is_empty());
+    }
+
+    #[test]
+    fn recovery_clears_alert_state() {
+        let mut manager = NotificationManager::simulated(300, Instant::now());
+        let config = default_config();
+        let alert = |active| Alert {
+            kind: AlertKind::Endpoint("api".to_string()),
+            active,
+            title: "Endpoint Down: api".to_string(),
+            body: "refused".to_string(),
+            recovery: Some(("Endpoint Recovered: api".to_string(), "api is back".to_string())),
+        };
+
+        manager.notify_alerts(vec![alert(true)], &config);
+        assert!(manager.previous_state[&AlertKind::Endpoint("api".to_string())]);
+
+        manager.notify_alerts(vec![alert(false)], &config);
+        assert!(!manager.previous_state[&AlertKind::Endpoint("api".to_string())]);
+        let delivered = manager.take_delivered();
+        assert_eq!(delivered.len(), 2);
+        assert!(!delivered[0].recovery);
+        assert!(delivered[1].recovery);
+        assert_eq!((delivered[1].title.as_str(), delivered[1].body.as_str()), ("Endpoint Recovered: api", "api is back"));
+
+        // A fresh failure after recovery notifies immediately, regardless of cooldown.
+        assert!(manager.should_notify(&AlertKind::Endpoint("api".to_string()), false));
+    }
+
+    #[test]
+    fn cpu_alerts_name_the_busiest_processes() {
+        let mut manager = NotificationManager::simulated(300, Instant::now());
+        let mut hot = make_metrics(99.0, 10, 100);
+        hot.process_metrics = vec![ProcessMetrics {
+            name: "stress".to_string(),
+            pid: sysinfo::Pid::from(42),
+            cpu_usage: 99.0,
+            memory_usage: 1,
+            disk_usage: 0,
+            cmd: Vec::new(),
+            user: None,
+            zombie: false,
+            start_time: 0,
+            threads: None,
+            parent: None,
+        }];
+
+        manager.check_and_notify(&[make_metrics(10.0, 10, 100), hot], &default_config());
+        let delivered = manager.take_delivered();
+        assert_eq!(delivered[0].body, "Average CPU at 99.0% (threshold: 90%)\nTop processes: stress (PID 42) 99.0%");
+    }
+
+    #[test]
+    fn simulated_managers_capture_and_follow_the_simulated_clock() {
+        let start = Instant::now();
+        let mut manager = NotificationManager::simulated(300, start);
+        let config = default_config();
+        let hot = make_metrics(99.0, 10, 100);
+
+        manager.check_and_notify(std::slice::from_ref(&hot), &config);
+        manager.advance_to(start + std::time::Duration::from_secs(299));
+        manager.check_and_notify(std::slice::from_ref(&hot), &config);
+        let delivered = manager.take_delivered();
+        assert_eq!(delivered.len(), 1);
+        assert_eq!(delivered[0].at, start);
+        assert!(!delivered[0].recovery);
+
+        manager.advance_to(start + std::time::Duration::from_secs(300));
+        manager.check_and_notify(std::slice::from_ref(&hot), &config);
+        assert_eq!(manager.take_delivered().len(), 1);
+        assert!(manager.take_delivered().is_empty());
+    }
+
+    #[test]
+    fn only_critical_findings_notify_and_vanished_ones_clear() {
+        let mut manager = NotificationManager::simulated(300, Instant::now());
+        let config = default_config();
+        let finding = |id: &str, severity| Finding {
+            id: id.to_string(),
+            category: FindingCategory::Memory,
+            severity,
+            subject: Subject::System,
+            summary: "memory at 95%".to_string(),
+            value: Some(95.0),
+            threshold: Some(80.0),
+            action: Some("Close applications".to_string()),
+            anomaly: None,
+        };
+
+        manager.notify_findings(&[
+            finding("memory_pressure", Severity::Critical),
+            finding("swap_pressure", Severity::Warning),
+        ], &config);
+        assert!(manager.previous_state[&AlertKind::Finding("memory_pressure".to_string())]);
+        assert!(!manager.previous_state.contains_key(&AlertKind::Finding("swap_pressure".to_string())));
+        let delivered = manager.take_delivered();
+        assert_eq!(delivered.len(), 1);
+        assert_eq!(delivered[0].title, "Critical: Memory");
+        assert_eq!(delivered[0].body, "memory at 95%\nClose applications");
+
+        manager.notify_findings(&[], &config);
+        assert!(!manager.previous_state[&AlertKind::Finding("memory_pressure".to_string())]);
+        assert!(manager.take_delivered().is_empty());
+    }
+
+    #[test]
+    fn urgent_findings_skip_the_cooldown_when_they_escalate() {
+        let start = Instant::now();
+        let mut manager = NotificationManager::simulated(300, start);
+        let config = default_config();
+        let storm = |per_min: f64| Finding {
+            id: "spawn_storm:100".to_string(),
+            category: FindingCategory::ProcessSpawning,
+            severity: Severity::Critical,
+            subject: Subject::Process { pid: 100, name: "bash".to_string() },
+            summary: format!("bash (PID 100) started processes ({per_min:.0}/min)"),
+            value: Some(per_min),
+            threshold: Some(60.0),
+            action: None,
+            anomaly: None,
+        };
+
+        manager.notify_urgent(&[storm(100.0)], &config);
+        manager.notify_findings(&[storm(100.0)], &config);
+        manager.advance_to(start + std::time::Duration::from_secs(30));
+        manager.notify_urgent(&[storm(150.0)], &config);
+        manager.notify_findings(&[storm(150.0)], &config);
+        assert_eq!(manager.take_delivered().len(), 1);
+
+        manager.advance_to(start + std::time::Duration::from_secs(60));
+        manager.notify_urgent(&[storm(400.0)], &config);
+        let delivered = manager.take_delivered();
+        assert_eq!(delivered.len(), 1);
+        assert_eq!(delivered[0].title, "Critical: Runaway Process Spawning");
+    }
+
+    #[test]
+    fn finding_alert_includes_action() {
+        let alert = Alert::from_finding(&Finding {
+            id: "zombie:7".to_string(),
+            category: FindingCategory::ZombieProcess,
+            severity: Severity::Warning,
+            subject: Subject::Process { pid: 7, name: "defunct".to_string() },
+            summary: "defunct (PID: 7)".to_string(),
+            value: None,
+            threshold: None,
+            action: Some("Restart the parent".to_string()),
+            anomaly: None,
+        });
+        assert_eq!(alert.kind, AlertKind::Finding("zombie:7".to_string()));
+        assert_eq!(alert.title, "Warning: Zombie Processes");
+        assert_eq!(alert.body, "defunct (PID: 7)\nRestart the parent");
+    }
+
     #[cfg(target_os = "macos")]
     #[test]
     fn applescript_escaping_handles_quotes_and_backslashes() {
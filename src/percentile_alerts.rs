@@ -0,0 +1,186 @@
+//! Alerts on a percentile of a metric over a trailing window (`[[percentile_alerts]]`).
+
+use std::collections::{BTreeMap, VecDeque};
+use std::time::{Duration, SystemTime};
+use crate::analysis::percentile;
+use crate::config::PercentileAlertConfig;
+use crate::history_query::Metric;
+use crate::notifications::{Alert, AlertKind};
+use crate::types::SystemMetrics;
+
+/// Recent values of every metric a rule watches, kept for the longest window on that metric.
+pub struct PercentileAlerts {
+    rules: Vec<PercentileAlertConfig>,
+    values: BTreeMap<Metric, VecDeque<(SystemTime, f64)>>,
+}
+
+impl PercentileAlerts {
+    pub fn new(rules: &[PercentileAlertConfig]) -> Self {
+        Self { rules: rules.to_vec(), values: BTreeMap::new() }
+    }
+
+    /// Adds stored samples, such as those recorded before a restart, oldest first.
+    pub fn seed(&mut self, samples: &[SystemMetrics]) {
+        for metric in self.metrics() {
+            for (at, value) in metric.sample_values(samples) {
+                self.push(metric, at, value);
+            }
+        }
+    }
+
+    /// Records the latest sample in `metrics_history` and returns an alert per rule whose
+    /// window is covered.
+    pub fn observe(&mut self, metrics_history: &[SystemMetrics]) -> Vec<Alert> {
+        let Some(latest) = metrics_history.last() else {
+            return Vec::new();
+        };
+        for metric in self.metrics() {
+            if let Some(value) = metric.latest_value(metrics_history) {
+                self.push(metric, latest.recorded_at, value);
+            }
+        }
+        self.evaluate(latest.recorded_at)
+    }
+
+    /// Alerts for the rules with values spanning their whole window up to `now`.
+    pub fn evaluate(&self, now: SystemTime) -> Vec<Alert> {
+        self.rules
+            .iter()
+            .filter_map(|rule| {
+                let value = self.percentile(rule, now)?;
+                let name = format!("p{} {}", rule.percentile, rule.metric.label());
+                let window = format_window(rule.window_mins);
+                let breach = match (rule.max, rule.min) {
+                    (Some(max), _) if value > max => Some(format!("max: {}", rule.metric.format_value(max))),
+                    (_, Some(min)) if value < min => Some(format!("min: {}", rule.metric.format_value(min))),
+                    _ => None,
+                };
+                let current = format!("{name} over the last {window} is {}", rule.metric.format_value(value));
+                Some(Alert {
+                    kind: AlertKind::Percentile(format!("{}:p{}:{}", rule.metric.name(), rule.percentile, rule.window_mins)),
+                    active: breach.is_some(),
+                    title: format!("Percentile Alert: {name}"),
+                    body: match breach {
+                        Some(limit) => format!("{current} ({limit})"),
+                        None => current.clone(),
+                    },
+                    recovery: Some((
+                        format!("Percentile Recovered: {name}"),
+                        current,
+                    )),
+                })
+            })
+            .collect()
+    }
+
+    /// The rule's percentile, or `None` until the values reach back a whole window, give or
+    /// take one sampling interval.
+    fn percentile(&self, rule: &PercentileAlertConfig, now: SystemTime) -> Option<f64> {
+        let start = now.checked_sub(window(rule.window_mins))?;
+        let in_window: Vec<(SystemTime, f64)> =
+            self.values.get(&rule.metric)?.iter().filter(|(at, _)| *at >= start).copied().collect();
+        let interval = in_window
+            .windows(2)
+            .map(|pair| pair[1].0.duration_since(pair[0].0).unwrap_or_default())
+            .max()?;
+        if in_window[0].0.duration_since(start).unwrap_or_default() > interval {
+            return None;
+        }
+        let mut sorted: Vec<f64> = in_window.iter().map(|(_, value)| *value).collect();
+        sorted.sort_by(f64::total_cmp);
+        Some(percentile(&sorted, rule.percentile))
+    }
+
+    fn metrics(&self) -> Vec<Metric> {
+        let mut metrics: Vec<Metric> = self.rules.iter().map(|rule| rule.metric).collect();
+        metrics.sort();
+        metrics.dedup();
+        metrics
+    }
+
+    fn push(&mut self, metric: Metric, at: SystemTime, value: f64) {
+        let longest = self
+            .rules
+            .iter()
+            .filter(|rule| rule.metric == metric)
+            .map(|rule| window(rule.window_mins))
+            .max()
+            .unwrap_or_default();
+        let values = self.values.entry(metric).or_default();
+        if values.back().is_some_and(|(last, _)| *last >= at) {
+            return;
+        }
+        values.push_back((at, value));
+        while values.front().is_some_and(|(first, _)| at.duration_since(*first).unwrap_or_default() > longest) {
+            values.pop_front();
+        }
+    }
+}
+
+fn window(minutes: f64) -> Duration {
+    Duration::from_secs_f64(minutes.max(0.0) * 60.0)
+}
+
+/// `15 minutes` or `2 hours`.
+fn format_window(minutes: f64) -> String {
+    if minutes >= 120.0 && minutes % 60.0 == 0.0 {
+        format!("{} hours", minutes / 60.0)
+    } else {
+        format!("{minutes} minutes")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::*;
+
+    fn sample(minute: u64, cpu: f32) -> SystemMetrics {
+        SystemMetrics::sample(minute * 60, cpu)
+    }
+
+    fn p95_cpu_rule() -> PercentileAlertConfig {
+        PercentileAlertConfig { metric: Metric::Cpu, percentile: 95.0, window_mins: 15.0, max: Some(85.0), min: None }
+    }
+
+    #[test]
+    fn alerts_once_the_window_is_covered_and_ignores_short_bursts() {
+        let mut alerts = PercentileAlerts::new(&[p95_cpu_rule()]);
+        let mut history = Vec::new();
+        for minute in 0..14 {
+            history.push(sample(minute, 95.0));
+            assert!(alerts.observe(&history).is_empty(), "window not covered at minute {minute}");
+        }
+
+        // Fifteen one-minute samples cover the window.
+        history.push(sample(14, 95.0));
+        let fired = alerts.observe(&history);
+        assert_eq!(fired.len(), 1);
+        assert!(fired[0].active);
+        assert_eq!(fired[0].title, "Percentile Alert: p95 CPU usage");
+        assert_eq!(fired[0].body, "p95 CPU usage over the last 15 minutes is 95.0% (max: 85.0%)");
+
+        // Mostly idle with a one-minute burst: p95 over half an hour stays below the limit.
+        let mut alerts = PercentileAlerts::new(&[PercentileAlertConfig { window_mins: 30.0, ..p95_cpu_rule() }]);
+        let history: Vec<_> = (0..=60).map(|minute| sample(minute, if minute == 45 { 100.0 } else { 20.0 })).collect();
+        alerts.seed(&history[..60]);
+        let fired = alerts.observe(&history);
+        assert!(!fired[0].active);
+        assert_eq!(fired[0].recovery.as_ref().unwrap().1, "p95 CPU usage over the last 30 minutes is 20.0%");
+    }
+
+    #[test]
+    fn keeps_only_the_longest_window_per_metric() {
+        let rules = [p95_cpu_rule(), PercentileAlertConfig { window_mins: 60.0, min: Some(10.0), max: None, ..p95_cpu_rule() }];
+        let mut alerts = PercentileAlerts::new(&rules);
+        let samples: Vec<_> = (0..=120).map(|minute| sample(minute, 5.0)).collect();
+        alerts.seed(&samples);
+
+        assert_eq!(alerts.values[&Metric::Cpu].len(), 61);
+        let fired = alerts.evaluate(samples[120].recorded_at);
+        assert_eq!(fired.len(), 2);
+        assert!(!fired[0].active);
+        assert!(fired[1].active);
+        assert!(fired[1].body.ends_with("(min: 10.0%)"));
+    }
+}
@@ -0,0 +1,160 @@
+//! Listening TCP ports, read from `/proc/net/tcp` and `/proc/net/tcp6` on Linux.
+
+use std::net::{Ipv4Addr, Ipv6Addr};
+use serde::{Deserialize, Serialize};
+
+/// `st` value of a listening socket in `/proc/net/tcp`.
+const TCP_LISTEN: &str = "0A";
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct ListeningPort {
+    /// `tcp` or `tcp6`.
+    pub protocol: String,
+    pub address: String,
+    pub port: u16,
+    /// Owning process, when its file descriptors are readable.
+    pub pid: Option<u32>,
+    pub process: Option<String>,
+}
+
+/// A listening socket parsed from one `/proc/net/tcp*` table.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProcSocket {
+    pub address: String,
+    pub port: u16,
+    pub inode: u64,
+}
+
+/// Listening sockets in the contents of `/proc/net/tcp` or `/proc/net/tcp6`.
+pub fn parse_proc_net_tcp(contents: &str) -> Vec<ProcSocket> {
+    contents
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            if fields.get(3) != Some(&TCP_LISTEN) {
+                return None;
+            }
+            let (address, port) = fields.get(1)?.split_once(':')?;
+            Some(ProcSocket {
+                address: parse_hex_address(address)?,
+                port: u16::from_str_radix(port, 16).ok()?,
+                inode: fields.get(9)?.parse().ok()?,
+            })
+        })
+        .collect()
+}
+
+/// Addresses are stored as 32-bit words in host (little-endian) byte order.
+fn parse_hex_address(hex: &str) -> Option<String> {
+    let mut bytes = Vec::with_capacity(16);
+    for word in 0..hex.len() / 8 {
+        let value = u32::from_str_radix(hex.get(word * 8..word * 8 + 8)?, 16).ok()?;
+        bytes.extend_from_slice(&value.to_le_bytes());
+    }
+    match bytes.len() {
+        4 => Some(Ipv4Addr::new(bytes[0], bytes[1], bytes[2], bytes[3]).to_string()),
+        16 => {
+            let octets: [u8; 16] = bytes.try_into().ok()?;
+            Some(Ipv6Addr::from(octets).to_string())
+        }
+        _ => None,
+    }
+}
+
+#[cfg(target_os = "linux")]
+pub fn listening_ports() -> Vec<ListeningPort> {
+    let owners = socket_owners();
+    let mut ports = Vec::new();
+    for protocol in ["tcp", "tcp6"] {
+        let Ok(contents) = std::fs::read_to_string(format!("/proc/net/{protocol}")) else {
+            continue;
+        };
+        for socket in parse_proc_net_tcp(&contents) {
+            let owner = owners.get(&socket.inode);
+            ports.push(ListeningPort {
+                protocol: protocol.to_string(),
+                address: socket.address,
+                port: socket.port,
+                pid: owner.map(|(pid, _)| *pid),
+                process: owner.map(|(_, name)| name.clone()),
+            });
+        }
+    }
+    ports.sort();
+    ports.dedup();
+    ports
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn listening_ports() -> Vec<ListeningPort> {
+    Vec::new()
+}
+
+/// Socket inode to owning pid and process name, for the processes whose descriptors we can read.
+#[cfg(target_os = "linux")]
+fn socket_owners() -> std::collections::HashMap<u64, (u32, String)> {
+    let mut owners = std::collections::HashMap::new();
+    let Ok(processes) = std::fs::read_dir("/proc") else {
+        return owners;
+    };
+    for entry in processes.flatten() {
+        let Some(pid) = entry.file_name().to_str().and_then(|name| name.parse::<u32>().ok()) else {
+            continue;
+        };
+        let Ok(fds) = std::fs::read_dir(entry.path().join("fd")) else {
+            continue;
+        };
+        let name = std::fs::read_to_string(entry.path().join("comm"))
+            .map(|comm| comm.trim().to_string())
+            .unwrap_or_default();
+        for fd in fds.flatten() {
+            if let Some(inode) = std::fs::read_link(fd.path())
+                .ok()
+                .and_then(|target| socket_inode(&target.to_string_lossy()))
+            {
+                owners.entry(inode).or_insert_with(|| (pid, name.clone()));
+            }
+        }
+    }
+    owners
+}
+
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn socket_inode(link: &str) -> Option<u64> {
+    link.strip_prefix("socket:[")?.strip_suffix(']')?.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_listening_sockets_only() {
+        let tcp = "  sl  local_address rem_address   st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode
+   0: 0100007F:0277 00000000:0000 0A 00000000:00000000 00:00000000 00000000     0        0 23456 1 0000000000000000 100 0 0 10 0
+   1: 0F02000A:A2C4 5E6B7C8D:01BB 01 00000000:00000000 02:000001F4 00000000  1000        0 34567 2 0000000000000000 20 4 30 10 -1
+   2: 00000000:1F90 00000000:0000 0A 00000000:00000000 00:00000000 00000000  1000        0 45678 1 0000000000000000 100 0 0 10 0";
+
+        assert_eq!(parse_proc_net_tcp(tcp), vec![
+            ProcSocket { address: "127.0.0.1".to_string(), port: 631, inode: 23456 },
+            ProcSocket { address: "0.0.0.0".to_string(), port: 8080, inode: 45678 },
+        ]);
+    }
+
+    #[test]
+    fn parses_ipv6_addresses() {
+        let tcp6 = "  sl  local_address                         remote_address                        st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode
+   0: 00000000000000000000000001000000:0016 00000000000000000000000000000000:0000 0A 00000000:00000000 00:00000000 00000000     0        0 1234 1 0000000000000000 100 0 0 10 0";
+
+        let sockets = parse_proc_net_tcp(tcp6);
+        assert_eq!(sockets[0].address, "::1");
+        assert_eq!(sockets[0].port, 22);
+    }
+
+    #[test]
+    fn socket_links_yield_inodes() {
+        assert_eq!(socket_inode("socket:[23456]"), Some(23456));
+        assert_eq!(socket_inode("pipe:[23456]"), None);
+    }
+}
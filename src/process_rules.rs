@@ -0,0 +1,300 @@
+use regex::Regex;
+use sysinfo::PidExt;
+use humansize::{format_size, BINARY};
+use crate::config::ProcessRuleConfig;
+use crate::notifications::{Alert, AlertKind};
+use crate::types::ProcessMetrics;
+
+pub struct ProcessRule {
+    config: ProcessRuleConfig,
+    regex: Option<Regex>,
+}
+
+impl ProcessRule {
+    pub fn new(config: &ProcessRuleConfig) -> Result<Self, String> {
+        if config.match_name.is_none()
+            && config.match_regex.is_none()
+            && config.match_cmdline.is_none()
+            && config.match_user.is_none()
+        {
+            return Err("set at least one of match_name, match_regex, match_cmdline or match_user".to_string());
+        }
+        let regex = config
+            .match_regex
+            .as_deref()
+            .map(Regex::new)
+            .transpose()
+            .map_err(|e| e.to_string())?;
+
+        Ok(Self {
+            config: config.clone(),
+            regex,
+        })
+    }
+
+    pub fn matches(&self, process: &ProcessMetrics) -> bool {
+        if let Some(name) = &self.config.match_name {
+            if !process.name.eq_ignore_ascii_case(name) {
+                return false;
+            }
+        }
+        if let Some(regex) = &self.regex {
+            if !regex.is_match(&process.name) {
+                return false;
+            }
+        }
+        if let Some(needle) = &self.config.match_cmdline {
+            if !process.cmd.join(" ").contains(needle.as_str()) {
+                return false;
+            }
+        }
+        if let Some(user) = &self.config.match_user {
+            if process.user.as_deref() != Some(user.as_str()) {
+                return false;
+            }
+        }
+        true
+    }
+
+    pub fn evaluate(&self, processes: &[ProcessMetrics]) -> Vec<Alert> {
+        let matched: Vec<&ProcessMetrics> = processes.iter().filter(|p| self.matches(p)).collect();
+        let rule = &self.config;
+        let mut alerts = Vec::new();
+
+        if let Some(min) = rule.min_instances {
+            alerts.push(self.alert(
+                "missing",
+                matched.len() < min,
+                format!("Process Missing: {}", rule.name),
+                format!("{} running instance(s), expected at least {min}", matched.len()),
+            ));
+        }
+
+        if let Some(max) = rule.max_instances {
+            alerts.push(self.alert(
+                "instances",
+                matched.len() > max,
+                format!("Too Many Processes: {}", rule.name),
+                format!("{} running instance(s), expected at most {max}", matched.len()),
+            ));
+        }
+
+        if let Some(max) = rule.max_cpu_percent {
+            let worst = matched.iter().max_by(|a, b| a.cpu_usage.total_cmp(&b.cpu_usage));
+            alerts.push(self.alert(
+                "cpu",
+                worst.is_some_and(|p| f64::from(p.cpu_usage) > max),
+                format!("High Process CPU: {}", rule.name),
+                worst
+                    .map(|p| format!("{} at {:.1}% CPU (threshold: {max}%)", describe(p), p.cpu_usage))
+                    .unwrap_or_default(),
+            ));
+        }
+
+        if let Some(max_mb) = rule.max_memory_mb {
+            let limit = (max_mb * 1024.0 * 1024.0) as u64;
+            let worst = matched.iter().max_by_key(|p| p.memory_usage);
+            alerts.push(self.alert(
+                "memory",
+                worst.is_some_and(|p| p.memory_usage > limit),
+                format!("High Process Memory: {}", rule.name),
+                worst
+                    .map(|p| format!(
+                        "{} using {} (threshold: {})",
+                        describe(p),
+                        format_size(p.memory_usage, BINARY),
+                        format_size(limit, BINARY),
+                    ))
+                    .unwrap_or_default(),
+            ));
+        }
+
+        if let Some(max) = rule.max_open_files {
+            let worst = matched
+                .iter()
+                .filter_map(|p| open_file_count(p.pid).map(|count| (p, count)))
+                .max_by_key(|(_, count)| *count);
+            alerts.push(self.alert(
+                "fds",
+                worst.is_some_and(|(_, count)| count > max),
+                format!("Too Many Open Files: {}", rule.name),
+                worst
+                    .map(|(p, count)| format!("{} has {count} open files (threshold: {max})", describe(p)))
+                    .unwrap_or_default(),
+            ));
+        }
+
+        alerts
+    }
+
+    fn alert(&self, check: &str, active: bool, title: String, body: String) -> Alert {
+        Alert {
+            kind: AlertKind::ProcessRule(format!("{}:{check}", self.config.name)),
+            active,
+            title,
+            body,
+            recovery: None,
+        }
+    }
+}
+
+pub fn build_rules(configs: &[ProcessRuleConfig]) -> Vec<ProcessRule> {
+    configs
+        .iter()
+        .filter_map(|config| match ProcessRule::new(config) {
+            Ok(rule) => Some(rule),
+            Err(e) => {
+                log::warn!("Ignoring process rule {}: {e}", config.name);
+                None
+            }
+        })
+        .collect()
+}
+
+pub fn evaluate_rules(rules: &[ProcessRule], processes: &[ProcessMetrics]) -> Vec<Alert> {
+    rules.iter().flat_map(|rule| rule.evaluate(processes)).collect()
+}
+
+fn describe(process: &ProcessMetrics) -> String {
+    format!("{} (pid {})", process.name, process.pid.as_u32())
+}
+
+#[cfg(target_os = "linux")]
+pub fn open_file_count(pid: sysinfo::Pid) -> Option<usize> {
+    std::fs::read_dir(format!("/proc/{}/fd", pid.as_u32()))
+        .ok()
+        .map(|entries| entries.count())
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn open_file_count(_pid: sysinfo::Pid) -> Option<usize> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn process(name: &str, pid: usize, cpu_usage: f32, memory_mb: u64) -> ProcessMetrics {
+        ProcessMetrics {
+            name: name.to_string(),
+            pid: sysinfo::Pid::from(pid),
+            cpu_usage,
+            memory_usage: memory_mb * 1024 * 1024,
+            disk_usage: 0,
+            cmd: vec![name.to_string()],
+            user: Some("alice".to_string()),
+            zombie: false,
+            start_time: 0,
+            threads: None,
+            parent: None,
+        }
+    }
+
+    fn rule(config: ProcessRuleConfig) -> ProcessRule {
+        ProcessRule::new(&config).unwrap()
+    }
+
+    fn active_keys(alerts: &[Alert]) -> Vec<String> {
+        alerts
+            .iter()
+            .filter(|alert| alert.active)
+            .map(|alert| match &alert.kind {
+                AlertKind::ProcessRule(key) => key.clone(),
+                other => panic!("unexpected alert kind {other:?}"),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn missing_process_alerts() {
+        let postgres = rule(ProcessRuleConfig {
+            name: "postgres".to_string(),
+            match_name: Some("postgres".to_string()),
+            min_instances: Some(1),
+            ..ProcessRuleConfig::default()
+        });
+
+        let running = vec![process("postgres", 10, 1.0, 100)];
+        assert!(active_keys(&postgres.evaluate(&running)).is_empty());
+
+        let stopped = vec![process("bash", 11, 1.0, 10)];
+        assert_eq!(active_keys(&postgres.evaluate(&stopped)), vec!["postgres:missing"]);
+    }
+
+    #[test]
+    fn memory_threshold_names_the_worst_process() {
+        let node = rule(ProcessRuleConfig {
+            name: "node".to_string(),
+            match_regex: Some("^node".to_string()),
+            max_memory_mb: Some(2048.0),
+            ..ProcessRuleConfig::default()
+        });
+        let processes = vec![
+            process("node", 20, 5.0, 512),
+            process("node-worker", 21, 5.0, 3072),
+            process("python", 22, 5.0, 8192),
+        ];
+
+        let alerts = node.evaluate(&processes);
+        assert_eq!(active_keys(&alerts), vec!["node:memory"]);
+        assert!(alerts[0].body.contains("node-worker (pid 21)"), "body: {}", alerts[0].body);
+    }
+
+    #[test]
+    fn instance_and_cpu_limits() {
+        let workers = rule(ProcessRuleConfig {
+            name: "workers".to_string(),
+            match_cmdline: Some("worker".to_string()),
+            max_instances: Some(1),
+            max_cpu_percent: Some(50.0),
+            ..ProcessRuleConfig::default()
+        });
+        let processes = vec![process("worker", 30, 80.0, 10), process("worker", 31, 10.0, 10)];
+
+        assert_eq!(active_keys(&workers.evaluate(&processes)), vec!["workers:instances", "workers:cpu"]);
+    }
+
+    #[test]
+    fn all_matchers_must_agree() {
+        let scoped = rule(ProcessRuleConfig {
+            name: "scoped".to_string(),
+            match_name: Some("Postgres".to_string()),
+            match_user: Some("postgres".to_string()),
+            ..ProcessRuleConfig::default()
+        });
+
+        let mut owned = process("postgres", 40, 0.0, 10);
+        assert!(!scoped.matches(&owned));
+        owned.user = Some("postgres".to_string());
+        assert!(scoped.matches(&owned));
+    }
+
+    #[test]
+    fn invalid_regex_rules_are_dropped() {
+        let rules = build_rules(&[ProcessRuleConfig {
+            name: "bad".to_string(),
+            match_regex: Some("(".to_string()),
+            ..ProcessRuleConfig::default()
+        }]);
+        assert!(rules.is_empty());
+    }
+
+    #[test]
+    fn rules_without_matchers_are_rejected() {
+        let unscoped = ProcessRuleConfig {
+            name: "everything".to_string(),
+            max_cpu_percent: Some(50.0),
+            ..ProcessRuleConfig::default()
+        };
+        assert!(ProcessRule::new(&unscoped).is_err());
+        assert!(build_rules(&[unscoped]).is_empty());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn counts_open_files_for_own_process() {
+        let own_pid = sysinfo::Pid::from_u32(std::process::id());
+        assert!(open_file_count(own_pid).unwrap() > 0);
+    }
+}
@@ -0,0 +1,273 @@
+//! Recording the raw sample stream to a file and replaying it against a config
+//! (`system-monitor record` / `replay`).
+
+use std::collections::BTreeMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::Path;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+use serde::{Deserialize, Serialize};
+use sysinfo::{System, SystemExt};
+use crate::baseline::Baselines;
+use crate::config::Config;
+use crate::daemon::{check_samples, push_sample, SampleState, MAX_HISTORY};
+use crate::history::rebase_timestamps;
+use crate::metrics::collect_system_metrics;
+use crate::notifications::NotificationManager;
+use crate::process_rules::build_rules;
+use crate::types::{MetricsScope, SystemMetrics};
+
+pub const RECORDING_FORMAT_VERSION: u32 = 1;
+
+/// First line of a recording; every following line is one [`SystemMetrics`] sample.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordingHeader {
+    pub format_version: u32,
+    pub recorder_version: String,
+    pub started_at: SystemTime,
+    pub interval_secs: u64,
+    pub host_name: Option<String>,
+}
+
+pub struct Recording {
+    pub header: RecordingHeader,
+    pub samples: Vec<SystemMetrics>,
+}
+
+/// Appends samples one line at a time, so an interrupted recording stays readable.
+pub struct RecordingWriter {
+    file: File,
+}
+
+impl RecordingWriter {
+    pub fn create(path: &Path, header: &RecordingHeader) -> io::Result<Self> {
+        let mut writer = Self {
+            file: OpenOptions::new().write(true).create(true).truncate(true).open(path)?,
+        };
+        writer.write_line(header)?;
+        Ok(writer)
+    }
+
+    pub fn append(&mut self, metrics: &SystemMetrics) -> io::Result<()> {
+        self.write_line(metrics)
+    }
+
+    fn write_line<T: Serialize>(&mut self, record: &T) -> io::Result<()> {
+        let mut line = serde_json::to_vec(record).map_err(io::Error::other)?;
+        line.push(b'\n');
+        self.file.write_all(&line)
+    }
+}
+
+/// Samples every `interval` for `duration` the way the daemon does, calling `on_sample`
+/// with the number written so far. Returns the number of samples written.
+pub fn record(
+    sys: &mut System,
+    path: &Path,
+    duration: Duration,
+    interval: Duration,
+    mut on_sample: impl FnMut(usize),
+) -> io::Result<usize> {
+    let interval = interval.max(Duration::from_secs(1));
+    let samples = (duration.as_secs() / interval.as_secs()).max(1) as usize;
+    let mut writer = RecordingWriter::create(path, &RecordingHeader {
+        format_version: RECORDING_FORMAT_VERSION,
+        recorder_version: env!("CARGO_PKG_VERSION").to_string(),
+        started_at: SystemTime::now(),
+        interval_secs: interval.as_secs(),
+        host_name: sys.host_name(),
+    })?;
+
+    let start = Instant::now();
+    for i in 0..samples {
+        sys.refresh_all();
+        writer.append(&collect_system_metrics(sys, MetricsScope::Light))?;
+        on_sample(i + 1);
+        if i + 1 < samples {
+            thread::sleep((start + interval * (i as u32 + 1)).saturating_duration_since(Instant::now()));
+        }
+    }
+    Ok(samples)
+}
+
+/// Reads a recording. Unreadable sample lines, such as a torn last line from an
+/// interrupted `record`, are skipped.
+pub fn read_recording(path: &Path) -> Result<Recording, String> {
+    let contents = fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {e}", path.display()))?;
+    let mut lines = contents.lines();
+    let header: RecordingHeader = lines
+        .next()
+        .and_then(|line| serde_json::from_str(line).ok())
+        .ok_or_else(|| format!("{} is not a system-monitor recording", path.display()))?;
+    if header.format_version != RECORDING_FORMAT_VERSION {
+        return Err(format!(
+            "{} has recording format {}, expected {RECORDING_FORMAT_VERSION}",
+            path.display(),
+            header.format_version
+        ));
+    }
+
+    let mut samples = Vec::new();
+    for (number, line) in lines.enumerate() {
+        match serde_json::from_str::<SystemMetrics>(line) {
+            Ok(sample) => samples.push(sample),
+            Err(e) => log::warn!("Skipping {}:{}: {e}", path.display(), number + 2),
+        }
+    }
+    rebase_timestamps(&mut samples);
+    Ok(Recording { header, samples })
+}
+
+/// A notification the replayed session would have sent.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReplayEvent {
+    /// Time since the first sample.
+    pub offset: Duration,
+    pub recorded_at: SystemTime,
+    pub title: String,
+    pub body: String,
+    pub recovery: bool,
+}
+
+#[derive(Debug, Default)]
+pub struct ReplayOutcome {
+    pub samples: usize,
+    pub span: Duration,
+    pub notifications: Vec<ReplayEvent>,
+    /// Number of samples each finding id was reported for.
+    pub findings: BTreeMap<String, usize>,
+}
+
+/// Feeds recorded samples through the daemon's sample checks (threshold alerts, process
+/// rules, security analysis and recommendations) under `config`, on a clock that follows
+/// the samples' recording times. Notifications are collected rather than sent, and the
+/// samples are learned into a copy of `baselines`.
+pub fn replay(samples: &[SystemMetrics], baselines: &Baselines, config: &Config) -> ReplayOutcome {
+    let Some(first) = samples.first().map(|sample| sample.recorded_at) else {
+        return ReplayOutcome::default();
+    };
+    let start = Instant::now();
+    let mut manager = NotificationManager::simulated(config.notifications.cooldown_secs, start);
+    let rules = build_rules(&config.process_rules);
+    let mut state = SampleState::new(baselines.clone(), config);
+    let mut history = Vec::with_capacity(MAX_HISTORY);
+    let mut outcome = ReplayOutcome { samples: samples.len(), ..ReplayOutcome::default() };
+
+    for sample in samples {
+        let offset = sample.recorded_at.duration_since(first).unwrap_or_default();
+        manager.advance_to(start + offset);
+        push_sample(&mut history, sample.clone());
+
+        let analysis = check_samples(&mut manager, &rules, &mut state, &history, config);
+        let mut ids: Vec<&str> = analysis
+            .security
+            .findings
+            .iter()
+            .chain(&analysis.recommendations)
+            .map(|finding| finding.id.as_str())
+            .collect();
+        ids.sort_unstable();
+        ids.dedup();
+        for id in ids {
+            *outcome.findings.entry(id.to_string()).or_default() += 1;
+        }
+
+        outcome.notifications.extend(manager.take_delivered().into_iter().map(|delivered| ReplayEvent {
+            offset,
+            recorded_at: sample.recorded_at,
+            title: delivered.title,
+            body: delivered.body,
+            recovery: delivered.recovery,
+        }));
+        outcome.span = offset;
+    }
+    outcome
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::*;
+    use std::time::UNIX_EPOCH;
+
+    fn sample(secs: u64, cpu: f32) -> SystemMetrics {
+        SystemMetrics::sample(secs, cpu)
+    }
+
+    fn header() -> RecordingHeader {
+        RecordingHeader {
+            format_version: RECORDING_FORMAT_VERSION,
+            recorder_version: "test".to_string(),
+            started_at: UNIX_EPOCH,
+            interval_secs: 60,
+            host_name: Some("box".to_string()),
+        }
+    }
+
+    #[test]
+    fn recordings_round_trip_and_skip_torn_lines() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("session.smrec");
+        let mut writer = RecordingWriter::create(&path, &header()).unwrap();
+        writer.append(&sample(0, 10.0)).unwrap();
+        writer.append(&sample(60, 20.0)).unwrap();
+        drop(writer);
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(b"{\"schema_version\":1,\"cpu").unwrap();
+
+        let recording = read_recording(&path).unwrap();
+        assert_eq!(recording.header, header());
+        assert_eq!(recording.samples.len(), 2);
+        assert_eq!(recording.samples[1].cpu_usage, vec![20.0]);
+        assert_eq!(
+            recording.samples[1].timestamp.duration_since(recording.samples[0].timestamp),
+            Duration::from_secs(60)
+        );
+    }
+
+    #[test]
+    fn files_without_a_header_are_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("not-a-recording.smrec");
+        fs::write(&path, "hello\n").unwrap();
+        assert!(read_recording(&path).err().unwrap().contains("not a system-monitor recording"));
+
+        let mut future = header();
+        future.format_version = RECORDING_FORMAT_VERSION + 1;
+        fs::write(&path, serde_json::to_string(&future).unwrap()).unwrap();
+        assert!(read_recording(&path).err().unwrap().contains("recording format"));
+    }
+
+    #[test]
+    fn replay_applies_cooldown_on_the_recorded_clock() {
+        // CPU stays high for 10 minutes, then recovers and spikes again.
+        let samples: Vec<_> = (0..14)
+            .map(|minute| sample(minute * 60, if (10..12).contains(&minute) { 5.0 } else { 99.0 }))
+            .collect();
+
+        let mut config = Config::default();
+        config.notifications.cooldown_secs = 300;
+        let outcome = replay(&samples, &Baselines::default(), &config);
+
+        let offsets: Vec<u64> = outcome.notifications.iter().map(|event| event.offset.as_secs()).collect();
+        assert_eq!(offsets, vec![0, 300, 720]);
+        assert_eq!(outcome.samples, 14);
+        assert_eq!(outcome.span, Duration::from_secs(13 * 60));
+        assert_eq!(outcome.findings["cpu_cores"], 12);
+
+        config.thresholds.cpu_percent = 99.5;
+        assert!(replay(&samples, &Baselines::default(), &config).notifications.is_empty());
+    }
+
+    #[test]
+    fn replay_shows_alerts_even_with_notifications_disabled() {
+        let samples: Vec<_> = (0..3).map(|minute| sample(minute * 60, 99.0)).collect();
+        let mut config = Config::default();
+        config.notifications.enabled = false;
+
+        let outcome = replay(&samples, &Baselines::default(), &config);
+        assert_eq!(outcome.notifications.len(), 1);
+        assert_eq!(outcome.notifications[0].title, "High CPU Usage");
+    }
+}
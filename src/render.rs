@@ -0,0 +1,722 @@
+use std::fmt::Write;
+use std::time::UNIX_EPOCH;
+use humansize::{format_size, BINARY};
+use crate::attribution::{describe_peak, format_contributors};
+use crate::history_query::format_utc;
+use crate::report::{format_bytes, format_rate, format_signed_size, HistoryPoint, MemoryTrend, MonitorReport};
+use crate::types::{Finding, TemperatureReading, TrendShape, SECURITY_CATEGORIES};
+
+/// Output format of [`crate::render_report`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportFormat {
+    /// GitHub-flavoured Markdown.
+    Markdown,
+    /// A self-contained HTML page with inline SVG charts.
+    Html,
+}
+
+type Series = (&'static str, &'static str, fn(&HistoryPoint) -> f64);
+
+const CHART_WIDTH: f64 = 640.0;
+const CHART_HEIGHT: f64 = 160.0;
+
+pub fn render_markdown(report: &MonitorReport) -> String {
+    let mut out = String::new();
+    let system = &report.system;
+    let host = system.host_name.as_deref().unwrap_or("unknown host");
+
+    let _ = writeln!(out, "# System Health Report: {host}\n");
+    let _ = writeln!(out, "Generated at {} (Unix time), {} samples.\n", unix_seconds(report), report.performance.samples);
+
+    let _ = writeln!(out, "## Health Score: {}/100\n", report.health.score);
+    for line in health_deductions(report) {
+        let _ = writeln!(out, "- {}", escape_markdown(&line));
+    }
+    let _ = writeln!(out);
+
+    let _ = writeln!(out, "## System Information\n");
+    let _ = writeln!(out, "| | |\n|---|---|");
+    for (label, value) in system_rows(report) {
+        let _ = writeln!(out, "| {label} | {} |", escape_markdown(&value));
+    }
+
+    let _ = writeln!(out, "\n## Performance Analysis\n");
+    let _ = writeln!(out, "### CPU Usage Trends\n");
+    let _ = writeln!(
+        out,
+        "| Core | Average | Peak | Slope | Volatility | Spikiness | Pattern |\n|---:|---:|---:|---:|---:|---:|---|"
+    );
+    for core in &report.performance.cpu_cores {
+        let _ = writeln!(
+            out,
+            "| {} | {:.2}% | {:.2}% | {:+.2}%/min | {:.2} | {:.1} | {} |",
+            core.core,
+            core.average_percent,
+            core.peak_percent,
+            core.shape.slope_per_min,
+            core.shape.volatility,
+            core.shape.spikiness,
+            core.shape.pattern
+        );
+    }
+
+    let _ = writeln!(out, "\n### CPU Usage Distribution\n");
+    let _ = writeln!(out, "| Core | Min | p50 | p90 | p95 | p99 | Std dev |\n|---:|---:|---:|---:|---:|---:|---:|");
+    for core in &report.performance.cpu_cores {
+        let stats = &core.stats;
+        let _ = writeln!(
+            out,
+            "| {} | {:.1}% | {:.1}% | {:.1}% | {:.1}% | {:.1}% | {:.1}% |",
+            core.core, stats.min, stats.p50, stats.p90, stats.p95, stats.p99, stats.std_dev
+        );
+    }
+
+    for (heading, memory) in memory_trends(report) {
+        let _ = writeln!(out, "\n### {heading} Usage\n");
+        let _ = writeln!(out, "- Average: {}", format_size(memory.average_bytes as u64, BINARY));
+        let _ = writeln!(out, "- Peak: {}", format_size(memory.peak_bytes as u64, BINARY));
+        let _ = writeln!(out, "- Trend: {}", memory.shape.describe(&format_signed_size(memory.shape.slope_per_min)));
+        let _ = writeln!(out, "- Distribution: {}", memory.stats.describe(format_bytes));
+    }
+
+    let network = &report.performance.network;
+    let _ = writeln!(out, "\n### Network Activity\n");
+    let _ = writeln!(
+        out,
+        "- Avg Throughput: ↓{}ps, ↑{}ps",
+        format_size(network.rx_rate as u64, BINARY),
+        format_size(network.tx_rate as u64, BINARY)
+    );
+    let _ = writeln!(
+        out,
+        "- Peak Throughput: ↓{}ps, ↑{}ps",
+        format_size(network.rx_peak as u64, BINARY),
+        format_size(network.tx_peak as u64, BINARY)
+    );
+    let (receive, transmit) = network_trends(report);
+    let _ = writeln!(out, "- Receive: {receive}");
+    let _ = writeln!(out, "- Transmit: {transmit}");
+    let _ = writeln!(out, "- Receive distribution: {}", network.rx_stats.describe(format_rate));
+    let _ = writeln!(out, "- Transmit distribution: {}", network.tx_stats.describe(format_rate));
+
+    let disk_io = &report.performance.disk_io;
+    let _ = writeln!(out, "\n### Disk I/O\n");
+    let _ = writeln!(out, "- Avg Throughput: {}", format_rate(disk_io.average));
+    let _ = writeln!(out, "- Peak Throughput: {}", format_rate(disk_io.peak));
+    let _ = writeln!(out, "- Trend: {}", rate_trend(&disk_io.shape));
+    let _ = writeln!(out, "- Distribution: {}", disk_io.stats.describe(format_rate));
+
+    if let Some(temperature) = &report.performance.temperature {
+        let _ = writeln!(out, "\n### CPU Temperature\n");
+        let _ = writeln!(out, "- Average: {:.1}°C", temperature.average_celsius);
+        let _ = writeln!(out, "- Peak: {:.1}°C", temperature.peak_celsius);
+        let _ = writeln!(
+            out,
+            "- Trend: {}",
+            temperature.shape.describe(&format!("{:+.2}°C", temperature.shape.slope_per_min))
+        );
+        let _ = writeln!(out, "- Distribution: {}", temperature.stats.describe(|value| format!("{value:.1}°C")));
+    }
+
+    if !report.performance.peaks.is_empty() {
+        let _ = writeln!(out, "\n### Peaks\n");
+        for peak in &report.performance.peaks {
+            let _ = writeln!(out, "- {} at {} UTC", escape_markdown(&describe_peak(peak)), format_utc(peak.at));
+        }
+    }
+
+    let temp_files = &report.performance.temp_files;
+    let _ = writeln!(out, "\n### Temporary Files\n");
+    let _ = writeln!(out, "- Total Size: {}", format_size(temp_files.total_size, BINARY));
+    let _ = writeln!(out, "- Total Files: {}", temp_files.total_files);
+
+    let _ = writeln!(out, "\n## Top Processes\n");
+    let _ = writeln!(out, "| Process | PID | CPU % | Memory |\n|---|---:|---:|---:|");
+    for process in &report.top_processes {
+        let _ = writeln!(
+            out,
+            "| {} | {} | {:.1} | {} |",
+            escape_markdown(&process.name),
+            process.pid,
+            process.cpu_percent,
+            format_size(process.memory_bytes, BINARY)
+        );
+    }
+
+    let _ = writeln!(out, "\n## Temperature Information\n");
+    let temperatures = temperature_rows(report);
+    if temperatures.is_empty() {
+        let _ = writeln!(out, "Temperature data unavailable on this system.");
+    } else {
+        let _ = writeln!(out, "| Sensor | °C | °F |\n|---|---:|---:|");
+        for (label, reading) in temperatures {
+            let _ = writeln!(
+                out,
+                "| {} | {:.1} | {:.1} |",
+                escape_markdown(&label),
+                reading.celsius,
+                reading.fahrenheit
+            );
+        }
+    }
+
+    let _ = writeln!(out, "\n## Security Analysis\n");
+    let sections = security_sections(report);
+    if sections.is_empty() {
+        let _ = writeln!(out, "No issues found.");
+    }
+    for (title, findings) in sections {
+        let _ = writeln!(out, "### {title}\n");
+        for finding in findings {
+            let _ = writeln!(out, "- **{}** {}", finding.severity.label(), escape_markdown(&finding.summary));
+        }
+        let _ = writeln!(out);
+    }
+
+    let _ = writeln!(out, "\n## Recommendations\n");
+    if report.recommendations.is_empty() {
+        let _ = writeln!(out, "No recommendations.");
+    }
+    for recommendation in &report.recommendations {
+        let _ = writeln!(
+            out,
+            "- **{}** {}{}",
+            recommendation.severity.label(),
+            escape_markdown(&recommendation.summary),
+            recommendation.action.as_deref().map(|a| format!(" - {}", escape_markdown(a))).unwrap_or_default()
+        );
+    }
+
+    out
+}
+
+pub fn render_html(report: &MonitorReport) -> String {
+    let mut out = String::new();
+    let host = report.system.host_name.as_deref().unwrap_or("unknown host");
+
+    let _ = writeln!(out, "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">");
+    let _ = writeln!(out, "<title>System Health Report: {}</title>", escape_html(host));
+    let _ = writeln!(out, "<style>{STYLE}</style>\n</head>\n<body>");
+    let _ = writeln!(out, "<h1>System Health Report: {}</h1>", escape_html(host));
+    let _ = writeln!(
+        out,
+        "<p class=\"meta\">Generated at {} (Unix time), {} samples.</p>",
+        unix_seconds(report),
+        report.performance.samples
+    );
+
+    let _ = writeln!(out, "<h2>Health Score: {}/100</h2>\n<ul>", report.health.score);
+    for line in health_deductions(report) {
+        let _ = writeln!(out, "<li>{}</li>", escape_html(&line));
+    }
+    let _ = writeln!(out, "</ul>");
+
+    let _ = writeln!(out, "<h2>System Information</h2>\n<table>");
+    for (label, value) in system_rows(report) {
+        let _ = writeln!(out, "<tr><th>{label}</th><td>{}</td></tr>", escape_html(&value));
+    }
+    let _ = writeln!(out, "</table>");
+
+    let _ = writeln!(out, "<h2>Performance Analysis</h2>");
+    let _ = writeln!(out, "<h3>Sampled History</h3>");
+    out.push_str(&history_chart(&report.history));
+
+    let _ = writeln!(out, "<h3>CPU Usage Trends</h3>\n<table>");
+    let _ = writeln!(
+        out,
+        "<tr><th>Core</th><th>Average</th><th>Peak</th><th>Slope</th><th>Volatility</th><th>Spikiness</th><th>Pattern</th></tr>"
+    );
+    for core in &report.performance.cpu_cores {
+        let _ = writeln!(
+            out,
+            "<tr><td>{}</td><td>{:.2}%</td><td>{:.2}%</td><td>{:+.2}%/min</td><td>{:.2}</td><td>{:.1}</td><td>{}</td></tr>",
+            core.core,
+            core.average_percent,
+            core.peak_percent,
+            core.shape.slope_per_min,
+            core.shape.volatility,
+            core.shape.spikiness,
+            core.shape.pattern
+        );
+    }
+    let _ = writeln!(out, "</table>");
+
+    let _ = writeln!(out, "<h3>CPU Usage Distribution</h3>\n<table>");
+    let _ = writeln!(
+        out,
+        "<tr><th>Core</th><th>Min</th><th>p50</th><th>p90</th><th>p95</th><th>p99</th><th>Std dev</th></tr>"
+    );
+    for core in &report.performance.cpu_cores {
+        let stats = &core.stats;
+        let _ = writeln!(
+            out,
+            "<tr><td>{}</td><td>{:.1}%</td><td>{:.1}%</td><td>{:.1}%</td><td>{:.1}%</td><td>{:.1}%</td><td>{:.1}%</td></tr>",
+            core.core, stats.min, stats.p50, stats.p90, stats.p95, stats.p99, stats.std_dev
+        );
+    }
+    let _ = writeln!(out, "</table>");
+
+    let network = &report.performance.network;
+    let temp_files = &report.performance.temp_files;
+    let _ = writeln!(out, "<h3>Memory, Swap, Network, Disk I/O, Temperature and Temporary Files</h3>\n<table>");
+    let mut rows = Vec::new();
+    for (heading, memory) in memory_trends(report) {
+        rows.push((format!("{heading} average"), format_size(memory.average_bytes as u64, BINARY)));
+        rows.push((format!("{heading} peak"), format_size(memory.peak_bytes as u64, BINARY)));
+        rows.push((
+            format!("{heading} trend"),
+            memory.shape.describe(&format_signed_size(memory.shape.slope_per_min)),
+        ));
+        rows.push((format!("{heading} distribution"), memory.stats.describe(format_bytes)));
+    }
+    let (receive, transmit) = network_trends(report);
+    rows.push(("Network ↓".to_string(), format!("{}ps", format_size(network.rx_rate as u64, BINARY))));
+    rows.push(("Network ↑".to_string(), format!("{}ps", format_size(network.tx_rate as u64, BINARY))));
+    rows.push(("Network ↓ peak".to_string(), format!("{}ps", format_size(network.rx_peak as u64, BINARY))));
+    rows.push(("Network ↑ peak".to_string(), format!("{}ps", format_size(network.tx_peak as u64, BINARY))));
+    rows.push(("Network ↓ trend".to_string(), receive));
+    rows.push(("Network ↑ trend".to_string(), transmit));
+    rows.push(("Network ↓ distribution".to_string(), network.rx_stats.describe(format_rate)));
+    rows.push(("Network ↑ distribution".to_string(), network.tx_stats.describe(format_rate)));
+    let disk_io = &report.performance.disk_io;
+    rows.push(("Disk I/O".to_string(), format_rate(disk_io.average)));
+    rows.push(("Disk I/O peak".to_string(), format_rate(disk_io.peak)));
+    rows.push(("Disk I/O trend".to_string(), rate_trend(&disk_io.shape)));
+    rows.push(("Disk I/O distribution".to_string(), disk_io.stats.describe(format_rate)));
+    if let Some(temperature) = &report.performance.temperature {
+        rows.push(("CPU temperature average".to_string(), format!("{:.1}°C", temperature.average_celsius)));
+        rows.push(("CPU temperature peak".to_string(), format!("{:.1}°C", temperature.peak_celsius)));
+        rows.push((
+            "CPU temperature trend".to_string(),
+            temperature.shape.describe(&format!("{:+.2}°C", temperature.shape.slope_per_min)),
+        ));
+        rows.push((
+            "CPU temperature distribution".to_string(),
+            temperature.stats.describe(|value| format!("{value:.1}°C")),
+        ));
+    }
+    for peak in &report.performance.peaks {
+        let mut value = format!("{} at {} UTC", peak.metric.format_value(peak.value), format_utc(peak.at));
+        if !peak.contributors.is_empty() {
+            value.push_str(&format!(", driven by {}", format_contributors(peak.metric, &peak.contributors)));
+        }
+        rows.push((format!("{} peak", peak.metric.label()), value));
+    }
+    rows.push((
+        "Temp files".to_string(),
+        format!("{} in {} files", format_size(temp_files.total_size, BINARY), temp_files.total_files),
+    ));
+    for (label, value) in rows {
+        let _ = writeln!(out, "<tr><th>{}</th><td>{}</td></tr>", escape_html(&label), escape_html(&value));
+    }
+    let _ = writeln!(out, "</table>");
+
+    let _ = writeln!(out, "<h2>Top Processes</h2>\n<table>");
+    let _ = writeln!(out, "<tr><th>Process</th><th>PID</th><th>CPU %</th><th>Memory</th></tr>");
+    for process in &report.top_processes {
+        let _ = writeln!(
+            out,
+            "<tr><td>{}</td><td>{}</td><td>{:.1}</td><td>{}</td></tr>",
+            escape_html(&process.name),
+            process.pid,
+            process.cpu_percent,
+            format_size(process.memory_bytes, BINARY)
+        );
+    }
+    let _ = writeln!(out, "</table>");
+
+    let _ = writeln!(out, "<h2>Temperature Information</h2>");
+    let temperatures = temperature_rows(report);
+    if temperatures.is_empty() {
+        let _ = writeln!(out, "<p>Temperature data unavailable on this system.</p>");
+    } else {
+        let _ = writeln!(out, "<table>\n<tr><th>Sensor</th><th>°C</th><th>°F</th></tr>");
+        for (label, reading) in temperatures {
+            let _ = writeln!(
+                out,
+                "<tr><td>{}</td><td>{:.1}</td><td>{:.1}</td></tr>",
+                escape_html(&label),
+                reading.celsius,
+                reading.fahrenheit
+            );
+        }
+        let _ = writeln!(out, "</table>");
+    }
+
+    let _ = writeln!(out, "<h2>Security Analysis</h2>");
+    let sections = security_sections(report);
+    if sections.is_empty() {
+        let _ = writeln!(out, "<p>No issues found.</p>");
+    }
+    for (title, findings) in sections {
+        let _ = writeln!(out, "<h3>{title}</h3>\n<ul>");
+        for finding in findings {
+            let _ = writeln!(out, "<li>{}</li>", finding_html(finding));
+        }
+        let _ = writeln!(out, "</ul>");
+    }
+
+    let _ = writeln!(out, "<h2>Recommendations</h2>");
+    if report.recommendations.is_empty() {
+        let _ = writeln!(out, "<p>No recommendations.</p>");
+    } else {
+        let _ = writeln!(out, "<ul>");
+        for recommendation in &report.recommendations {
+            let _ = writeln!(out, "<li>{}</li>", finding_html(recommendation));
+        }
+        let _ = writeln!(out, "</ul>");
+    }
+
+    let _ = writeln!(out, "</body>\n</html>");
+    out
+}
+
+fn history_chart(history: &[HistoryPoint]) -> String {
+    if history.len() < 2 {
+        return "<p>Not enough samples to chart.</p>\n".to_string();
+    }
+
+    let span = history.last().map(|p| p.offset_secs).unwrap_or_default().max(f64::EPSILON);
+    let series: [Series; 3] = [
+        ("CPU", "#d9534f", |p| p.cpu_percent),
+        ("Memory", "#0275d8", |p| p.memory_percent),
+        ("Swap", "#f0ad4e", |p| p.swap_percent),
+    ];
+
+    let mut svg = String::new();
+    let _ = writeln!(
+        svg,
+        "<svg class=\"chart\" viewBox=\"0 0 {CHART_WIDTH} {CHART_HEIGHT}\" role=\"img\" aria-label=\"CPU, memory and swap usage over time\">"
+    );
+    for percent in [0.0, 50.0, 100.0] {
+        let y = chart_y(percent);
+        let _ = writeln!(svg, "<line x1=\"0\" y1=\"{y:.1}\" x2=\"{CHART_WIDTH}\" y2=\"{y:.1}\" class=\"grid\"/>");
+        let _ = writeln!(svg, "<text x=\"2\" y=\"{:.1}\" class=\"axis\">{percent:.0}%</text>", (y - 2.0).max(10.0));
+    }
+    for (_, color, value) in &series {
+        let points: Vec<String> = history
+            .iter()
+            .map(|point| {
+                let x = point.offset_secs / span * CHART_WIDTH;
+                format!("{x:.1},{:.1}", chart_y(value(point)))
+            })
+            .collect();
+        let _ = writeln!(
+            svg,
+            "<polyline fill=\"none\" stroke=\"{color}\" stroke-width=\"2\" points=\"{}\"/>",
+            points.join(" ")
+        );
+    }
+    let _ = writeln!(svg, "</svg>");
+
+    let legend: Vec<String> = series
+        .iter()
+        .map(|(label, color, _)| format!("<span style=\"color:{color}\">■</span> {label}"))
+        .collect();
+    let _ = writeln!(svg, "<p class=\"legend\">{} — over {span:.0}s</p>", legend.join(" "));
+    svg
+}
+
+fn chart_y(percent: f64) -> f64 {
+    CHART_HEIGHT - percent.clamp(0.0, 100.0) / 100.0 * CHART_HEIGHT
+}
+
+fn memory_trends(report: &MonitorReport) -> [(&'static str, &MemoryTrend); 2] {
+    [("Memory", &report.performance.memory), ("Swap", &report.performance.swap)]
+}
+
+/// Receive and transmit trend descriptions.
+fn network_trends(report: &MonitorReport) -> (String, String) {
+    let network = &report.performance.network;
+    (rate_trend(&network.rx), rate_trend(&network.tx))
+}
+
+fn rate_trend(shape: &TrendShape) -> String {
+    shape.describe(&format!("{}/s", format_signed_size(shape.slope_per_min)))
+}
+
+/// `Memory pressure -8: Memory at 91% (sub-score 50/100)`, biggest first.
+fn health_deductions(report: &MonitorReport) -> Vec<String> {
+    let deductions = report.health.deductions();
+    if deductions.is_empty() {
+        return vec!["No deductions".to_string()];
+    }
+    deductions
+        .iter()
+        .map(|component| {
+            format!(
+                "{} -{:.0}: {} (sub-score {:.0}/100)",
+                component.factor.label(),
+                component.deduction,
+                component.reason,
+                component.score
+            )
+        })
+        .collect()
+}
+
+fn system_rows(report: &MonitorReport) -> Vec<(&'static str, String)> {
+    let system = &report.system;
+    let unknown = || "unknown".to_string();
+    vec![
+        ("Device Name", system.host_name.clone().unwrap_or_else(unknown)),
+        ("System", system.long_os_version.clone().unwrap_or_else(unknown)),
+        (
+            "OS",
+            format!(
+                "{} {}",
+                system.os_name.as_deref().unwrap_or_default(),
+                system.os_version.as_deref().unwrap_or_default()
+            ),
+        ),
+        ("Kernel", system.kernel_version.clone().unwrap_or_else(unknown)),
+        (
+            "CPUs",
+            format!(
+                "{} (Physical), {} (Logical)",
+                system.physical_cores.unwrap_or_default(),
+                system.logical_cores
+            ),
+        ),
+    ]
+}
+
+fn temperature_rows(report: &MonitorReport) -> Vec<(String, TemperatureReading)> {
+    let Some(temperatures) = &report.temperatures else {
+        return Vec::new();
+    };
+
+    let mut rows = Vec::new();
+    if let Some(cpu) = &temperatures.cpu_temp {
+        rows.push(("CPU".to_string(), cpu.clone()));
+    }
+    if let Some(gpu) = &temperatures.gpu_temp {
+        rows.push(("GPU".to_string(), gpu.clone()));
+    }
+    let mut components: Vec<_> = temperatures.components.iter().collect();
+    components.sort_by(|a, b| a.0.cmp(b.0));
+    rows.extend(components.into_iter().map(|(label, reading)| (label.clone(), reading.clone())));
+    rows
+}
+
+fn security_sections(report: &MonitorReport) -> Vec<(&'static str, Vec<&Finding>)> {
+    SECURITY_CATEGORIES
+        .into_iter()
+        .map(|category| (category.title(), report.security.in_category(category).collect::<Vec<_>>()))
+        .filter(|(_, findings)| !findings.is_empty())
+        .collect()
+}
+
+fn finding_html(finding: &Finding) -> String {
+    let severity = finding.severity.label();
+    let mut html = format!(
+        "<span class=\"sev-{}\">{severity}</span> {}",
+        severity.to_lowercase(),
+        escape_html(&finding.summary)
+    );
+    if let Some(action) = &finding.action {
+        let _ = write!(html, " &ndash; {}", escape_html(action));
+    }
+    html
+}
+
+fn unix_seconds(report: &MonitorReport) -> u64 {
+    report
+        .generated_at
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+fn escape_markdown(value: &str) -> String {
+    value.replace('|', "\\|")
+}
+
+fn escape_html(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+const STYLE: &str = "body{font-family:-apple-system,Segoe UI,Helvetica,Arial,sans-serif;max-width:960px;margin:2em auto;padding:0 1em;color:#222}\
+table{border-collapse:collapse;margin:.5em 0}th,td{border:1px solid #ddd;padding:4px 8px;text-align:left}\
+th{background:#f5f5f5}.meta,.legend{color:#666}.chart{width:100%;height:auto;border:1px solid #ddd}\
+.chart .grid{stroke:#eee}.chart .axis{font-size:10px;fill:#999}\
+.sev-critical{color:#c9302c;font-weight:bold}.sev-warning{color:#b8860b;font-weight:bold}.sev-info{color:#31708f}";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::attribution::{Contributor, Peak};
+    use crate::health_score::{HealthComponent, HealthFactor, HealthScore};
+    use crate::history_query::Metric;
+    use crate::report::*;
+    use crate::types::*;
+    use std::collections::HashMap;
+    use std::time::SystemTime;
+
+    fn sample_report() -> MonitorReport {
+        MonitorReport {
+            schema_version: REPORT_SCHEMA_VERSION,
+            generated_at: SystemTime::now(),
+            health: HealthScore {
+                score: 92,
+                components: vec![HealthComponent {
+                    factor: HealthFactor::Cpu,
+                    weight: 20.0,
+                    score: 60.0,
+                    deduction: 8.0,
+                    reason: "CPU averaged 76%".to_string(),
+                }],
+            },
+            system: SystemInfo {
+                host_name: Some("build<01>".to_string()),
+                os_name: Some("Linux".to_string()),
+                os_version: Some("6.1".to_string()),
+                long_os_version: None,
+                kernel_version: None,
+                physical_cores: Some(4),
+                logical_cores: 8,
+            },
+            performance: PerformanceReport {
+                samples: 3,
+                cpu_cores: vec![CoreTrend {
+                    core: 0,
+                    average_percent: 12.5,
+                    peak_percent: 40.0,
+                    stats: SeriesStats { min: 0.0, p50: 10.0, p90: 30.0, p95: 35.0, p99: 40.0, std_dev: 9.5 },
+                    shape: TrendShape {
+                        slope_per_min: 0.5,
+                        direction: TrendDirection::Rising,
+                        volatility: 0.8,
+                        spikiness: 3.2,
+                        pattern: "rising, volatile, spiky".to_string(),
+                    },
+                }],
+                memory: MemoryTrend {
+                    average_bytes: 1024.0,
+                    peak_bytes: 2048.0,
+                    stats: SeriesStats::default(),
+                    shape: TrendShape::default(),
+                },
+                swap: MemoryTrend {
+                    average_bytes: 0.0,
+                    peak_bytes: 0.0,
+                    stats: SeriesStats::default(),
+                    shape: TrendShape::default(),
+                },
+                network: NetworkTrend {
+                    rx_rate: 0.0,
+                    tx_rate: 0.0,
+                    rx_peak: 0.0,
+                    tx_peak: 0.0,
+                    rx: TrendShape::default(),
+                    tx: TrendShape::default(),
+                    rx_stats: SeriesStats::default(),
+                    tx_stats: SeriesStats::default(),
+                },
+                disk_io: UsageTrend::default(),
+                temperature: None,
+                peaks: vec![Peak {
+                    metric: Metric::Cpu,
+                    at: SystemTime::UNIX_EPOCH,
+                    value: 99.0,
+                    contributors: vec![Contributor { pid: 99, name: "stress".to_string(), value: 390.0 }],
+                }],
+                temp_files: TempFilesSummary { total_size: 0, total_files: 0 },
+            },
+            temperatures: Some(TemperatureMetrics {
+                cpu_temp: Some(TemperatureReading { celsius: 55.0, fahrenheit: 131.0 }),
+                gpu_temp: None,
+                components: HashMap::new(),
+            }),
+            security: SecurityAnalysis {
+                findings: vec![Finding {
+                    id: "process_cpu:99".to_string(),
+                    category: FindingCategory::ResourceUsage,
+                    severity: Severity::Warning,
+                    subject: Subject::Process { pid: 99, name: "stress".to_string() },
+                    summary: "stress (PID: 99) using 99.0% CPU".to_string(),
+                    value: Some(99.0),
+                    threshold: Some(90.0),
+                    action: None,
+                    anomaly: None,
+                }],
+            },
+            recommendations: vec![Finding {
+                id: "cpu_cores".to_string(),
+                category: FindingCategory::Cpu,
+                severity: Severity::Warning,
+                subject: Subject::Cores { cores: vec![0] },
+                summary: "High CPU usage on cores 0".to_string(),
+                value: Some(99.0),
+                threshold: Some(90.0),
+                action: Some("Check for CPU-intensive processes".to_string()),
+                anomaly: None,
+            }],
+            top_processes: vec![ProcessSummary {
+                name: "stress".to_string(),
+                pid: 99,
+                cpu_percent: 99.0,
+                memory_bytes: 1 << 30,
+            }],
+            history: vec![
+                HistoryPoint { offset_secs: 0.0, cpu_percent: 10.0, memory_percent: 50.0, swap_percent: 0.0 },
+                HistoryPoint { offset_secs: 5.0, cpu_percent: 90.0, memory_percent: 55.0, swap_percent: 0.0 },
+            ],
+        }
+    }
+
+    #[test]
+    fn markdown_covers_all_sections() {
+        let markdown = render_markdown(&sample_report());
+
+        assert!(markdown.contains("## Health Score: 92/100\n\n- CPU saturation -8: CPU averaged 76% (sub-score 60/100)\n"));
+        assert!(markdown.find("## Health Score").unwrap() < markdown.find("## System Information").unwrap());
+        for heading in [
+            "## System Information",
+            "## Performance Analysis",
+            "## Top Processes",
+            "## Temperature Information",
+            "## Security Analysis",
+            "## Recommendations",
+        ] {
+            assert!(markdown.contains(heading), "missing {heading}");
+        }
+        assert!(markdown.contains("| 0 | 12.50% | 40.00% | +0.50%/min | 0.80 | 3.2 | rising, volatile, spiky |"));
+        assert!(markdown.contains("### Swap Usage"));
+        assert!(markdown.contains("- CPU usage peaked at 99.0%: stress (PID 99) 390.0% at "));
+        assert!(markdown.contains("| 0 | 0.0% | 10.0% | 30.0% | 35.0% | 40.0% | 9.5% |"));
+        assert!(markdown.contains("| CPU | 55.0 | 131.0 |"));
+        assert!(markdown.contains("- **Warning** stress (PID: 99) using 99.0% CPU"));
+        assert!(markdown.contains("- **Warning** High CPU usage on cores 0 - Check for CPU-intensive processes"));
+    }
+
+    #[test]
+    fn html_is_self_contained_with_chart() {
+        let html = render_html(&sample_report());
+
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("<style>"));
+        assert!(!html.contains("<script"));
+        assert!(!html.contains("http://") && !html.contains("https://"));
+        assert_eq!(html.matches("<polyline").count(), 3);
+        assert!(html.contains("build&lt;01&gt;"));
+        assert!(html.contains("<h2>Health Score: 92/100</h2>\n<ul>\n<li>CPU saturation -8: CPU averaged 76% (sub-score 60/100)</li>"));
+        assert!(html.contains("<li><span class=\"sev-warning\">Warning</span> High CPU usage on cores 0"));
+    }
+
+    #[test]
+    fn chart_needs_two_points() {
+        assert!(history_chart(&[]).contains("Not enough samples"));
+    }
+
+    #[test]
+    fn chart_y_is_clamped() {
+        assert_eq!(chart_y(100.0), 0.0);
+        assert_eq!(chart_y(0.0), CHART_HEIGHT);
+        assert_eq!(chart_y(150.0), 0.0);
+    }
+}
@@ -0,0 +1,432 @@
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use humansize::{format_size, BINARY};
+use serde::{Deserialize, Serialize};
+use sysinfo::{PidExt, System, SystemExt};
+use crate::analysis::{
+    analyze_cpu_trend, analyze_disk_io_trend, analyze_memory_trend, analyze_network_trend, analyze_swap_trend,
+    analyze_temperature_trend,
+};
+use crate::attribution::{find_peaks, Peak};
+use crate::config::Config;
+use crate::health_score::HealthScore;
+use crate::types::{
+    Finding, NetworkTrend, SecurityAnalysis, SeriesStats, SystemMetrics, TempFileInfo, TemperatureMetrics, TrendShape,
+    UsageTrend,
+};
+
+/// Version of the JSON documents described in docs/json-output.md.
+pub const REPORT_SCHEMA_VERSION: u32 = 3;
+
+/// The full report document; docs/json-output.md describes its JSON form.
+#[derive(Serialize)]
+pub struct MonitorReport {
+    /// [`REPORT_SCHEMA_VERSION`] at the time of writing.
+    pub schema_version: u32,
+    /// When the report was built.
+    pub generated_at: SystemTime,
+    /// The health score and its components.
+    pub health: HealthScore,
+    /// Host name, OS and core counts.
+    pub system: SystemInfo,
+    /// Trends over the sampled history.
+    pub performance: PerformanceReport,
+    /// Sensor readings from the latest sample, if any.
+    pub temperatures: Option<TemperatureMetrics>,
+    /// Findings from the security and anomaly checks.
+    pub security: SecurityAnalysis,
+    /// Findings about resource usage, with suggested actions.
+    pub recommendations: Vec<Finding>,
+    /// The busiest processes in the latest sample.
+    pub top_processes: Vec<ProcessSummary>,
+    /// CPU, memory and swap per sample, for charts.
+    pub history: Vec<HistoryPoint>,
+}
+
+/// One of the busiest processes in the latest sample.
+#[derive(Serialize)]
+pub struct ProcessSummary {
+    /// Process name.
+    pub name: String,
+    /// Process ID.
+    pub pid: u32,
+    /// CPU usage, in percent of one core.
+    pub cpu_percent: f32,
+    /// Resident memory.
+    pub memory_bytes: u64,
+}
+
+/// CPU, memory and swap usage of one sample, in percent.
+#[derive(Serialize)]
+pub struct HistoryPoint {
+    /// Seconds since the first sample.
+    pub offset_secs: f64,
+    /// Average across cores.
+    pub cpu_percent: f64,
+    /// Memory in use.
+    pub memory_percent: f64,
+    /// Swap in use.
+    pub swap_percent: f64,
+}
+
+/// Host name, OS and core counts.
+#[derive(Serialize, Deserialize)]
+pub struct SystemInfo {
+    /// Host name.
+    pub host_name: Option<String>,
+    /// OS name, e.g. `Ubuntu`.
+    pub os_name: Option<String>,
+    /// OS version, e.g. `22.04`.
+    pub os_version: Option<String>,
+    /// OS name and version together.
+    pub long_os_version: Option<String>,
+    /// Kernel version.
+    pub kernel_version: Option<String>,
+    /// Physical cores, when the OS reports them.
+    pub physical_cores: Option<usize>,
+    /// Logical cores.
+    pub logical_cores: usize,
+}
+
+/// Trends over the sampled history.
+#[derive(Serialize)]
+pub struct PerformanceReport {
+    /// Samples the trends cover.
+    pub samples: usize,
+    /// Per core.
+    pub cpu_cores: Vec<CoreTrend>,
+    /// Memory in use.
+    pub memory: MemoryTrend,
+    /// Swap in use.
+    pub swap: MemoryTrend,
+    /// Receive and transmit rates.
+    pub network: NetworkTrend,
+    /// Bytes read and written per second by all processes.
+    pub disk_io: UsageTrend,
+    /// CPU temperature, when a sensor reports it.
+    pub temperature: Option<TemperatureTrend>,
+    /// The CPU, memory and disk I/O peaks with the processes behind them.
+    pub peaks: Vec<Peak>,
+    /// Temporary files found in the latest sample.
+    pub temp_files: TempFilesSummary,
+}
+
+/// Usage of one core over the run, in percent.
+#[derive(Serialize)]
+pub struct CoreTrend {
+    /// Core index.
+    pub core: usize,
+    /// Mean usage.
+    pub average_percent: f64,
+    /// Highest usage.
+    pub peak_percent: f64,
+    /// Spread of the series.
+    #[serde(flatten)]
+    pub stats: SeriesStats,
+    /// Slope and direction of the series.
+    #[serde(flatten)]
+    pub shape: TrendShape,
+}
+
+/// Memory or swap in use over the run, in bytes.
+#[derive(Serialize)]
+pub struct MemoryTrend {
+    /// Mean usage.
+    pub average_bytes: f64,
+    /// Highest usage.
+    pub peak_bytes: f64,
+    /// Spread of the series.
+    #[serde(flatten)]
+    pub stats: SeriesStats,
+    /// Slope and direction of the series.
+    #[serde(flatten)]
+    pub shape: TrendShape,
+}
+
+impl From<UsageTrend> for MemoryTrend {
+    fn from(trend: UsageTrend) -> Self {
+        Self { average_bytes: trend.average, peak_bytes: trend.peak, stats: trend.stats, shape: trend.shape }
+    }
+}
+
+/// CPU temperature over the run, in °C.
+#[derive(Serialize)]
+pub struct TemperatureTrend {
+    /// Mean temperature.
+    pub average_celsius: f64,
+    /// Highest temperature.
+    pub peak_celsius: f64,
+    /// Spread of the series.
+    #[serde(flatten)]
+    pub stats: SeriesStats,
+    /// Slope and direction of the series.
+    #[serde(flatten)]
+    pub shape: TrendShape,
+}
+
+/// Size and count of the temporary files found.
+#[derive(Serialize)]
+pub struct TempFilesSummary {
+    /// Bytes across all files.
+    pub total_size: u64,
+    /// Number of files.
+    pub total_files: usize,
+}
+
+#[derive(Serialize)]
+pub struct TempFilesReport<'a> {
+    pub schema_version: u32,
+    pub generated_at: SystemTime,
+    pub total_size: u64,
+    pub total_files: usize,
+    pub files: &'a [TempFileInfo],
+}
+
+#[derive(Serialize)]
+pub struct ConfigReport<'a> {
+    pub schema_version: u32,
+    pub config_path: Option<PathBuf>,
+    pub config: &'a Config,
+}
+
+impl SystemInfo {
+    /// Reads the host details from `sys`.
+    pub fn collect(sys: &System) -> Self {
+        Self {
+            host_name: sys.host_name(),
+            os_name: sys.name(),
+            os_version: sys.os_version(),
+            long_os_version: sys.long_os_version(),
+            kernel_version: sys.kernel_version(),
+            physical_cores: sys.physical_core_count(),
+            logical_cores: sys.cpus().len(),
+        }
+    }
+}
+
+pub fn build_performance_report(metrics_history: &[SystemMetrics]) -> PerformanceReport {
+    let cpu_cores = analyze_cpu_trend(metrics_history)
+        .into_iter()
+        .enumerate()
+        .map(|(core, trend)| CoreTrend {
+            core,
+            average_percent: trend.average,
+            peak_percent: trend.peak,
+            stats: trend.stats,
+            shape: trend.shape,
+        })
+        .collect();
+
+    let latest = metrics_history.last();
+
+    PerformanceReport {
+        samples: metrics_history.len(),
+        cpu_cores,
+        memory: analyze_memory_trend(metrics_history).into(),
+        swap: analyze_swap_trend(metrics_history).into(),
+        network: analyze_network_trend(metrics_history),
+        disk_io: analyze_disk_io_trend(metrics_history),
+        temperature: analyze_temperature_trend(metrics_history).map(|trend| TemperatureTrend {
+            average_celsius: trend.average,
+            peak_celsius: trend.peak,
+            stats: trend.stats,
+            shape: trend.shape,
+        }),
+        peaks: find_peaks(metrics_history),
+        temp_files: TempFilesSummary {
+            total_size: latest.map(|m| m.temp_files.total_size).unwrap_or_default(),
+            total_files: latest.map(|m| m.temp_files.files.len()).unwrap_or_default(),
+        },
+    }
+}
+
+const TOP_PROCESS_COUNT: usize = 10;
+
+pub fn build_top_processes(metrics: &SystemMetrics) -> Vec<ProcessSummary> {
+    let mut processes: Vec<ProcessSummary> = metrics
+        .process_metrics
+        .iter()
+        .map(|process| ProcessSummary {
+            name: process.name.clone(),
+            pid: process.pid.as_u32(),
+            cpu_percent: process.cpu_usage,
+            memory_bytes: process.memory_usage,
+        })
+        .collect();
+    processes.sort_by_key(|process| std::cmp::Reverse(process.memory_bytes));
+    processes.truncate(TOP_PROCESS_COUNT);
+    processes
+}
+
+pub fn build_history(metrics_history: &[SystemMetrics]) -> Vec<HistoryPoint> {
+    let Some(first) = metrics_history.first() else {
+        return Vec::new();
+    };
+
+    metrics_history
+        .iter()
+        .map(|metrics| HistoryPoint {
+            offset_secs: metrics.timestamp.duration_since(first.timestamp).as_secs_f64(),
+            cpu_percent: f64::from(
+                metrics.cpu_usage.iter().sum::<f32>() / metrics.cpu_usage.len().max(1) as f32,
+            ),
+            memory_percent: percent(metrics.memory_usage, metrics.memory_total),
+            swap_percent: percent(metrics.swap_usage, metrics.swap_total),
+        })
+        .collect()
+}
+
+fn percent(used: u64, total: u64) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        used as f64 / total as f64 * 100.0
+    }
+}
+
+pub fn format_bytes(bytes: f64) -> String {
+    format_size(bytes.max(0.0) as u64, BINARY)
+}
+
+pub fn format_rate(bytes_per_sec: f64) -> String {
+    format!("{}/s", format_bytes(bytes_per_sec))
+}
+
+/// A change in bytes with its sign, for byte slopes such as `+1.5 MiB`.
+pub fn format_signed_size(bytes: f64) -> String {
+    let sign = if bytes < 0.0 { '-' } else { '+' };
+    format!("{sign}{}", format_size(bytes.abs() as u64, BINARY))
+}
+
+pub fn build_monitor_report(
+    sys: &System,
+    metrics_history: &[SystemMetrics],
+    security: SecurityAnalysis,
+    recommendations: Vec<Finding>,
+    health: HealthScore,
+) -> MonitorReport {
+    MonitorReport {
+        schema_version: REPORT_SCHEMA_VERSION,
+        generated_at: SystemTime::now(),
+        health,
+        system: SystemInfo::collect(sys),
+        performance: build_performance_report(metrics_history),
+        temperatures: metrics_history.last().map(|m| m.temperature.clone()),
+        security,
+        recommendations,
+        top_processes: metrics_history.last().map(build_top_processes).unwrap_or_default(),
+        history: build_history(metrics_history),
+    }
+}
+
+pub fn build_temp_files_report(metrics: &SystemMetrics) -> TempFilesReport<'_> {
+    TempFilesReport {
+        schema_version: REPORT_SCHEMA_VERSION,
+        generated_at: SystemTime::now(),
+        total_size: metrics.temp_files.total_size,
+        total_files: metrics.temp_files.files.len(),
+        files: &metrics.temp_files.files,
+    }
+}
+
+/// `config_path` is the file `config` was loaded from (see [`crate::config::config_path`]).
+pub fn build_config_report<'a>(config: &'a Config, config_path: Option<&Path>) -> ConfigReport<'a> {
+    ConfigReport {
+        schema_version: REPORT_SCHEMA_VERSION,
+        config_path: config_path.map(Path::to_path_buf),
+        config,
+    }
+}
+
+pub fn to_json<T: Serialize>(document: &T) -> String {
+    serde_json::to_string_pretty(document).expect("report types always serialize")
+}
+
+pub fn write_output(contents: &str, output: Option<&Path>) -> io::Result<()> {
+    match output {
+        Some(path) => std::fs::write(path, contents),
+        None => {
+            let mut stdout = io::stdout().lock();
+            stdout.write_all(contents.as_bytes())?;
+            stdout.write_all(b"\n")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::*;
+    use std::time::{Duration, Instant};
+
+    fn make_metrics(cpu: f32, mem_used: u64, offset_secs: u64, start: Instant) -> SystemMetrics {
+        SystemMetrics {
+            timestamp: start + Duration::from_secs(offset_secs),
+            cpu_usage: vec![cpu, cpu / 2.0],
+            memory_usage: mem_used,
+            memory_total: 100,
+            network_rx: 1000,
+            network_tx: 500,
+            temp_files: TempFileMetrics { total_size: 42, files: Vec::new() },
+            ..SystemMetrics::default()
+        }
+    }
+
+    #[test]
+    fn performance_report_serializes_expected_fields() {
+        let start = Instant::now();
+        let history = vec![make_metrics(20.0, 40, 0, start), make_metrics(60.0, 60, 5, start)];
+
+        let report = build_performance_report(&history);
+        let value: serde_json::Value = serde_json::from_str(&to_json(&report)).unwrap();
+
+        assert_eq!(value["samples"], 2);
+        assert_eq!(value["cpu_cores"].as_array().unwrap().len(), 2);
+        assert_eq!(value["cpu_cores"][0]["average_percent"], 40.0);
+        assert_eq!(value["cpu_cores"][0]["peak_percent"], 60.0);
+        assert_eq!(value["memory"]["peak_bytes"], 60.0);
+        assert_eq!(value["network"]["rx_rate"], 400.0);
+        assert_eq!(value["cpu_cores"][0]["direction"], "rising");
+        assert!(value["memory"]["pattern"].is_string());
+        assert_eq!(value["swap"]["peak_bytes"], 0.0);
+        assert!(value["temperature"].is_null());
+        assert_eq!(value["temp_files"]["total_size"], 42);
+    }
+
+    #[test]
+    fn history_points_use_offsets_and_percentages() {
+        let start = Instant::now();
+        let mut later = make_metrics(60.0, 75, 10, start);
+        later.swap_total = 200;
+        later.swap_usage = 50;
+        let history = build_history(&[make_metrics(20.0, 40, 0, start), later]);
+
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[1].offset_secs, 10.0);
+        assert_eq!(history[1].cpu_percent, 45.0);
+        assert_eq!(history[1].memory_percent, 75.0);
+        assert_eq!(history[1].swap_percent, 25.0);
+        assert_eq!(history[0].swap_percent, 0.0);
+    }
+
+    #[test]
+    fn config_report_embeds_full_config() {
+        let config = Config::default();
+        let path = Path::new("/tmp/alt.toml");
+        let value: serde_json::Value = serde_json::from_str(&to_json(&build_config_report(&config, Some(path)))).unwrap();
+
+        assert_eq!(value["schema_version"], REPORT_SCHEMA_VERSION);
+        assert_eq!(value["config_path"], "/tmp/alt.toml");
+        assert_eq!(value["config"]["thresholds"]["cpu_percent"], 90.0);
+        assert_eq!(value["config"]["daemon"]["check_interval_secs"], 60);
+    }
+
+    #[test]
+    fn write_output_creates_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("report.json");
+        write_output("{}", Some(&path)).unwrap();
+        assert_eq!(std::fs::read_to_string(path).unwrap(), "{}");
+    }
+}
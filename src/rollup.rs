@@ -0,0 +1,324 @@
+use std::collections::BTreeMap;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use serde::{Deserialize, Serialize};
+use crate::analysis::percentile;
+use crate::types::{DiskMetrics, SystemMetrics, TemperatureMetrics, TemperatureReading};
+
+/// Width of a rollup window.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Resolution {
+    /// One-minute windows.
+    #[serde(rename = "1m")]
+    Minute,
+    /// One-hour windows.
+    #[serde(rename = "1h")]
+    Hour,
+    /// One-day windows.
+    #[serde(rename = "1d")]
+    Day,
+}
+
+impl Resolution {
+    /// Every resolution, finest first.
+    pub const ALL: [Resolution; 3] = [Resolution::Minute, Resolution::Hour, Resolution::Day];
+
+    /// Length of one window.
+    pub fn duration(self) -> Duration {
+        match self {
+            Resolution::Minute => Duration::from_secs(60),
+            Resolution::Hour => Duration::from_secs(3600),
+            Resolution::Day => Duration::from_secs(86_400),
+        }
+    }
+
+    /// `1m`, `1h` or `1d`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Resolution::Minute => "1m",
+            Resolution::Hour => "1h",
+            Resolution::Day => "1d",
+        }
+    }
+
+    /// Start of the window containing `time`. Windows are aligned to the Unix epoch (UTC).
+    pub fn window_start(self, time: SystemTime) -> SystemTime {
+        let secs = time.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+        let step = self.duration().as_secs();
+        UNIX_EPOCH + Duration::from_secs(secs - secs % step)
+    }
+}
+
+/// Summary of one series within a window.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Stat {
+    /// Lowest value.
+    pub min: f64,
+    /// Mean value.
+    pub avg: f64,
+    /// Highest value.
+    pub max: f64,
+    /// 95th percentile.
+    pub p95: f64,
+}
+
+impl Stat {
+    /// Summarizes `values`; `None` when empty.
+    pub fn from_values(values: &[f64]) -> Option<Self> {
+        if values.is_empty() {
+            return None;
+        }
+        let mut sorted = values.to_vec();
+        sorted.sort_by(f64::total_cmp);
+        Some(Self {
+            min: sorted[0],
+            avg: sorted.iter().sum::<f64>() / sorted.len() as f64,
+            max: sorted[sorted.len() - 1],
+            p95: percentile(&sorted, 95.0),
+        })
+    }
+}
+
+/// Usage of one mount within a window.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DiskRollup {
+    /// Capacity in bytes.
+    pub total: u64,
+    /// Bytes used.
+    pub used: Stat,
+}
+
+/// Aggregate of the samples in one window. Network and disk I/O values are rates in bytes
+/// per second.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Rollup {
+    /// Window width.
+    pub resolution: Resolution,
+    /// Start of the window.
+    pub start: SystemTime,
+    /// Samples aggregated into the window.
+    pub samples: usize,
+    /// Average across cores.
+    pub cpu_total: Stat,
+    /// Per core, in percent.
+    pub cpu: Vec<Stat>,
+    /// Memory in use, in bytes.
+    pub memory_used: Stat,
+    /// Total memory in bytes.
+    pub memory_total: u64,
+    /// Swap in use, in bytes.
+    pub swap_used: Stat,
+    /// Total swap in bytes.
+    pub swap_total: u64,
+    /// Receive rate; `None` without two samples to compare.
+    pub network_rx: Option<Stat>,
+    /// Transmit rate; `None` without two samples to compare.
+    pub network_tx: Option<Stat>,
+    /// Bytes read and written per second by all processes.
+    #[serde(default)]
+    pub disk_io: Option<Stat>,
+    /// Per mount point.
+    pub disks: BTreeMap<String, DiskRollup>,
+    /// CPU temperature in °C, when a sensor reports it.
+    pub cpu_temp: Option<Stat>,
+}
+
+impl Rollup {
+    /// A sample holding each series' average, so rollups can feed the regular analysis functions.
+    pub fn to_metrics(&self) -> SystemMetrics {
+        let window_secs = self.resolution.duration().as_secs_f64();
+        let bytes_in_window = |rate: &Option<Stat>| rate.map(|stat| (stat.avg * window_secs) as u64).unwrap_or_default();
+
+        SystemMetrics {
+            recorded_at: self.start,
+            cpu_usage: self.cpu.iter().map(|stat| stat.avg as f32).collect(),
+            memory_usage: self.memory_used.avg as u64,
+            memory_total: self.memory_total,
+            swap_usage: self.swap_used.avg as u64,
+            swap_total: self.swap_total,
+            network_rx: bytes_in_window(&self.network_rx),
+            network_tx: bytes_in_window(&self.network_tx),
+            disk_io: bytes_in_window(&self.disk_io),
+            disk_usage: self
+                .disks
+                .iter()
+                .map(|(mount, disk)| {
+                    (mount.clone(), DiskMetrics {
+                        total: disk.total,
+                        used: disk.used.avg as u64,
+                        read_rate: 0.0,
+                        write_rate: 0.0,
+                    })
+                })
+                .collect(),
+            temperature: TemperatureMetrics {
+                cpu_temp: self.cpu_temp.map(|stat| TemperatureReading {
+                    celsius: stat.avg as f32,
+                    fahrenheit: (stat.avg * 9.0 / 5.0 + 32.0) as f32,
+                }),
+                ..TemperatureMetrics::default()
+            },
+            ..SystemMetrics::default()
+        }
+    }
+}
+
+/// Groups samples (sorted by `recorded_at`) into windows of `resolution`.
+pub fn build_rollups(samples: &[SystemMetrics], resolution: Resolution) -> Vec<Rollup> {
+    let mut rollups = Vec::new();
+    let mut start = 0;
+    while start < samples.len() {
+        let window = resolution.window_start(samples[start].recorded_at);
+        let end = samples[start..]
+            .iter()
+            .position(|sample| resolution.window_start(sample.recorded_at) != window)
+            .map_or(samples.len(), |offset| start + offset);
+
+        let previous = start.checked_sub(1).map(|index| &samples[index]);
+        rollups.push(aggregate(resolution, window, previous, &samples[start..end]));
+        start = end;
+    }
+    rollups
+}
+
+fn aggregate(resolution: Resolution, start: SystemTime, previous: Option<&SystemMetrics>, window: &[SystemMetrics]) -> Rollup {
+    let last = window.last().expect("windows are never empty");
+    let series = |value: fn(&SystemMetrics) -> f64| window.iter().map(value).collect::<Vec<_>>();
+    let stat = |values: Vec<f64>| Stat::from_values(&values).expect("windows are never empty");
+
+    let cores = window.iter().map(|sample| sample.cpu_usage.len()).max().unwrap_or_default();
+    let cpu = (0..cores)
+        .filter_map(|core| {
+            let values: Vec<f64> = window
+                .iter()
+                .filter_map(|sample| sample.cpu_usage.get(core).map(|&usage| f64::from(usage)))
+                .collect();
+            Stat::from_values(&values)
+        })
+        .collect();
+
+    let mut rx_rates = Vec::new();
+    let mut tx_rates = Vec::new();
+    let mut io_rates = Vec::new();
+    let mut before = previous;
+    for sample in window {
+        if let Some(elapsed) = before
+            .and_then(|before| sample.recorded_at.duration_since(before.recorded_at).ok())
+            .filter(|elapsed| !elapsed.is_zero())
+        {
+            rx_rates.push(sample.network_rx as f64 / elapsed.as_secs_f64());
+            tx_rates.push(sample.network_tx as f64 / elapsed.as_secs_f64());
+            io_rates.push(sample.disk_io as f64 / elapsed.as_secs_f64());
+        }
+        before = Some(sample);
+    }
+
+    let disks = last
+        .disk_usage
+        .iter()
+        .map(|(mount, disk)| {
+            let used: Vec<f64> = window
+                .iter()
+                .filter_map(|sample| sample.disk_usage.get(mount).map(|d| d.used as f64))
+                .collect();
+            (mount.clone(), DiskRollup { total: disk.total, used: stat(used) })
+        })
+        .collect();
+
+    let temps: Vec<f64> = window
+        .iter()
+        .filter_map(|sample| sample.temperature.cpu_temp.as_ref().map(|t| f64::from(t.celsius)))
+        .collect();
+
+    Rollup {
+        resolution,
+        start,
+        samples: window.len(),
+        cpu_total: stat(series(|sample| {
+            sample.cpu_usage.iter().map(|&usage| f64::from(usage)).sum::<f64>() / sample.cpu_usage.len().max(1) as f64
+        })),
+        cpu,
+        memory_used: stat(series(|sample| sample.memory_usage as f64)),
+        memory_total: last.memory_total,
+        swap_used: stat(series(|sample| sample.swap_usage as f64)),
+        swap_total: last.swap_total,
+        network_rx: Stat::from_values(&rx_rates),
+        network_tx: Stat::from_values(&tx_rates),
+        disk_io: Stat::from_values(&io_rates),
+        disks,
+        cpu_temp: Stat::from_values(&temps),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::analyze_cpu_trend;
+    use std::collections::HashMap;
+
+    fn at(secs: u64) -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(1_700_000_000 - 1_700_000_000 % 86_400 + secs)
+    }
+
+    fn sample(secs: u64, cpu: f32, network_rx: u64) -> SystemMetrics {
+        SystemMetrics {
+            recorded_at: at(secs),
+            memory_usage: (cpu * 10.0) as u64,
+            memory_total: 1000,
+            network_rx,
+            disk_usage: HashMap::from([("/".to_string(), DiskMetrics {
+                total: 100,
+                used: 40,
+                read_rate: 0.0,
+                write_rate: 0.0,
+            })]),
+            ..SystemMetrics::sample(0, cpu)
+        }
+    }
+
+    #[test]
+    fn stats_include_p95() {
+        let values: Vec<f64> = (1..=100).map(f64::from).collect();
+        let stat = Stat::from_values(&values).unwrap();
+
+        assert_eq!(stat, Stat { min: 1.0, avg: 50.5, max: 100.0, p95: 95.0 });
+        assert_eq!(Stat::from_values(&[]), None);
+    }
+
+    #[test]
+    fn samples_are_grouped_into_aligned_windows() {
+        let samples: Vec<_> = (0..5).map(|i| sample(i * 30, (i * 10) as f32, 600)).collect();
+        let rollups = build_rollups(&samples, Resolution::Minute);
+
+        assert_eq!(rollups.len(), 3);
+        assert_eq!(rollups[0].start, at(0));
+        assert_eq!(rollups[0].samples, 2);
+        assert_eq!(rollups[0].cpu[0].max, 10.0);
+        assert_eq!(rollups[1].cpu[0].avg, 25.0);
+        assert_eq!(rollups[1].network_rx.unwrap().avg, 20.0);
+        assert_eq!(rollups[0].network_rx.unwrap().avg, 20.0);
+        assert_eq!(rollups[2].disks["/"].used.avg, 40.0);
+    }
+
+    #[test]
+    fn first_sample_has_no_network_rate() {
+        let rollups = build_rollups(&[sample(0, 0.0, 500)], Resolution::Hour);
+        assert_eq!(rollups[0].network_rx, None);
+    }
+
+    #[test]
+    fn materialized_rollups_feed_trend_analysis() {
+        let samples: Vec<_> = (0..120).map(|i| sample(i * 60, if i < 60 { 20.0 } else { 60.0 }, 0)).collect();
+        let metrics: Vec<_> = build_rollups(&samples, Resolution::Hour).iter().map(Rollup::to_metrics).collect();
+
+        assert_eq!(metrics.len(), 2);
+        let trend = analyze_cpu_trend(&metrics);
+        assert_eq!(trend[0].average, 40.0);
+        assert_eq!(trend[0].peak, 60.0);
+    }
+
+    #[test]
+    fn windows_align_to_resolution() {
+        assert_eq!(Resolution::Hour.window_start(at(3600 + 59)), at(3600));
+        assert_eq!(Resolution::Day.window_start(at(86_400 * 2 + 5)), at(86_400 * 2));
+    }
+}
@@ -1,71 +1,107 @@
-use sysinfo::{System, SystemExt, ProcessExt, NetworkExt, NetworksExt, ProcessStatus};
-use crate::types::{SystemMetrics, SecurityAnalysis};
+use sysinfo::PidExt;
+use crate::types::{sort_findings, Finding, FindingCategory, SecurityAnalysis, Severity, Subject, SystemMetrics};
+use crate::baseline::{baseline_findings, Baselines};
 use crate::config::Config;
+use crate::cores::core_findings;
+use crate::spawns::spawn_findings;
 use humansize::{format_size, BINARY};
 
-pub fn perform_security_analysis(sys: &System, metrics_history: &[SystemMetrics], config: &Config) -> SecurityAnalysis {
-    let mut analysis = SecurityAnalysis {
-        unusual_network_activity: Vec::new(),
-        high_resource_usage: Vec::new(),
-        zombie_processes: Vec::new(),
-        swap_pressure: Vec::new(),
+/// Findings for the latest sample in `metrics_history`, with its metrics scored against
+/// the learned `baselines`. Works from recorded samples alone, so replayed sessions are
+/// analyzed the same way.
+pub fn perform_security_analysis(
+    metrics_history: &[SystemMetrics],
+    baselines: &Baselines,
+    config: &Config,
+) -> SecurityAnalysis {
+    let mut findings = Vec::new();
+    let Some(latest) = metrics_history.last() else {
+        return SecurityAnalysis::default();
     };
 
-    let memory_threshold_bytes = (sys.total_memory() as f64 * config.thresholds.memory_percent / 100.0) as u64;
+    let memory_threshold_bytes = (latest.memory_total as f64 * config.thresholds.memory_percent / 100.0) as u64;
+
+    for process in &latest.process_metrics {
+        let pid = process.pid.as_u32();
+        let subject = Subject::Process { pid, name: process.name.clone() };
 
-    for process in sys.processes().values() {
-        if f64::from(process.cpu_usage()) > config.thresholds.cpu_percent || process.memory() > memory_threshold_bytes {
-            analysis.high_resource_usage.push(format!(
-                "{} (CPU: {:.1}%, Memory: {})",
-                process.name(),
-                process.cpu_usage(),
-                format_size(process.memory(), BINARY)
-            ));
+        if f64::from(process.cpu_usage) > config.thresholds.cpu_percent {
+            findings.push(Finding {
+                id: format!("process_cpu:{pid}"),
+                category: FindingCategory::ResourceUsage,
+                severity: Severity::Warning,
+                subject: subject.clone(),
+                summary: format!("{} (PID: {pid}) using {:.1}% CPU", process.name, process.cpu_usage),
+                value: Some(f64::from(process.cpu_usage)),
+                threshold: Some(config.thresholds.cpu_percent),
+                action: Some("Check whether this process is expected to be this busy".to_string()),
+                anomaly: None,
+            });
         }
 
-        if process.status() == ProcessStatus::Zombie {
-            analysis.zombie_processes.push(format!(
-                "{} (PID: {})",
-                process.name(),
-                process.pid()
-            ));
+        if process.memory_usage > memory_threshold_bytes {
+            findings.push(Finding {
+                id: format!("process_memory:{pid}"),
+                category: FindingCategory::ResourceUsage,
+                severity: Severity::Warning,
+                subject: subject.clone(),
+                summary: format!("{} (PID: {pid}) using {}", process.name, format_size(process.memory_usage, BINARY)),
+                value: Some(process.memory_usage as f64),
+                threshold: Some(memory_threshold_bytes as f64),
+                action: Some("Restart the process if its memory use is unexpected".to_string()),
+                anomaly: None,
+            });
         }
-    }
 
-    let network_baseline = calculate_network_baseline(metrics_history);
-    let current_total: u64 = sys.networks()
-        .iter()
-        .map(|(_, data)| data.received() + data.transmitted())
-        .sum();
-    if network_baseline > 0 && current_total > network_baseline * 2 {
-        analysis.unusual_network_activity.push(format!(
-            "Network throughput ({}) exceeds 2x baseline ({})",
-            format_size(current_total, BINARY),
-            format_size(network_baseline, BINARY)
-        ));
+        if process.zombie {
+            findings.push(Finding {
+                id: format!("zombie:{pid}"),
+                category: FindingCategory::ZombieProcess,
+                severity: Severity::Warning,
+                subject,
+                summary: format!("{} (PID: {pid})", process.name),
+                value: None,
+                threshold: None,
+                action: Some("Restart or signal the parent process so it reaps its children".to_string()),
+                anomaly: None,
+            });
+        }
     }
 
-    if let Some(last) = metrics_history.last() {
-        if last.swap_total > 0 {
-            let swap_pct = last.swap_usage as f64 / last.swap_total as f64 * 100.0;
-            if swap_pct > config.thresholds.swap_percent {
-                analysis.swap_pressure.push(format!(
+    findings.extend(baseline_findings(metrics_history, baselines, &config.baseline));
+    findings.extend(spawn_findings(metrics_history, &config.spawn_detection));
+
+    if latest.swap_total > 0 {
+        let swap_pct = latest.swap_usage as f64 / latest.swap_total as f64 * 100.0;
+        if swap_pct > config.thresholds.swap_percent {
+            findings.push(Finding {
+                id: "swap_pressure".to_string(),
+                category: FindingCategory::Swap,
+                severity: Severity::Warning,
+                subject: Subject::System,
+                summary: format!(
                     "Swap usage at {swap_pct:.0}% ({} / {})",
-                    format_size(last.swap_usage, BINARY),
-                    format_size(last.swap_total, BINARY)
-                ));
-            }
+                    format_size(latest.swap_usage, BINARY),
+                    format_size(latest.swap_total, BINARY)
+                ),
+                value: Some(swap_pct),
+                threshold: Some(config.thresholds.swap_percent),
+                action: Some("System may be thrashing - close unused applications or add more RAM".to_string()),
+                anomaly: None,
+            });
         }
     }
 
-    analysis
+    sort_findings(&mut findings);
+    SecurityAnalysis { findings }
 }
 
+/// Actionable findings for the latest sample, followed by the security findings that carry an action.
 pub fn generate_recommendations(
     metrics_history: &[SystemMetrics],
     security_analysis: &SecurityAnalysis,
     config: &Config,
-) -> Vec<String> {
+) -> Vec<Finding> {
     let mut recommendations = Vec::new();
     let Some(last_metrics) = metrics_history.last() else {
         return recommendations;
@@ -73,141 +109,190 @@ pub fn generate_recommendations(
 
     let memory_usage_percent = last_metrics.memory_usage as f64 / last_metrics.memory_total as f64 * 100.0;
     if memory_usage_percent > config.thresholds.memory_percent {
-        recommendations.push("* Critical: High memory usage detected - Consider closing unused applications".to_string());
-        recommendations.push("* Run memory diagnostics to check for memory leaks".to_string());
+        recommendations.push(Finding {
+            id: "memory_pressure".to_string(),
+            category: FindingCategory::Memory,
+            severity: Severity::Critical,
+            subject: Subject::System,
+            summary: format!("High memory usage detected ({memory_usage_percent:.0}%)"),
+            value: Some(memory_usage_percent),
+            threshold: Some(config.thresholds.memory_percent),
+            action: Some("Consider closing unused applications and run memory diagnostics to check for leaks".to_string()),
+            anomaly: None,
+        });
     }
 
-    let high_cpu_cores: Vec<usize> = last_metrics.cpu_usage.iter()
+    let high_cpu_cores: Vec<(usize, f32)> = last_metrics.cpu_usage.iter()
+        .copied()
         .enumerate()
-        .filter(|(_, &usage)| f64::from(usage) > config.thresholds.cpu_percent)
-        .map(|(core, _)| core)
+        .filter(|(_, usage)| f64::from(*usage) > config.thresholds.cpu_percent)
         .collect();
 
     if !high_cpu_cores.is_empty() {
-        recommendations.push(format!(
-            "* High CPU usage on cores {} - Check for CPU-intensive processes",
-            high_cpu_cores.iter().map(ToString::to_string).collect::<Vec<_>>().join(", ")
-        ));
-    }
-
-    if !security_analysis.unusual_network_activity.is_empty() {
-        recommendations.push("* Unusual network activity detected - Check firewall settings".to_string());
-        recommendations.push("* Monitor network connections for unauthorized access".to_string());
+        let cores: Vec<usize> = high_cpu_cores.iter().map(|(core, _)| *core).collect();
+        let peak = high_cpu_cores.iter().map(|(_, usage)| *usage).fold(0.0_f32, f32::max);
+        recommendations.push(Finding {
+            id: "cpu_cores".to_string(),
+            category: FindingCategory::Cpu,
+            severity: Severity::Warning,
+            subject: Subject::Cores { cores: cores.clone() },
+            summary: format!(
+                "High CPU usage on cores {}",
+                cores.iter().map(ToString::to_string).collect::<Vec<_>>().join(", ")
+            ),
+            value: Some(f64::from(peak)),
+            threshold: Some(config.thresholds.cpu_percent),
+            action: Some("Check for CPU-intensive processes".to_string()),
+            anomaly: None,
+        });
     }
 
-    if !security_analysis.swap_pressure.is_empty() {
-        recommendations.push("* High swap usage detected - System may be thrashing".to_string());
-        recommendations.push("  - Close unused applications or add more RAM".to_string());
-    }
+    recommendations.extend(core_findings(metrics_history));
 
     let browser_threshold = (config.thresholds.browser_memory_mb * 1024.0 * 1024.0) as u64;
-    let browser_processes: Vec<_> = last_metrics.process_metrics.iter()
-        .filter(|p| {
-            let name = p.name.to_lowercase();
-            name.contains("chrome")
-                || name.contains("chromium")
-                || name.contains("firefox")
-                || name.contains("librewolf")
-                || name.contains("waterfox")
-                || name.contains("msedge")
-                || name.contains("edge")
-                || name.contains("safari")
-                || name.contains("opera")
-                || name.contains("brave")
-                || name.contains("vivaldi")
-                || name.contains("tor")
-                || name.contains("palemoon")
-                || name.contains("seamonkey")
-                || name.contains("falkon")
-                || name.contains("konqueror")
-                || name.contains("epiphany")
-                || name.contains("midori")
-                || name.contains("qutebrowser")
-                || name.contains("iexplore")
-                || name.contains("maxthon")
-                || name.contains("whale")
-                || name.contains("yandex")
-        })
-        .collect();
+    let heaviest_browser = last_metrics.process_metrics.iter()
+        .filter(|p| is_browser(&p.name))
+        .filter(|p| p.memory_usage > browser_threshold)
+        .max_by_key(|p| p.memory_usage);
 
-    if browser_processes.iter().any(|p| p.memory_usage > browser_threshold) {
-        recommendations.push("* Browser memory usage is high:".to_string());
-        recommendations.push("  - Consider reducing number of open tabs".to_string());
+    if let Some(browser) = heaviest_browser {
+        recommendations.push(Finding {
+            id: "browser_memory".to_string(),
+            category: FindingCategory::Browser,
+            severity: Severity::Info,
+            subject: Subject::Process { pid: browser.pid.as_u32(), name: browser.name.clone() },
+            summary: format!("Browser memory usage is high: {} using {}", browser.name, format_size(browser.memory_usage, BINARY)),
+            value: Some(browser.memory_usage as f64),
+            threshold: Some(browser_threshold as f64),
+            action: Some("Consider reducing number of open tabs".to_string()),
+            anomaly: None,
+        });
     }
 
+    recommendations.extend(
+        security_analysis.findings.iter()
+            .filter(|finding| {
+                matches!(
+                    finding.category,
+                    FindingCategory::Network
+                        | FindingCategory::Anomaly
+                        | FindingCategory::MemoryLeak
+                        | FindingCategory::DiskSpace
+                        | FindingCategory::ProcessSpawning
+                        | FindingCategory::Swap
+                )
+            })
+            .cloned(),
+    );
+
+    sort_findings(&mut recommendations);
     recommendations
 }
 
-fn calculate_network_baseline(metrics_history: &[SystemMetrics]) -> u64 {
-    if metrics_history.is_empty() {
-        return 0;
-    }
-    let total: u64 = metrics_history.iter()
-        .map(|m| m.network_rx + m.network_tx)
-        .sum();
-    total / metrics_history.len() as u64
+fn is_browser(name: &str) -> bool {
+    let name = name.to_lowercase();
+    name.contains("chrome")
+        || name.contains("chromium")
+        || name.contains("firefox")
+        || name.contains("librewolf")
+        || name.contains("waterfox")
+        || name.contains("msedge")
+        || name.contains("edge")
+        || name.contains("safari")
+        || name.contains("opera")
+        || name.contains("brave")
+        || name.contains("vivaldi")
+        || name.contains("tor")
+        || name.contains("palemoon")
+        || name.contains("seamonkey")
+        || name.contains("falkon")
+        || name.contains("konqueror")
+        || name.contains("epiphany")
+        || name.contains("midori")
+        || name.contains("qutebrowser")
+        || name.contains("iexplore")
+        || name.contains("maxthon")
+        || name.contains("whale")
+        || name.contains("yandex")
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::types::*;
-    use std::collections::HashMap;
-    use std::time::Instant;
+    use std::time::{Duration, UNIX_EPOCH};
 
     fn make_metrics(cpu: f32, mem_used: u64, mem_total: u64) -> SystemMetrics {
         SystemMetrics {
-            timestamp: Instant::now(),
-            cpu_usage: vec![cpu],
             memory_usage: mem_used,
             memory_total: mem_total,
-            swap_usage: 0,
-            swap_total: 0,
-            network_rx: 0,
-            network_tx: 0,
-            disk_usage: HashMap::new(),
-            process_metrics: Vec::new(),
-            temp_files: TempFileMetrics { total_size: 0, files: Vec::new() },
-            temperature: TemperatureMetrics {
-                cpu_temp: None,
-                gpu_temp: None,
-                components: HashMap::new(),
-            },
+            ..SystemMetrics::sample(0, cpu)
         }
     }
 
     #[test]
-    fn network_baseline_empty_history_returns_zero() {
-        assert_eq!(calculate_network_baseline(&[]), 0);
-    }
+    fn security_analysis_reads_the_latest_sample() {
+        let at = |secs: u64| UNIX_EPOCH + Duration::from_secs(1_700_000_000 + secs);
+        let mut quiet = make_metrics(10.0, 50, 1000);
+        quiet.network_rx = 100;
+        let mut baselines = Baselines::default();
+        for week in 0..12 {
+            let mut history = Vec::new();
+            for minute in 0..2 {
+                quiet.recorded_at = at(week * 7 * 24 * 3600 + minute * 60);
+                history.push(quiet.clone());
+                baselines.observe(&history);
+            }
+        }
 
-    #[test]
-    fn network_baseline_single_entry() {
-        let mut m = make_metrics(10.0, 50, 100);
-        m.network_rx = 1000;
-        m.network_tx = 500;
-        assert_eq!(calculate_network_baseline(&[m]), 1500);
-    }
+        quiet.recorded_at = at(0);
+        let mut latest = make_metrics(95.0, 50, 1000);
+        latest.recorded_at = at(60);
+        latest.network_rx = 100;
+        latest.swap_usage = 90;
+        latest.swap_total = 100;
+        latest.process_metrics = vec![
+            ProcessMetrics {
+                name: "busy".to_string(),
+                pid: sysinfo::Pid::from(7),
+                cpu_usage: 99.0,
+                memory_usage: 950,
+                disk_usage: 0,
+                cmd: Vec::new(),
+                user: None,
+                zombie: false,
+                start_time: 0,
+                threads: None,
+                parent: None,
+            },
+            ProcessMetrics {
+                name: "defunct".to_string(),
+                pid: sysinfo::Pid::from(8),
+                cpu_usage: 0.0,
+                memory_usage: 0,
+                disk_usage: 0,
+                cmd: Vec::new(),
+                user: None,
+                zombie: true,
+                start_time: 0,
+                threads: None,
+                parent: None,
+            },
+        ];
 
-    #[test]
-    fn network_baseline_averages_across_history() {
-        let mut m1 = make_metrics(10.0, 50, 100);
-        m1.network_rx = 1000;
-        m1.network_tx = 0;
-        let mut m2 = make_metrics(10.0, 50, 100);
-        m2.network_rx = 3000;
-        m2.network_tx = 0;
-        assert_eq!(calculate_network_baseline(&[m1, m2]), 2000);
+        let analysis = perform_security_analysis(&[quiet, latest], &baselines, &Config::default());
+        let mut ids: Vec<_> = analysis.findings.iter().map(|f| f.id.as_str()).collect();
+        ids.sort_unstable();
+        assert_eq!(
+            ids,
+            vec!["baseline:cpu", "process_cpu:7", "process_memory:7", "swap_pressure", "zombie:8"]
+        );
+        assert!(perform_security_analysis(&[], &baselines, &Config::default()).findings.is_empty());
     }
 
     #[test]
     fn generate_recommendations_empty_history_returns_empty() {
-        let analysis = SecurityAnalysis {
-            unusual_network_activity: Vec::new(),
-            high_resource_usage: Vec::new(),
-            zombie_processes: Vec::new(),
-            swap_pressure: Vec::new(),
-        };
+        let analysis = SecurityAnalysis::default();
         let config = Config::default();
         assert!(generate_recommendations(&[], &analysis, &config).is_empty());
     }
@@ -219,13 +304,20 @@ mod tests {
         m.swap_total = 100;
         let config = Config::default();
         let analysis = SecurityAnalysis {
-            unusual_network_activity: Vec::new(),
-            high_resource_usage: Vec::new(),
-            zombie_processes: Vec::new(),
-            swap_pressure: vec!["Swap usage at 90%".to_string()],
+            findings: vec![Finding {
+                id: "swap_pressure".to_string(),
+                category: FindingCategory::Swap,
+                severity: Severity::Warning,
+                subject: Subject::System,
+                summary: "Swap usage at 90%".to_string(),
+                value: Some(90.0),
+                threshold: Some(config.thresholds.swap_percent),
+                action: Some("Add more RAM".to_string()),
+                anomaly: None,
+            }],
         };
         let recs = generate_recommendations(&[m], &analysis, &config);
-        assert!(recs.iter().any(|r| r.contains("swap")));
+        assert!(recs.iter().any(|r| r.category == FindingCategory::Swap));
     }
 
     #[test]
@@ -233,29 +325,31 @@ mod tests {
         let mut m = make_metrics(10.0, 50, 100);
         m.swap_total = 0;
         m.swap_usage = 0;
-        let analysis = SecurityAnalysis {
-            unusual_network_activity: Vec::new(),
-            high_resource_usage: Vec::new(),
-            zombie_processes: Vec::new(),
-            swap_pressure: Vec::new(),
-        };
+        let analysis = SecurityAnalysis::default();
         let config = Config::default();
         let recs = generate_recommendations(&[m], &analysis, &config);
-        assert!(!recs.iter().any(|r| r.contains("swap")));
+        assert!(!recs.iter().any(|r| r.category == FindingCategory::Swap));
     }
 
     #[test]
     fn high_memory_triggers_recommendation() {
         let m = make_metrics(10.0, 90, 100);
-        let analysis = SecurityAnalysis {
-            unusual_network_activity: Vec::new(),
-            high_resource_usage: Vec::new(),
-            zombie_processes: Vec::new(),
-            swap_pressure: Vec::new(),
-        };
+        let analysis = SecurityAnalysis::default();
         let config = Config::default();
         let recs = generate_recommendations(&[m], &analysis, &config);
-        assert!(recs.iter().any(|r| r.contains("High memory usage")));
+        assert!(recs.iter().any(|r| r.id == "memory_pressure" && r.severity == Severity::Critical));
+    }
+
+    #[test]
+    fn recommendations_put_critical_findings_first() {
+        let m = make_metrics(99.0, 95, 100);
+        let config = Config::default();
+        let recs = generate_recommendations(&[m], &SecurityAnalysis::default(), &config);
+
+        let ids: Vec<_> = recs.iter().map(|r| r.id.as_str()).collect();
+        assert_eq!(ids, vec!["memory_pressure", "cpu_cores"]);
+        assert_eq!(recs[1].subject, Subject::Cores { cores: vec![0] });
+        assert_eq!(recs[1].value, Some(99.0));
     }
 
     #[test]
@@ -267,16 +361,18 @@ mod tests {
             cpu_usage: 1.0,
             memory_usage: 600 * 1024 * 1024,
             disk_usage: 0,
+            cmd: Vec::new(),
+            user: None,
+            zombie: false,
+            start_time: 0,
+            threads: None,
+            parent: None,
         });
-        let analysis = SecurityAnalysis {
-            unusual_network_activity: Vec::new(),
-            high_resource_usage: Vec::new(),
-            zombie_processes: Vec::new(),
-            swap_pressure: Vec::new(),
-        };
+        let analysis = SecurityAnalysis::default();
         let mut config = Config::default();
         config.thresholds.browser_memory_mb = 512.0;
         let recs = generate_recommendations(&[m], &analysis, &config);
-        assert!(recs.iter().any(|r| r.contains("Browser memory")));
+        let browser = recs.iter().find(|r| r.category == FindingCategory::Browser).unwrap();
+        assert_eq!(browser.subject, Subject::Process { pid: 1, name: "chrome".to_string() });
     }
 }
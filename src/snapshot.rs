@@ -0,0 +1,421 @@
+//! Point-in-time snapshots and the comparison between two of them
+//! (`system-monitor snapshot save` / `diff`).
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::Path;
+use std::time::SystemTime;
+use serde::{Deserialize, Serialize};
+use sysinfo::System;
+use crate::metrics::collect_system_metrics;
+use crate::ports::{listening_ports, ListeningPort};
+use crate::report::SystemInfo;
+use crate::types::{MetricsScope, SystemMetrics};
+
+pub const SNAPSHOT_SCHEMA_VERSION: u32 = 1;
+
+/// Processes compared by name in a diff, taken from the largest by memory on either side.
+const TOP_PROCESS_COUNT: usize = 10;
+const MB: f64 = 1024.0 * 1024.0;
+
+#[derive(Serialize, Deserialize)]
+pub struct Snapshot {
+    pub schema_version: u32,
+    pub taken_at: SystemTime,
+    pub system: SystemInfo,
+    pub metrics: SystemMetrics,
+    #[serde(default)]
+    pub listening_ports: Vec<ListeningPort>,
+}
+
+/// Samples the system once. CPU figures need a primed `System` (see `prime_cpu_usage`).
+pub fn take_snapshot(sys: &mut System) -> Snapshot {
+    let metrics = collect_system_metrics(sys, MetricsScope::Light);
+    Snapshot {
+        schema_version: SNAPSHOT_SCHEMA_VERSION,
+        taken_at: metrics.recorded_at,
+        system: SystemInfo::collect(sys),
+        metrics,
+        listening_ports: listening_ports(),
+    }
+}
+
+pub fn load_snapshot(path: &Path) -> Result<Snapshot, String> {
+    let contents = fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {e}", path.display()))?;
+    let snapshot: Snapshot =
+        serde_json::from_str(&contents).map_err(|e| format!("{} is not a snapshot: {e}", path.display()))?;
+    if snapshot.schema_version != SNAPSHOT_SCHEMA_VERSION {
+        return Err(format!(
+            "{} has snapshot schema {}, expected {SNAPSHOT_SCHEMA_VERSION}",
+            path.display(),
+            snapshot.schema_version
+        ));
+    }
+    Ok(snapshot)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Unit {
+    Percent,
+    Bytes,
+    Celsius,
+    Count,
+}
+
+/// One compared value. `significant` marks changes worth a closer look: a sizeable move,
+/// or a value present on only one side.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Change {
+    pub label: String,
+    pub unit: Unit,
+    pub before: Option<f64>,
+    pub after: Option<f64>,
+    pub significant: bool,
+}
+
+impl Change {
+    pub fn new(label: impl Into<String>, unit: Unit, before: Option<f64>, after: Option<f64>) -> Self {
+        let significant = match (before, after) {
+            (Some(before), Some(after)) => is_significant(unit, before, after),
+            (None, None) => false,
+            _ => true,
+        };
+        Self { label: label.into(), unit, before, after, significant }
+    }
+
+    pub fn delta(&self) -> Option<f64> {
+        Some(self.after? - self.before?)
+    }
+}
+
+fn is_significant(unit: Unit, before: f64, after: f64) -> bool {
+    let delta = (after - before).abs();
+    let relative = delta / before.abs().max(after.abs()).max(f64::EPSILON);
+    match unit {
+        Unit::Percent => delta >= 10.0,
+        Unit::Celsius => delta >= 5.0,
+        Unit::Bytes => relative >= 0.25 && delta >= 64.0 * MB,
+        Unit::Count => relative >= 0.25 && delta >= 10.0,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ProcessChange {
+    pub name: String,
+    pub instances: Change,
+    pub memory: Change,
+    pub cpu: Change,
+}
+
+impl ProcessChange {
+    pub fn significant(&self) -> bool {
+        self.memory.significant || self.cpu.significant
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SnapshotSide {
+    pub taken_at: SystemTime,
+    pub host_name: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SnapshotDiff {
+    pub schema_version: u32,
+    pub before: SnapshotSide,
+    pub after: SnapshotSide,
+    pub resources: Vec<Change>,
+    /// Matched by process name, since pids differ between runs.
+    pub top_processes: Vec<ProcessChange>,
+    pub new_processes: Vec<String>,
+    pub removed_processes: Vec<String>,
+    pub opened_ports: Vec<ListeningPort>,
+    pub closed_ports: Vec<ListeningPort>,
+    pub disks: Vec<Change>,
+    pub temperatures: Vec<Change>,
+}
+
+impl SnapshotDiff {
+    pub fn significant_changes(&self) -> usize {
+        let changes = self.resources.iter().chain(&self.disks).chain(&self.temperatures);
+        changes.filter(|change| change.significant).count()
+            + self.top_processes.iter().filter(|process| process.significant()).count()
+            + self.opened_ports.len()
+            + self.closed_ports.len()
+    }
+}
+
+pub fn diff_snapshots(before: &Snapshot, after: &Snapshot) -> SnapshotDiff {
+    let (old, new) = (&before.metrics, &after.metrics);
+    let old_processes = processes_by_name(old);
+    let new_processes = processes_by_name(new);
+
+    let top: BTreeSet<&str> = top_names(&old_processes).into_iter().chain(top_names(&new_processes)).collect();
+    let mut top_processes: Vec<ProcessChange> = top
+        .into_iter()
+        .map(|name| {
+            // A process missing on one side counts as zero, so only sizeable newcomers stand out.
+            let none = ProcessTotals::default();
+            let old = old_processes.get(name).unwrap_or(&none);
+            let new = new_processes.get(name).unwrap_or(&none);
+            ProcessChange {
+                name: name.to_string(),
+                instances: Change::new("instances", Unit::Count, Some(old.count as f64), Some(new.count as f64)),
+                memory: Change::new("memory", Unit::Bytes, Some(old.memory as f64), Some(new.memory as f64)),
+                cpu: Change::new("cpu", Unit::Percent, Some(old.cpu), Some(new.cpu)),
+            }
+        })
+        .collect();
+    top_processes.sort_by(|a, b| {
+        let memory = |process: &ProcessChange| process.memory.after.unwrap_or_default();
+        memory(b).total_cmp(&memory(a))
+    });
+
+    let port_key = |port: &ListeningPort| (port.protocol.clone(), port.address.clone(), port.port);
+    let old_ports: BTreeSet<_> = before.listening_ports.iter().map(port_key).collect();
+    let new_ports: BTreeSet<_> = after.listening_ports.iter().map(port_key).collect();
+
+    SnapshotDiff {
+        schema_version: SNAPSHOT_SCHEMA_VERSION,
+        before: side(before),
+        after: side(after),
+        resources: vec![
+            Change::new("CPU usage", Unit::Percent, Some(average_cpu(old)), Some(average_cpu(new))),
+            Change::new(
+                "Memory used",
+                Unit::Percent,
+                Some(percent(old.memory_usage, old.memory_total)),
+                Some(percent(new.memory_usage, new.memory_total)),
+            ),
+            Change::new("Memory used", Unit::Bytes, Some(old.memory_usage as f64), Some(new.memory_usage as f64)),
+            Change::new(
+                "Swap used",
+                Unit::Percent,
+                Some(percent(old.swap_usage, old.swap_total)),
+                Some(percent(new.swap_usage, new.swap_total)),
+            ),
+            Change::new(
+                "Processes",
+                Unit::Count,
+                Some(old.process_metrics.len() as f64),
+                Some(new.process_metrics.len() as f64),
+            ),
+        ],
+        top_processes,
+        new_processes: new_processes.keys().filter(|name| !old_processes.contains_key(*name)).cloned().collect(),
+        removed_processes: old_processes.keys().filter(|name| !new_processes.contains_key(*name)).cloned().collect(),
+        opened_ports: after.listening_ports.iter().filter(|p| !old_ports.contains(&port_key(p))).cloned().collect(),
+        closed_ports: before.listening_ports.iter().filter(|p| !new_ports.contains(&port_key(p))).cloned().collect(),
+        disks: keyed_changes(Unit::Percent, &disk_percentages(old), &disk_percentages(new)),
+        temperatures: keyed_changes(Unit::Celsius, &temperatures(old), &temperatures(new)),
+    }
+}
+
+fn side(snapshot: &Snapshot) -> SnapshotSide {
+    SnapshotSide {
+        taken_at: snapshot.taken_at,
+        host_name: snapshot.system.host_name.clone(),
+    }
+}
+
+#[derive(Default)]
+struct ProcessTotals {
+    count: usize,
+    memory: u64,
+    cpu: f64,
+}
+
+fn processes_by_name(metrics: &SystemMetrics) -> BTreeMap<String, ProcessTotals> {
+    let mut totals: BTreeMap<String, ProcessTotals> = BTreeMap::new();
+    for process in &metrics.process_metrics {
+        let entry = totals.entry(process.name.clone()).or_default();
+        entry.count += 1;
+        entry.memory += process.memory_usage;
+        entry.cpu += f64::from(process.cpu_usage);
+    }
+    totals
+}
+
+fn top_names(processes: &BTreeMap<String, ProcessTotals>) -> Vec<&str> {
+    let mut names: Vec<(&str, u64)> = processes.iter().map(|(name, totals)| (name.as_str(), totals.memory)).collect();
+    names.sort_by_key(|&(_, memory)| std::cmp::Reverse(memory));
+    names.into_iter().take(TOP_PROCESS_COUNT).map(|(name, _)| name).collect()
+}
+
+fn keyed_changes(unit: Unit, before: &BTreeMap<String, f64>, after: &BTreeMap<String, f64>) -> Vec<Change> {
+    let keys: BTreeSet<&String> = before.keys().chain(after.keys()).collect();
+    keys.into_iter()
+        .map(|key| Change::new(key.clone(), unit, before.get(key).copied(), after.get(key).copied()))
+        .collect()
+}
+
+fn disk_percentages(metrics: &SystemMetrics) -> BTreeMap<String, f64> {
+    metrics
+        .disk_usage
+        .iter()
+        .map(|(mount, disk)| (mount.clone(), percent(disk.used, disk.total)))
+        .collect()
+}
+
+fn temperatures(metrics: &SystemMetrics) -> BTreeMap<String, f64> {
+    let temperature = &metrics.temperature;
+    let mut readings: BTreeMap<String, f64> = temperature
+        .components
+        .iter()
+        .map(|(label, reading)| (label.clone(), f64::from(reading.celsius)))
+        .collect();
+    if let Some(cpu) = &temperature.cpu_temp {
+        readings.insert("CPU".to_string(), f64::from(cpu.celsius));
+    }
+    if let Some(gpu) = &temperature.gpu_temp {
+        readings.insert("GPU".to_string(), f64::from(gpu.celsius));
+    }
+    readings
+}
+
+fn average_cpu(metrics: &SystemMetrics) -> f64 {
+    metrics.cpu_usage.iter().map(|&usage| f64::from(usage)).sum::<f64>() / metrics.cpu_usage.len().max(1) as f64
+}
+
+fn percent(used: u64, total: u64) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        used as f64 / total as f64 * 100.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::*;
+    use std::collections::HashMap;
+
+    const GB: u64 = 1024 * 1024 * 1024;
+
+    fn process(name: &str, pid: u32, memory_usage: u64) -> ProcessMetrics {
+        ProcessMetrics {
+            name: name.to_string(),
+            pid: sysinfo::Pid::from(pid as usize),
+            cpu_usage: 1.0,
+            memory_usage,
+            disk_usage: 0,
+            cmd: Vec::new(),
+            user: None,
+            zombie: false,
+            start_time: 0,
+            threads: None,
+            parent: None,
+        }
+    }
+
+    fn port(port: u16) -> ListeningPort {
+        ListeningPort {
+            protocol: "tcp".to_string(),
+            address: "0.0.0.0".to_string(),
+            port,
+            pid: Some(1),
+            process: None,
+        }
+    }
+
+    fn snapshot(cpu: f32, disk_used: u64, processes: Vec<ProcessMetrics>, ports: Vec<ListeningPort>) -> Snapshot {
+        Snapshot {
+            schema_version: SNAPSHOT_SCHEMA_VERSION,
+            taken_at: SystemTime::now(),
+            system: SystemInfo {
+                host_name: Some("box".to_string()),
+                os_name: None,
+                os_version: None,
+                long_os_version: None,
+                kernel_version: None,
+                physical_cores: None,
+                logical_cores: 1,
+            },
+            metrics: SystemMetrics {
+                cpu_usage: vec![cpu],
+                memory_usage: 4 * GB,
+                memory_total: 16 * GB,
+                disk_usage: HashMap::from([("/".to_string(), DiskMetrics {
+                    total: 100,
+                    used: disk_used,
+                    read_rate: 0.0,
+                    write_rate: 0.0,
+                })]),
+                process_metrics: processes,
+                temperature: TemperatureMetrics {
+                    cpu_temp: Some(TemperatureReading { celsius: 50.0, fahrenheit: 122.0 }),
+                    ..TemperatureMetrics::default()
+                },
+                ..SystemMetrics::default()
+            },
+            listening_ports: ports,
+        }
+    }
+
+    #[test]
+    fn significance_depends_on_unit() {
+        assert!(Change::new("cpu", Unit::Percent, Some(10.0), Some(25.0)).significant);
+        assert!(!Change::new("cpu", Unit::Percent, Some(10.0), Some(15.0)).significant);
+        assert!(Change::new("mem", Unit::Bytes, Some(GB as f64), Some(2.0 * GB as f64)).significant);
+        assert!(!Change::new("mem", Unit::Bytes, Some(1000.0), Some(5000.0)).significant);
+        assert!(Change::new("temp", Unit::Celsius, None, Some(40.0)).significant);
+        assert_eq!(Change::new("temp", Unit::Celsius, Some(40.0), Some(43.0)).delta(), Some(3.0));
+    }
+
+    #[test]
+    fn diff_reports_processes_ports_and_disks() {
+        let before = snapshot(
+            10.0,
+            50,
+            vec![process("postgres", 10, GB), process("chrome", 11, GB), process("old", 12, 10)],
+            vec![port(22), port(5432)],
+        );
+        let after = snapshot(
+            40.0,
+            52,
+            vec![
+                process("postgres", 20, 3 * GB),
+                process("chrome", 21, GB),
+                process("chrome", 22, GB),
+                process("new", 23, 10),
+            ],
+            vec![port(22), port(8080)],
+        );
+
+        let diff = diff_snapshots(&before, &after);
+
+        assert!(diff.resources[0].significant);
+        assert_eq!(diff.new_processes, vec!["new"]);
+        assert_eq!(diff.removed_processes, vec!["old"]);
+        assert_eq!(diff.opened_ports, vec![port(8080)]);
+        assert_eq!(diff.closed_ports, vec![port(5432)]);
+
+        assert_eq!(diff.top_processes[0].name, "postgres");
+        assert!(diff.top_processes[0].memory.significant);
+        let chrome = diff.top_processes.iter().find(|p| p.name == "chrome").unwrap();
+        assert_eq!(chrome.instances.after, Some(2.0));
+
+        assert_eq!(diff.disks[0].label, "/");
+        assert!(!diff.disks[0].significant);
+        assert_eq!(diff.temperatures[0].label, "CPU");
+        // CPU usage, postgres memory, chrome memory, one opened and one closed port.
+        assert_eq!(diff.significant_changes(), 5);
+    }
+
+    #[test]
+    fn snapshots_round_trip_through_json() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("snapshot.json");
+        let original = snapshot(10.0, 50, vec![process("sshd", 1, 10)], vec![port(22)]);
+        fs::write(&path, crate::report::to_json(&original)).unwrap();
+
+        let loaded = load_snapshot(&path).unwrap();
+        assert_eq!(loaded.listening_ports, vec![port(22)]);
+        assert_eq!(loaded.metrics.process_metrics[0].name, "sshd");
+
+        fs::write(&path, "{}").unwrap();
+        assert!(load_snapshot(&path).err().unwrap().contains("is not a snapshot"));
+    }
+}
@@ -0,0 +1,279 @@
+//! Process creation between consecutive samples, per parent and per user, to catch fork
+//! bombs, runaway build tools and crash-restart loops.
+
+use std::collections::{BTreeMap, HashSet};
+use std::time::UNIX_EPOCH;
+use sysinfo::PidExt;
+use crate::config::SpawnConfig;
+use crate::types::{Finding, FindingCategory, ProcessMetrics, Severity, Subject, SystemMetrics};
+
+/// Processes started by one parent since the previous sample.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParentSpawns {
+    pub pid: u32,
+    /// `None` once the parent has exited.
+    pub name: Option<String>,
+    /// Children running in the latest sample.
+    pub children: usize,
+    pub spawned: usize,
+    pub per_min: f64,
+}
+
+/// Processes started by one user since the previous sample.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UserSpawns {
+    pub user: String,
+    pub spawned: usize,
+    pub per_min: f64,
+    /// The parent that started most of them.
+    pub parent: u32,
+}
+
+/// Spawn counts between the last two samples in `metrics_history`, busiest first. A process
+/// is new when it was not in the previous sample and started after it, so processes that
+/// start and exit between two samples are not seen.
+pub fn spawn_activity(metrics_history: &[SystemMetrics]) -> (Vec<ParentSpawns>, Vec<UserSpawns>) {
+    let [.., previous, latest] = metrics_history else {
+        return (Vec::new(), Vec::new());
+    };
+    let Some(minutes) = latest
+        .recorded_at
+        .duration_since(previous.recorded_at)
+        .ok()
+        .filter(|elapsed| !elapsed.is_zero())
+        .map(|elapsed| elapsed.as_secs_f64() / 60.0)
+    else {
+        return (Vec::new(), Vec::new());
+    };
+    let since = previous.recorded_at.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+    let seen: HashSet<(u32, u64)> = previous.process_metrics.iter().map(key).collect();
+    let spawned: Vec<&ProcessMetrics> = latest
+        .process_metrics
+        .iter()
+        .filter(|process| process.start_time >= since && !seen.contains(&key(process)))
+        .collect();
+
+    let mut parents: BTreeMap<u32, ParentSpawns> = BTreeMap::new();
+    for process in &latest.process_metrics {
+        if let Some(parent) = process.parent {
+            parents.entry(parent).or_insert_with(|| parent_spawns(parent, latest)).children += 1;
+        }
+    }
+    let mut users: BTreeMap<&str, BTreeMap<u32, usize>> = BTreeMap::new();
+    for process in &spawned {
+        let Some(parent) = process.parent else {
+            continue;
+        };
+        parents.entry(parent).or_insert_with(|| parent_spawns(parent, latest)).spawned += 1;
+        if let Some(user) = &process.user {
+            *users.entry(user).or_default().entry(parent).or_default() += 1;
+        }
+    }
+
+    let mut parents: Vec<ParentSpawns> = parents
+        .into_values()
+        .map(|parent| ParentSpawns { per_min: parent.spawned as f64 / minutes, ..parent })
+        .collect();
+    parents.sort_by(|a, b| b.spawned.cmp(&a.spawned).then(b.children.cmp(&a.children)).then(a.pid.cmp(&b.pid)));
+    let mut users: Vec<UserSpawns> = users
+        .into_iter()
+        .filter_map(|(user, by_parent)| {
+            let spawned = by_parent.values().sum();
+            let (&parent, _) = by_parent.iter().max_by(|a, b| a.1.cmp(b.1).then(b.0.cmp(a.0)))?;
+            Some(UserSpawns { user: user.to_string(), spawned, per_min: spawned as f64 / minutes, parent })
+        })
+        .collect();
+    users.sort_by(|a, b| b.spawned.cmp(&a.spawned).then(a.user.cmp(&b.user)));
+    (parents, users)
+}
+
+/// A critical finding per parent starting processes faster than `max_spawns_per_min` or
+/// running more than `max_children`, and per user starting them faster than
+/// `max_user_spawns_per_min`, reported against the parent that started most of them.
+pub fn spawn_findings(metrics_history: &[SystemMetrics], config: &SpawnConfig) -> Vec<Finding> {
+    if !config.enabled {
+        return Vec::new();
+    }
+    let (parents, users) = spawn_activity(metrics_history);
+    let mut findings: Vec<Finding> = parents
+        .iter()
+        .filter(|parent| parent.per_min > config.max_spawns_per_min || parent.children > config.max_children)
+        .map(|parent| {
+            let (value, threshold) = if parent.per_min > config.max_spawns_per_min {
+                (parent.per_min, config.max_spawns_per_min)
+            } else {
+                (parent.children as f64, config.max_children as f64)
+            };
+            spawn_finding(
+                parent,
+                format!(
+                    "{} started {} processes ({:.0}/min), {} children running",
+                    describe(parent),
+                    parent.spawned,
+                    parent.per_min,
+                    parent.children
+                ),
+                value,
+                threshold,
+            )
+        })
+        .collect();
+
+    for user in users.iter().filter(|user| user.per_min > config.max_user_spawns_per_min) {
+        if findings.iter().any(|finding| finding.id == format!("spawn_storm:{}", user.parent)) {
+            continue;
+        }
+        let Some(parent) = parents.iter().find(|parent| parent.pid == user.parent) else {
+            continue;
+        };
+        findings.push(spawn_finding(
+            parent,
+            format!(
+                "{} started {} processes ({:.0}/min), most of them from {}",
+                user.user,
+                user.spawned,
+                user.per_min,
+                describe(parent)
+            ),
+            user.per_min,
+            config.max_user_spawns_per_min,
+        ));
+    }
+    findings
+}
+
+fn spawn_finding(parent: &ParentSpawns, summary: String, value: f64, threshold: f64) -> Finding {
+    Finding {
+        id: format!("spawn_storm:{}", parent.pid),
+        category: FindingCategory::ProcessSpawning,
+        severity: Severity::Critical,
+        subject: Subject::Process {
+            pid: parent.pid,
+            name: parent.name.clone().unwrap_or_default(),
+        },
+        summary,
+        value: Some(value),
+        threshold: Some(threshold),
+        action: Some(format!(
+            "Check what PID {} is starting and stop it if unexpected; `kill -STOP` the parent first so it cannot respawn",
+            parent.pid
+        )),
+        anomaly: None,
+    }
+}
+
+fn parent_spawns(pid: u32, latest: &SystemMetrics) -> ParentSpawns {
+    ParentSpawns {
+        pid,
+        name: latest
+            .process_metrics
+            .iter()
+            .find(|process| process.pid.as_u32() == pid)
+            .map(|process| process.name.clone()),
+        children: 0,
+        spawned: 0,
+        per_min: 0.0,
+    }
+}
+
+fn key(process: &ProcessMetrics) -> (u32, u64) {
+    (process.pid.as_u32(), process.start_time)
+}
+
+/// `make (PID 812)`, or `PID 812` once it has exited.
+fn describe(parent: &ParentSpawns) -> String {
+    match &parent.name {
+        Some(name) => format!("{name} (PID {})", parent.pid),
+        None => format!("PID {}", parent.pid),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::*;
+    use std::time::Duration;
+
+    const START: u64 = 1_700_000_000;
+
+    fn process(pid: usize, name: &str, parent: Option<u32>, user: &str, start_time: u64) -> ProcessMetrics {
+        ProcessMetrics {
+            name: name.to_string(),
+            pid: sysinfo::Pid::from(pid),
+            cpu_usage: 0.0,
+            memory_usage: 0,
+            disk_usage: 0,
+            cmd: Vec::new(),
+            user: Some(user.to_string()),
+            zombie: false,
+            start_time,
+            threads: None,
+            parent,
+        }
+    }
+
+    fn sample(secs: u64, process_metrics: Vec<ProcessMetrics>) -> SystemMetrics {
+        SystemMetrics {
+            recorded_at: UNIX_EPOCH + Duration::from_secs(START + secs),
+            process_metrics,
+            ..SystemMetrics::default()
+        }
+    }
+
+    /// A shell and a build tool, plus `children` copies of a forking script under the shell,
+    /// started `at` seconds in.
+    fn processes(children: usize, at: u64) -> Vec<ProcessMetrics> {
+        let mut processes = vec![
+            process(1, "init", None, "root", 0),
+            process(100, "bash", Some(1), "alice", 10),
+            process(200, "make", Some(1), "bob", 10),
+        ];
+        processes.extend((0..children).map(|n| process(1000 + n, "bomb.sh", Some(100), "alice", START + at)));
+        processes
+    }
+
+    #[test]
+    fn fork_bomb_is_critical_and_names_the_parent() {
+        let mut latest = processes(5, 0);
+        latest.extend((0..200).map(|n| process(2000 + n, "bomb.sh", Some(100), "alice", START + 20)));
+        let history = vec![sample(0, processes(5, 0)), sample(30, latest)];
+        let (parents, _) = spawn_activity(&history);
+        assert_eq!(parents[0].pid, 100);
+        assert_eq!((parents[0].spawned, parents[0].children, parents[0].per_min), (200, 205, 400.0));
+
+        let findings = spawn_findings(&history, &SpawnConfig::default());
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].id, "spawn_storm:100");
+        assert_eq!(findings[0].severity, Severity::Critical);
+        assert_eq!(findings[0].subject, Subject::Process { pid: 100, name: "bash".to_string() });
+        assert_eq!(findings[0].summary, "bash (PID 100) started 200 processes (400/min), 205 children running");
+    }
+
+    #[test]
+    fn user_storms_spread_over_parents_name_the_busiest_one() {
+        let mut latest = processes(0, 0);
+        for n in 0..80 {
+            let parent = if n < 50 { 100 } else { 200 };
+            latest.push(process(5000 + n, "cc1", Some(parent), "alice", START + 30));
+        }
+        let history = vec![sample(0, processes(0, 0)), sample(60, latest)];
+        let config = SpawnConfig { max_user_spawns_per_min: 60.0, ..SpawnConfig::default() };
+
+        let findings = spawn_findings(&history, &config);
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].id, "spawn_storm:100");
+        assert_eq!(findings[0].summary, "alice started 80 processes (80/min), most of them from bash (PID 100)");
+    }
+
+    #[test]
+    fn steady_processes_and_missing_samples_are_quiet() {
+        let steady = vec![sample(0, processes(300, 0)), sample(60, processes(300, 0))];
+        assert_eq!(spawn_activity(&steady).0[0].spawned, 0);
+        assert!(spawn_findings(&steady, &SpawnConfig::default()).is_empty());
+
+        assert!(spawn_findings(&steady[..1], &SpawnConfig::default()).is_empty());
+        let disabled = SpawnConfig { enabled: false, ..SpawnConfig::default() };
+        let bomb = vec![sample(0, processes(0, 0)), sample(30, processes(600, 20))];
+        assert!(spawn_findings(&bomb, &disabled).is_empty());
+    }
+}
@@ -1,82 +1,604 @@
 use std::collections::HashMap;
 use std::time::{SystemTime, Instant};
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, PartialEq)]
+/// Bumped whenever a serialized field of the metrics model changes meaning or shape.
+pub const METRICS_SCHEMA_VERSION: u32 = 1;
+
+/// How much a sample collects.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum MetricsScope {
+    /// Everything, including a walk of the temp directories.
     Full,
+    /// Skips the temp file walk.
     Light,
 }
 
-#[derive(Clone)]
+/// One sensor reading.
+#[derive(Clone, Serialize, Deserialize)]
 pub struct TemperatureReading {
+    /// Degrees Celsius.
     pub celsius: f32,
+    /// Degrees Fahrenheit.
     pub fahrenheit: f32,
 }
 
+/// Sensor readings from one sample.
+#[derive(Clone, Default, Serialize, Deserialize)]
 pub struct TemperatureMetrics {
+    /// The CPU package sensor, if found.
     pub cpu_temp: Option<TemperatureReading>,
+    /// The GPU sensor, if found.
     pub gpu_temp: Option<TemperatureReading>,
+    /// Every sensor, by label.
     pub components: HashMap<String, TemperatureReading>,
 }
 
 #[allow(dead_code)]
+/// One sample of system-wide and per-process metrics.
+#[derive(Clone, Serialize, Deserialize)]
 pub struct SystemMetrics {
+    /// [`METRICS_SCHEMA_VERSION`] at the time of collection.
+    pub schema_version: u32,
+    /// Monotonic time of collection; only meaningful within the process that collected it.
+    #[serde(skip, default = "Instant::now")]
     pub timestamp: Instant,
+    /// Wall-clock time of collection, comparable across runs and machines.
+    pub recorded_at: SystemTime,
+    /// Percent busy per logical core.
     pub cpu_usage: Vec<f32>,
+    /// Bytes of memory in use.
     pub memory_usage: u64,
+    /// Bytes of memory installed.
     pub memory_total: u64,
+    /// Bytes of swap in use.
     pub swap_usage: u64,
+    /// Bytes of swap configured.
     pub swap_total: u64,
+    /// Bytes received on all interfaces since the previous sample.
     pub network_rx: u64,
+    /// Bytes sent on all interfaces since the previous sample.
     pub network_tx: u64,
+    /// Bytes read and written by all processes since the previous sample.
+    #[serde(default)]
+    pub disk_io: u64,
+    /// Space per mount point.
     pub disk_usage: HashMap<String, DiskMetrics>,
+    /// Every running process.
     pub process_metrics: Vec<ProcessMetrics>,
+    /// Temp directory usage; empty for [`MetricsScope::Light`] samples.
     pub temp_files: TempFileMetrics,
+    /// Sensor readings.
     pub temperature: TemperatureMetrics,
 }
 
+impl Default for SystemMetrics {
+    /// An empty sample taken now.
+    fn default() -> Self {
+        Self {
+            schema_version: METRICS_SCHEMA_VERSION,
+            timestamp: Instant::now(),
+            recorded_at: SystemTime::now(),
+            cpu_usage: Vec::new(),
+            memory_usage: 0,
+            memory_total: 0,
+            swap_usage: 0,
+            swap_total: 0,
+            network_rx: 0,
+            network_tx: 0,
+            disk_io: 0,
+            disk_usage: HashMap::new(),
+            process_metrics: Vec::new(),
+            temp_files: TempFileMetrics::default(),
+            temperature: TemperatureMetrics::default(),
+        }
+    }
+}
+
+#[cfg(test)]
+impl SystemMetrics {
+    /// A test sample recorded `secs` after 2023-11-14 22:13:20 UTC, with one core at `cpu`
+    /// percent and memory at 40%.
+    pub(crate) fn sample(secs: u64, cpu: f32) -> Self {
+        Self {
+            recorded_at: SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(1_700_000_000 + secs),
+            cpu_usage: vec![cpu],
+            memory_usage: 40,
+            memory_total: 100,
+            ..Self::default()
+        }
+    }
+}
+
+/// Space and throughput of one mount.
 #[allow(dead_code)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct DiskMetrics {
+    /// Capacity in bytes.
     pub total: u64,
+    /// Bytes in use.
     pub used: u64,
+    /// Bytes read per second.
     pub read_rate: f64,
+    /// Bytes written per second.
     pub write_rate: f64,
 }
 
+/// One process in a sample.
 #[allow(dead_code)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct ProcessMetrics {
+    /// Process name.
     pub name: String,
+    /// Process ID.
+    #[serde(with = "pid_serde")]
     pub pid: sysinfo::Pid,
+    /// CPU usage, in percent of one core.
     pub cpu_usage: f32,
+    /// Resident memory in bytes.
     pub memory_usage: u64,
+    /// Bytes read and written since the previous sample.
     pub disk_usage: u64,
+    /// Command line.
+    pub cmd: Vec<String>,
+    /// Owning user, when it can be resolved.
+    pub user: Option<String>,
+    /// Exited but not yet reaped by its parent.
+    #[serde(default)]
+    pub zombie: bool,
+    /// Unix seconds the process started; with the pid, identifies it across samples.
+    #[serde(default)]
+    pub start_time: u64,
+    /// Threads in the process, where the platform reports them.
+    #[serde(default)]
+    pub threads: Option<u32>,
+    /// Pid of the process that started it.
+    #[serde(default)]
+    pub parent: Option<u32>,
 }
 
+/// Files found in the temp directories.
+#[derive(Clone, Default, Serialize, Deserialize)]
 pub struct TempFileMetrics {
+    /// Bytes across all files.
     pub total_size: u64,
+    /// Every file found.
     pub files: Vec<TempFileInfo>,
 }
 
+/// One temporary file.
+#[derive(Clone, Serialize, Deserialize)]
 pub struct TempFileInfo {
+    /// Full path.
     pub path: String,
+    /// Size in bytes.
     pub size: u64,
+    /// Modification time, where the platform reports it.
     pub last_modified: Option<SystemTime>,
 }
 
+/// How urgent a finding is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// Worth knowing, no action needed.
+    Info,
+    /// Should be looked at.
+    Warning,
+    /// Needs action now; critical findings are notified by the daemon.
+    Critical,
+}
+
+impl Severity {
+    /// `Info`, `Warning` or `Critical`.
+    pub fn label(self) -> &'static str {
+        match self {
+            Severity::Info => "Info",
+            Severity::Warning => "Warning",
+            Severity::Critical => "Critical",
+        }
+    }
+}
+
+/// The check a finding comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FindingCategory {
+    /// Network traffic well above recent levels.
+    Network,
+    /// A process using a lot of CPU or memory.
+    ResourceUsage,
+    /// Processes that exited but were not reaped.
+    ZombieProcess,
+    /// Swap in heavy use.
+    Swap,
+    /// System memory pressure.
+    Memory,
+    /// System CPU load, including single-core bottlenecks.
+    Cpu,
+    /// Browser processes using a lot of memory.
+    Browser,
+    /// A metric far from what its learned baseline expects at this time of week.
+    Anomaly,
+    /// A process whose memory keeps growing.
+    MemoryLeak,
+    /// A mount forecast to fill up soon.
+    DiskSpace,
+    /// A parent or user starting processes faster than configured.
+    ProcessSpawning,
+}
+
+impl FindingCategory {
+    /// Heading used for the category in reports and notifications.
+    pub fn title(self) -> &'static str {
+        match self {
+            FindingCategory::Network => "Unusual Network Activity",
+            FindingCategory::ResourceUsage => "High Resource Usage",
+            FindingCategory::ZombieProcess => "Zombie Processes",
+            FindingCategory::Swap => "Swap Pressure",
+            FindingCategory::Memory => "Memory",
+            FindingCategory::Cpu => "CPU",
+            FindingCategory::Browser => "Browsers",
+            FindingCategory::Anomaly => "Unusual Resource Usage",
+            FindingCategory::MemoryLeak => "Suspected Memory Leaks",
+            FindingCategory::DiskSpace => "Disk Space Forecast",
+            FindingCategory::ProcessSpawning => "Runaway Process Spawning",
+        }
+    }
+}
+
+/// Categories produced by the security analysis, in display order.
+pub const SECURITY_CATEGORIES: [FindingCategory; 8] = [
+    FindingCategory::ProcessSpawning,
+    FindingCategory::Network,
+    FindingCategory::Anomaly,
+    FindingCategory::MemoryLeak,
+    FindingCategory::DiskSpace,
+    FindingCategory::ResourceUsage,
+    FindingCategory::ZombieProcess,
+    FindingCategory::Swap,
+];
+
+/// What a finding is about.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Subject {
+    /// The host as a whole.
+    System,
+    /// One process.
+    Process {
+        /// Process id.
+        pid: u32,
+        /// Process name.
+        name: String,
+    },
+    /// A mounted file system.
+    Mount {
+        /// Mount point.
+        path: String,
+    },
+    /// A temperature sensor.
+    Sensor {
+        /// Sensor label.
+        label: String,
+    },
+    /// Logical cores, by index.
+    Cores {
+        /// Core indexes.
+        cores: Vec<usize>,
+    },
+}
+
+/// A single observation from analysis, rendered by the CLI, daemon, notifications and reports.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Finding {
+    /// Stable identifier, unique per subject (e.g. `zombie:1234`).
+    pub id: String,
+    /// The check the finding comes from.
+    pub category: FindingCategory,
+    /// How urgent it is.
+    pub severity: Severity,
+    /// What it is about.
+    pub subject: Subject,
+    /// One line describing what was observed.
+    pub summary: String,
+    /// Measured value in the unit the threshold is expressed in.
+    pub value: Option<f64>,
+    /// The limit `value` was compared against.
+    pub threshold: Option<f64>,
+    /// Suggested next step.
+    pub action: Option<String>,
+    /// Set on anomaly findings: how far the value is from what was expected.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub anomaly: Option<AnomalyScore>,
+}
+
+/// Which side of the expected value an anomaly is on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Direction {
+    /// Higher than expected.
+    Above,
+    /// Lower than expected.
+    Below,
+}
+
+impl Direction {
+    /// `Below` for a negative `delta`, otherwise `Above`.
+    pub fn of(delta: f64) -> Self {
+        if delta < 0.0 {
+            Direction::Below
+        } else {
+            Direction::Above
+        }
+    }
+
+    /// `above` or `below`.
+    pub fn label(self) -> &'static str {
+        match self {
+            Direction::Above => "above",
+            Direction::Below => "below",
+        }
+    }
+}
+
+/// How far an anomalous value is from what was expected.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct AnomalyScore {
+    /// Distance from the expected value in standard deviations (robust ones in MAD mode).
+    pub score: f64,
+    /// Which side of the expected value it is on.
+    pub direction: Direction,
+}
+
+/// Orders findings most severe first and keeps only the most severe finding per id.
+pub fn sort_findings(findings: &mut Vec<Finding>) {
+    findings.sort_by(|a, b| a.id.cmp(&b.id).then_with(|| b.severity.cmp(&a.severity)));
+    findings.dedup_by(|later, kept| later.id == kept.id);
+    findings.sort_by(|a, b| b.severity.cmp(&a.severity).then_with(|| a.id.cmp(&b.id)));
+}
+
+/// Findings from the security and anomaly checks, most severe first.
+#[derive(Default, Serialize, Deserialize)]
 pub struct SecurityAnalysis {
-    pub unusual_network_activity: Vec<String>,
-    pub high_resource_usage: Vec<String>,
-    pub zombie_processes: Vec<String>,
-    pub swap_pressure: Vec<String>,
+    /// One finding per id.
+    pub findings: Vec<Finding>,
+}
+
+impl SecurityAnalysis {
+    /// Adds findings, keeping the list most severe first with one finding per id.
+    pub fn add_findings(&mut self, findings: Vec<Finding>) {
+        self.findings.extend(findings);
+        sort_findings(&mut self.findings);
+    }
+
+    /// Findings from one check.
+    pub fn in_category(&self, category: FindingCategory) -> impl Iterator<Item = &Finding> {
+        self.findings.iter().filter(move |finding| finding.category == category)
+    }
+}
+
+/// Which way a series' fitted line moves over the analysed window.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TrendDirection {
+    /// The fitted line goes up.
+    Rising,
+    /// The fitted line goes down.
+    Falling,
+    /// The slope is negligible.
+    #[default]
+    Flat,
+}
+
+impl TrendDirection {
+    /// `rising`, `falling` or `flat`.
+    pub fn label(self) -> &'static str {
+        match self {
+            TrendDirection::Rising => "rising",
+            TrendDirection::Falling => "falling",
+            TrendDirection::Flat => "flat",
+        }
+    }
+}
+
+/// How a series moved, independent of its level.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TrendShape {
+    /// Least-squares slope per minute, in the series' unit.
+    pub slope_per_min: f64,
+    /// Which way the fitted line moves.
+    pub direction: TrendDirection,
+    /// Coefficient of variation: standard deviation over the average.
+    pub volatility: f64,
+    /// Peak over the average; around 1 when nothing stands out.
+    pub spikiness: f64,
+    /// Summary such as `rising, volatile` or `flat, steady, spiky`.
+    pub pattern: String,
+}
+
+impl TrendShape {
+    /// One-line description; `slope` is `slope_per_min` formatted in the series' unit.
+    pub fn describe(&self, slope: &str) -> String {
+        format!(
+            "{} ({slope}/min, volatility {:.2}, spikiness {:.1})",
+            self.pattern, self.volatility, self.spikiness
+        )
+    }
+}
+
+/// Distribution of a series, in its unit.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct SeriesStats {
+    /// Lowest value.
+    pub min: f64,
+    /// Median.
+    pub p50: f64,
+    /// 90th percentile.
+    pub p90: f64,
+    /// 95th percentile.
+    pub p95: f64,
+    /// 99th percentile.
+    pub p99: f64,
+    /// Standard deviation.
+    pub std_dev: f64,
+}
+
+impl SeriesStats {
+    /// One-line description with each value formatted by `format`.
+    pub fn describe(&self, format: impl Fn(f64) -> String) -> String {
+        format!(
+            "min {}, p50 {}, p90 {}, p95 {}, p99 {}, std dev {}",
+            format(self.min),
+            format(self.p50),
+            format(self.p90),
+            format(self.p95),
+            format(self.p99),
+            format(self.std_dev)
+        )
+    }
 }
 
+/// Level and shape of one series over the run.
+#[derive(Default, Serialize, Deserialize)]
 pub struct UsageTrend {
+    /// Mean value.
     pub average: f64,
+    /// Highest value.
     pub peak: f64,
-    pub pattern: f64,
+    /// Spread of the series.
+    #[serde(flatten)]
+    pub stats: SeriesStats,
+    /// Slope and direction of the series.
+    #[serde(flatten)]
+    pub shape: TrendShape,
 }
 
+/// Receive and transmit rates over the run, in bytes per second.
+#[derive(Serialize, Deserialize)]
 pub struct NetworkTrend {
+    /// Mean receive rate.
     pub rx_rate: f64,
+    /// Mean transmit rate.
     pub tx_rate: f64,
+    /// Highest receive rate.
+    pub rx_peak: f64,
+    /// Highest transmit rate.
+    pub tx_peak: f64,
+    /// Shape of the receive series.
+    pub rx: TrendShape,
+    /// Shape of the transmit series.
+    pub tx: TrendShape,
+    /// Spread of the receive series.
+    pub rx_stats: SeriesStats,
+    /// Spread of the transmit series.
+    pub tx_stats: SeriesStats,
+}
+
+mod pid_serde {
+    use serde::{Deserialize, Deserializer, Serializer};
+    use sysinfo::PidExt;
+
+    pub fn serialize<S: Serializer>(pid: &sysinfo::Pid, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u32(pid.as_u32())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<sysinfo::Pid, D::Error> {
+        u32::deserialize(deserializer).map(sysinfo::Pid::from_u32)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sysinfo::PidExt;
+
+    #[test]
+    fn metrics_round_trip_through_json() {
+        let metrics = SystemMetrics {
+            cpu_usage: vec![12.5, 40.0],
+            memory_usage: 4096,
+            memory_total: 8192,
+            swap_total: 1024,
+            network_rx: 10,
+            network_tx: 20,
+            disk_usage: HashMap::from([("/".to_string(), DiskMetrics {
+                total: 100,
+                used: 40,
+                read_rate: 0.0,
+                write_rate: 0.0,
+            })]),
+            process_metrics: vec![ProcessMetrics {
+                name: "postgres".to_string(),
+                pid: sysinfo::Pid::from_u32(4242),
+                cpu_usage: 3.0,
+                memory_usage: 2048,
+                disk_usage: 0,
+                cmd: vec!["postgres".to_string(), "-D".to_string()],
+                user: Some("postgres".to_string()),
+                zombie: false,
+                start_time: 0,
+                threads: None,
+                parent: None,
+            }],
+            temperature: TemperatureMetrics {
+                cpu_temp: Some(TemperatureReading { celsius: 50.0, fahrenheit: 122.0 }),
+                ..TemperatureMetrics::default()
+            },
+            ..SystemMetrics::sample(0, 12.5)
+        };
+
+        let json = serde_json::to_string(&metrics).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["schema_version"], METRICS_SCHEMA_VERSION);
+        assert_eq!(value["process_metrics"][0]["pid"], 4242);
+        assert!(value.get("timestamp").is_none());
+
+        let restored: SystemMetrics = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.recorded_at, metrics.recorded_at);
+        assert_eq!(restored.cpu_usage, metrics.cpu_usage);
+        assert_eq!(restored.disk_usage["/"].used, 40);
+        assert_eq!(restored.process_metrics[0].pid.as_u32(), 4242);
+        assert_eq!(restored.temperature.cpu_temp.unwrap().celsius, 50.0);
+    }
+
+    fn finding(id: &str, severity: Severity) -> Finding {
+        Finding {
+            id: id.to_string(),
+            category: FindingCategory::Swap,
+            severity,
+            subject: Subject::System,
+            summary: id.to_string(),
+            value: None,
+            threshold: None,
+            action: None,
+            anomaly: None,
+        }
+    }
+
+    #[test]
+    fn findings_sort_by_severity_and_dedupe_by_id() {
+        let mut findings = vec![
+            finding("b", Severity::Info),
+            finding("a", Severity::Warning),
+            finding("b", Severity::Critical),
+            finding("c", Severity::Warning),
+        ];
+        sort_findings(&mut findings);
+
+        let order: Vec<_> = findings.iter().map(|f| (f.id.as_str(), f.severity)).collect();
+        assert_eq!(order, vec![("b", Severity::Critical), ("a", Severity::Warning), ("c", Severity::Warning)]);
+    }
+
+    #[test]
+    fn findings_serialize_with_lowercase_severity_and_tagged_subject() {
+        let mut f = finding("zombie:7", Severity::Warning);
+        f.subject = Subject::Process { pid: 7, name: "defunct".to_string() };
+        let value = serde_json::to_value(&f).unwrap();
+
+        assert_eq!(value["severity"], "warning");
+        assert_eq!(value["category"], "swap");
+        assert_eq!(value["subject"]["type"], "process");
+        assert_eq!(value["subject"]["pid"], 7);
+    }
 }
@@ -0,0 +1,124 @@
+use std::time::{Duration, Instant};
+use system_monitor::sysinfo::{System, SystemExt};
+use system_monitor::sysinfo::Pid;
+use system_monitor::{
+    analyze, build_report, collect_snapshot, evaluate_alerts, new_system, render_report, AlertKind, AnomalyScore,
+    Config, Direction, FindingCategory, HealthComponent, HealthFactor, MetricsScope, ProcessMetrics, ReportFormat,
+    Severity, Subject, SystemMetrics, METRICS_SCHEMA_VERSION, REPORT_SCHEMA_VERSION,
+};
+
+fn sample(cpu: f32, memory_percent: u64, offset_secs: u64, start: Instant) -> SystemMetrics {
+    SystemMetrics {
+        timestamp: start + Duration::from_secs(offset_secs),
+        cpu_usage: vec![cpu, cpu],
+        memory_usage: memory_percent,
+        memory_total: 100,
+        ..SystemMetrics::default()
+    }
+}
+
+#[test]
+fn snapshot_reads_the_running_system() {
+    let mut sys = new_system();
+    let snapshot = collect_snapshot(&mut sys, MetricsScope::Light);
+
+    assert_eq!(snapshot.schema_version, METRICS_SCHEMA_VERSION);
+    assert!(!snapshot.cpu_usage.is_empty());
+    assert!(snapshot.memory_total > 0);
+}
+
+#[test]
+fn analysis_reports_findings_from_history() {
+    let start = Instant::now();
+    let history = vec![sample(20.0, 50, 0, start), sample(99.0, 95, 5, start)];
+
+    let analysis = analyze(&history, &Config::default());
+
+    let memory = analysis
+        .recommendations
+        .iter()
+        .find(|finding| finding.category == FindingCategory::Memory)
+        .expect("memory finding");
+    assert_eq!(memory.severity, Severity::Critical);
+    assert_eq!(memory.value, Some(95.0));
+    assert!(analysis.recommendations.iter().any(|finding| finding.category == FindingCategory::Cpu));
+}
+
+fn process(pid: usize, name: &str, zombie: bool) -> ProcessMetrics {
+    ProcessMetrics {
+        name: name.to_string(),
+        pid: Pid::from(pid),
+        cpu_usage: 0.0,
+        memory_usage: 0,
+        disk_usage: 0,
+        cmd: vec![name.to_string()],
+        user: Some("alice".to_string()),
+        zombie,
+        start_time: 0,
+        threads: None,
+        parent: Some(1),
+    }
+}
+
+#[test]
+fn findings_and_health_are_readable_through_named_types() {
+    let start = Instant::now();
+    let mut history: Vec<SystemMetrics> =
+        (0..30).map(|n| sample(if n % 2 == 0 { 10.0 } else { 12.0 }, 40, n * 5, start)).collect();
+    let mut spike = sample(95.0, 40, 150, start);
+    spike.process_metrics = vec![process(1, "init", false), process(4242, "worker", true)];
+    history.push(spike);
+
+    let analysis = analyze(&history, &Config::default());
+    let findings: Vec<_> = analysis.security.findings.iter().chain(&analysis.recommendations).collect();
+
+    let zombie = findings.iter().find(|finding| finding.id == "zombie:4242").expect("zombie finding");
+    assert_eq!(zombie.subject, Subject::Process { pid: 4242, name: "worker".to_string() });
+
+    let anomaly: AnomalyScore = findings.iter().find_map(|finding| finding.anomaly).expect("anomaly finding");
+    assert_eq!(anomaly.direction, Direction::Above);
+    assert!(anomaly.score > 0.0);
+
+    let findings_component: &HealthComponent = analysis
+        .health
+        .components
+        .iter()
+        .find(|component| component.factor == HealthFactor::Findings)
+        .expect("findings component");
+    assert!(findings_component.score < 100.0);
+}
+
+#[test]
+fn alerts_follow_configured_thresholds() {
+    let start = Instant::now();
+    let mut config = Config::default();
+    config.thresholds.cpu_percent = 50.0;
+
+    let alerts = evaluate_alerts(&sample(60.0, 10, 0, start), &config);
+
+    assert!(alerts.iter().any(|alert| alert.kind == AlertKind::Cpu && alert.active));
+    assert!(alerts.iter().any(|alert| alert.kind == AlertKind::Memory && !alert.active));
+}
+
+#[test]
+fn reports_render_in_every_format() {
+    let start = Instant::now();
+    let history = vec![sample(20.0, 50, 0, start), sample(40.0, 60, 5, start)];
+    let sys = System::new();
+    let report = build_report(&sys, &history, analyze(&history, &Config::default()));
+
+    let json = serde_json::to_value(&report).unwrap();
+    assert_eq!(json["schema_version"], REPORT_SCHEMA_VERSION);
+    assert_eq!(json["history"].as_array().unwrap().len(), 2);
+    assert_eq!(json["performance"]["cpu_cores"].as_array().unwrap().len(), 2);
+    assert_eq!(json["performance"]["cpu_cores"][0]["peak_percent"], 40.0);
+    assert_eq!(json["health"]["score"], report.health.score);
+    assert!(json["health"]["components"].as_array().unwrap().iter().any(|component| component["factor"] == "memory"));
+
+    let markdown = render_report(&report, ReportFormat::Markdown);
+    assert!(markdown.contains("## Performance Analysis"));
+    assert!(markdown.contains(&format!("## Health Score: {}/100", report.health.score)));
+    let html = render_report(&report, ReportFormat::Html);
+    assert!(html.contains("<polyline"));
+    assert!(html.contains("<h2>Health Score:"));
+}